@@ -0,0 +1,798 @@
+//! ANSI escape sequences.
+
+use std::{
+    env,
+    ffi::OsStr,
+    fmt::{self, Write},
+    mem,
+};
+
+use crate::export::html::write_escaped;
+
+/// RGB values of the 16 basic colors, in the order of SGR color codes.
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// SGR sequence to reset the foreground color.
+pub(crate) const SGR_DEFAULT_FG: &str = "\x1b[39m";
+/// SGR sequence to enable dim (faint) text.
+pub(crate) const SGR_DIM: &str = "\x1b[2m";
+/// SGR sequence to reset bold and dim text.
+pub(crate) const SGR_NORMAL_INTENSITY: &str = "\x1b[22m";
+
+/// Terminal color.
+///
+/// Colors which are not available for the [color level][`ColorLevel`] of the tree are replaced
+/// with the closest available colors.
+///
+/// [`ColorLevel`]: enum.ColorLevel.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Color {
+    /// Black.
+    Black,
+    /// Red.
+    Red,
+    /// Green.
+    Green,
+    /// Yellow.
+    Yellow,
+    /// Blue.
+    Blue,
+    /// Magenta.
+    Magenta,
+    /// Cyan.
+    Cyan,
+    /// White.
+    White,
+    /// Bright black (gray).
+    BrightBlack,
+    /// Bright red.
+    BrightRed,
+    /// Bright green.
+    BrightGreen,
+    /// Bright yellow.
+    BrightYellow,
+    /// Bright blue.
+    BrightBlue,
+    /// Bright magenta.
+    BrightMagenta,
+    /// Bright cyan.
+    BrightCyan,
+    /// Bright white.
+    BrightWhite,
+    /// Color of the 256-color palette.
+    ///
+    /// Indices 0 to 15 are the basic colors, 16 to 231 are the 6x6x6 color cube, and 232 to 255
+    /// are the grayscale ramp.
+    Indexed(u8),
+    /// 24-bit RGB color.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Basic colors in the order of SGR color codes.
+    const BASIC: [Self; 16] = [
+        Self::Black,
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::White,
+        Self::BrightBlack,
+        Self::BrightRed,
+        Self::BrightGreen,
+        Self::BrightYellow,
+        Self::BrightBlue,
+        Self::BrightMagenta,
+        Self::BrightCyan,
+        Self::BrightWhite,
+    ];
+
+    /// Returns the closest color available for the given color level.
+    ///
+    /// Distances between colors are measured in RGB space, assuming the xterm palette.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, ColorLevel};
+    ///
+    /// let orange = Color::Rgb(0xff, 0x87, 0x00);
+    /// assert_eq!(orange.downgrade(ColorLevel::TrueColor), orange);
+    /// assert_eq!(orange.downgrade(ColorLevel::Ansi256), Color::Indexed(208));
+    /// assert_eq!(orange.downgrade(ColorLevel::Basic), Color::Yellow);
+    /// assert_eq!(Color::Indexed(9).downgrade(ColorLevel::Basic), Color::BrightRed);
+    /// ```
+    pub fn downgrade(self, level: ColorLevel) -> Self {
+        match (self, level) {
+            (Self::Indexed(index), ColorLevel::Basic) if index < 16 => {
+                Self::BASIC[usize::from(index)]
+            }
+            (Self::Indexed(index), ColorLevel::Basic) => closest_basic(indexed_rgb(index)),
+            (Self::Rgb(r, g, b), ColorLevel::Basic) => closest_basic((r, g, b)),
+            (Self::Rgb(r, g, b), ColorLevel::Ansi256) => Self::Indexed(closest_indexed((r, g, b))),
+            (color, _) => color,
+        }
+    }
+
+    /// Returns the SGR parameters to set the color as the foreground color.
+    fn fg_params(self) -> Vec<u8> {
+        match self {
+            Self::Indexed(index) => vec![38, 5, index],
+            Self::Rgb(r, g, b) => vec![38, 2, r, g, b],
+            basic => {
+                let index = Self::BASIC
+                    .iter()
+                    .position(|&c| c == basic)
+                    .expect("Should never fail: other colors are basic colors")
+                    as u8;
+                match index {
+                    0..=7 => vec![30 + index],
+                    _ => vec![82 + index],
+                }
+            }
+        }
+    }
+
+    /// Returns the SGR parameters to set the color as the background color.
+    fn bg_params(self) -> Vec<u8> {
+        let mut params = self.fg_params();
+        // `38` (extended) and `30..=37`, `90..=97` (basic) are shifted by 10 for backgrounds.
+        params[0] += 10;
+        params
+    }
+
+    /// Writes the SGR sequence to set the color as the foreground color.
+    pub(crate) fn write_fg<W: fmt::Write>(self, writer: &mut W) -> fmt::Result {
+        write_sgr(writer, &self.fg_params())
+    }
+}
+
+/// Color capability of the destination.
+///
+/// Colors beyond the capability are downgraded to the closest available colors.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum ColorLevel {
+    /// The 16 basic colors.
+    Basic,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+}
+
+impl ColorLevel {
+    /// Guesses the color level of the terminal from the environment variables.
+    ///
+    /// `TrueColor` is chosen if `COLORTERM` environment variable is `truecolor` or `24bit`, and
+    /// `Ansi256` is chosen if `TERM` environment variable contains `256color`.
+    /// Otherwise `Basic` is chosen.
+    pub fn from_environment() -> Self {
+        color_level_for_environment(
+            env::var_os("COLORTERM").as_deref(),
+            env::var_os("TERM").as_deref(),
+        )
+    }
+}
+
+/// Returns the color level for the given `COLORTERM` and `TERM` environment variables.
+fn color_level_for_environment(colorterm: Option<&OsStr>, term: Option<&OsStr>) -> ColorLevel {
+    if colorterm.is_some_and(|v| v == "truecolor" || v == "24bit") {
+        return ColorLevel::TrueColor;
+    }
+    if term.is_some_and(|v| v.to_string_lossy().contains("256color")) {
+        return ColorLevel::Ansi256;
+    }
+    ColorLevel::Basic
+}
+
+/// Returns the squared distance between the colors.
+fn color_distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Returns the basic color closest to the RGB color.
+fn closest_basic(rgb: (u8, u8, u8)) -> Color {
+    let index = (0..16)
+        .min_by_key(|&i| color_distance(BASIC_COLORS[i], rgb))
+        .expect("Should never fail: there are basic colors");
+    Color::BASIC[index]
+}
+
+/// Returns the index of the color cube or the grayscale ramp closest to the RGB color.
+fn closest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&i| color_distance(indexed_rgb(i), rgb))
+        .expect("Should never fail: there are indexed colors")
+}
+
+/// When to emit colors and text styles.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    /// Always emit colors.
+    #[default]
+    Always,
+    /// Never emit colors.
+    Never,
+    /// Emit colors if the environment supports them.
+    ///
+    /// Colors are disabled if `NO_COLOR` environment variable is set to a non-empty value.
+    /// Otherwise, colors are enabled if `CLICOLOR_FORCE` environment variable is set to a
+    /// non-empty value other than `0`, or if the destination is a terminal.
+    Auto,
+}
+
+impl ColorMode {
+    /// Returns whether colors should be emitted to the destination.
+    ///
+    /// `is_terminal` is whether the destination is a terminal, which is only used in `Auto`
+    /// mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::ColorMode;
+    ///
+    /// assert!(ColorMode::Always.is_enabled(false));
+    /// assert!(!ColorMode::Never.is_enabled(true));
+    /// ```
+    pub fn is_enabled(self, is_terminal: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => auto_color_enabled(
+                env::var_os("NO_COLOR").as_deref(),
+                env::var_os("CLICOLOR_FORCE").as_deref(),
+                is_terminal,
+            ),
+        }
+    }
+
+    /// Resolves `Auto` mode into `Always` or `Never` for the destination.
+    ///
+    /// `is_terminal` is whether the destination is a terminal.
+    pub(crate) fn resolve(self, is_terminal: bool) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        if self.is_enabled(is_terminal) {
+            Self::Always
+        } else {
+            Self::Never
+        }
+    }
+}
+
+/// Returns whether colors are enabled in `ColorMode::Auto` mode.
+fn auto_color_enabled(
+    no_color: Option<&OsStr>,
+    clicolor_force: Option<&OsStr>,
+    is_terminal: bool,
+) -> bool {
+    if no_color.is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    is_terminal
+}
+
+/// Text style of labels.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{Color, EdgeConfig, ItemStyle, TextStyle, TreeConfig, TreePrinter};
+///
+/// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+/// let label_style = *TextStyle::new().bold(true).fg(Some(Color::Red));
+/// let style = ItemStyle::last(EdgeConfig::Ascii).with_label_style(label_style);
+/// writer.open_node(style, "foo\n\nbar")?;
+/// let buf = writer.finalize()?;
+///
+/// let expected = "`-- \x1b[1;31mfoo\x1b[22;39m\n\
+///                 \n    \
+///                 \x1b[1;31mbar\x1b[22;39m\n";
+/// assert_eq!(buf, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextStyle {
+    /// Bold.
+    bold: bool,
+    /// Italic.
+    italic: bool,
+    /// Foreground color.
+    fg: Option<Color>,
+    /// Background color.
+    bg: Option<Color>,
+}
+
+impl TextStyle {
+    /// Creates a new plain `TextStyle`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the text is bold.
+    pub fn bold(&mut self, v: bool) -> &mut Self {
+        self.bold = v;
+        self
+    }
+
+    /// Sets whether the text is italic.
+    pub fn italic(&mut self, v: bool) -> &mut Self {
+        self.italic = v;
+        self
+    }
+
+    /// Sets the foreground color.
+    pub fn fg(&mut self, color: Option<Color>) -> &mut Self {
+        self.fg = color;
+        self
+    }
+
+    /// Sets the background color.
+    pub fn bg(&mut self, color: Option<Color>) -> &mut Self {
+        self.bg = color;
+        self
+    }
+
+    /// Returns the style with the colors downgraded to the given color level.
+    pub(crate) fn downgrade(mut self, level: ColorLevel) -> Self {
+        self.fg = self.fg.map(|c| c.downgrade(level));
+        self.bg = self.bg.map(|c| c.downgrade(level));
+        self
+    }
+
+    /// Returns whether the style has no attributes.
+    fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Writes the text wrapped by SGR sequences which enable and disable the style.
+    pub(crate) fn write_styled<W: fmt::Write>(&self, writer: &mut W, text: &str) -> fmt::Result {
+        if self.is_plain() || text.is_empty() {
+            return writer.write_str(text);
+        }
+
+        let mut start = Vec::new();
+        let mut end = Vec::new();
+        if self.bold {
+            start.push(1);
+            end.push(22);
+        }
+        if self.italic {
+            start.push(3);
+            end.push(23);
+        }
+        if let Some(fg) = self.fg {
+            start.extend(fg.fg_params());
+            end.push(39);
+        }
+        if let Some(bg) = self.bg {
+            start.extend(bg.bg_params());
+            end.push(49);
+        }
+        write_sgr(writer, &start)?;
+        writer.write_str(text)?;
+        write_sgr(writer, &end)
+    }
+}
+
+/// Writes an SGR sequence with the given parameters.
+fn write_sgr<W: fmt::Write>(writer: &mut W, params: &[u8]) -> fmt::Result {
+    writer.write_str("\x1b[")?;
+    for (i, param) in params.iter().enumerate() {
+        if i != 0 {
+            writer.write_char(';')?;
+        }
+        write!(writer, "{}", param)?;
+    }
+    writer.write_char('m')
+}
+
+/// Color set by an SGR sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SgrColor {
+    /// One of the 16 basic colors, in the order of SGR color codes.
+    Basic(u8),
+    /// Color of the 256-color palette.
+    Indexed(u8),
+    /// 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl SgrColor {
+    /// Returns the CSS color.
+    fn css(self) -> String {
+        match self {
+            Self::Basic(index) => css_rgb(BASIC_COLORS[usize::from(index)]),
+            Self::Indexed(index) => css_rgb(indexed_rgb(index)),
+            Self::Rgb(r, g, b) => css_rgb((r, g, b)),
+        }
+    }
+}
+
+/// Text attributes set by SGR sequences.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SgrState {
+    /// Bold.
+    pub(crate) bold: bool,
+    /// Dim.
+    pub(crate) dim: bool,
+    /// Italic.
+    pub(crate) italic: bool,
+    /// Underline.
+    pub(crate) underline: bool,
+    /// Reverse video.
+    pub(crate) reverse: bool,
+    /// Foreground color.
+    pub(crate) fg: Option<SgrColor>,
+    /// Background color.
+    pub(crate) bg: Option<SgrColor>,
+}
+
+impl SgrState {
+    /// Applies the parameters of an SGR sequence.
+    pub(crate) fn apply(&mut self, params: &str) {
+        let mut params = params.split(';').map(|p| {
+            if p.is_empty() {
+                Some(0)
+            } else {
+                p.parse::<u8>().ok()
+            }
+        });
+        while let Some(param) = params.next() {
+            match param {
+                Some(0) => *self = Self::default(),
+                Some(1) => self.bold = true,
+                Some(2) => self.dim = true,
+                Some(3) => self.italic = true,
+                Some(4) => self.underline = true,
+                Some(7) => self.reverse = true,
+                Some(22) => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                Some(23) => self.italic = false,
+                Some(24) => self.underline = false,
+                Some(27) => self.reverse = false,
+                Some(n @ 30..=37) => self.fg = Some(SgrColor::Basic(n - 30)),
+                Some(38) => self.fg = extended_color(&mut params),
+                Some(39) => self.fg = None,
+                Some(n @ 40..=47) => self.bg = Some(SgrColor::Basic(n - 40)),
+                Some(48) => self.bg = extended_color(&mut params),
+                Some(49) => self.bg = None,
+                Some(n @ 90..=97) => self.fg = Some(SgrColor::Basic(n - 82)),
+                Some(n @ 100..=107) => self.bg = Some(SgrColor::Basic(n - 92)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the CSS declarations for the state.
+    fn css(&self) -> String {
+        let mut css = String::new();
+        let fg = self.fg.map(SgrColor::css);
+        let bg = self.bg.map(SgrColor::css);
+        let (fg, bg) = if self.reverse {
+            (
+                Some(bg.as_deref().unwrap_or("Canvas")),
+                Some(fg.as_deref().unwrap_or("CanvasText")),
+            )
+        } else {
+            (fg.as_deref(), bg.as_deref())
+        };
+        if let Some(fg) = fg {
+            write!(css, "color:{};", fg).expect("Should never fail: writing to `String`");
+        }
+        if let Some(bg) = bg {
+            write!(css, "background-color:{};", bg)
+                .expect("Should never fail: writing to `String`");
+        }
+        if self.bold {
+            css.push_str("font-weight:bold;");
+        }
+        if self.dim {
+            css.push_str("opacity:0.5;");
+        }
+        if self.italic {
+            css.push_str("font-style:italic;");
+        }
+        if self.underline {
+            css.push_str("text-decoration:underline;");
+        }
+        css
+    }
+}
+
+/// Parses the rest of an extended color (`38;5;n` or `38;2;r;g;b`).
+fn extended_color(params: &mut impl Iterator<Item = Option<u8>>) -> Option<SgrColor> {
+    match params.next()? {
+        Some(5) => {
+            let index = params.next()??;
+            Some(SgrColor::Indexed(index))
+        }
+        Some(2) => {
+            let r = params.next()??;
+            let g = params.next()??;
+            let b = params.next()??;
+            Some(SgrColor::Rgb(r, g, b))
+        }
+        _ => None,
+    }
+}
+
+/// Part of text with escape sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnsiSegment<'a> {
+    /// Plain text.
+    Text(&'a str),
+    /// Parameters of an SGR sequence.
+    Sgr(&'a str),
+}
+
+/// Iterator splitting text into plain text and SGR sequences.
+///
+/// Other escape sequences are removed.
+#[derive(Debug, Clone)]
+pub(crate) struct AnsiSegments<'a> {
+    /// Rest of the text.
+    rest: &'a str,
+}
+
+impl<'a> AnsiSegments<'a> {
+    /// Creates a new `AnsiSegments`.
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { rest: input }
+    }
+}
+
+impl<'a> Iterator for AnsiSegments<'a> {
+    type Item = AnsiSegment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            match self.rest.find('\x1b') {
+                Some(0) => {}
+                Some(esc) => {
+                    let (text, rest) = self.rest.split_at(esc);
+                    self.rest = rest;
+                    return Some(AnsiSegment::Text(text));
+                }
+                None => return Some(AnsiSegment::Text(mem::take(&mut self.rest))),
+            }
+            let seq = &self.rest[1..];
+            let body = match seq.strip_prefix('[') {
+                Some(body) => body,
+                None => {
+                    // Not a CSI sequence. Drop the escape character.
+                    self.rest = seq;
+                    continue;
+                }
+            };
+            let end = match body.find(|c: char| ('\x40'..='\x7e').contains(&c)) {
+                Some(end) => end,
+                None => {
+                    // Unterminated sequence.
+                    self.rest = "";
+                    return None;
+                }
+            };
+            self.rest = &body[(end + 1)..];
+            if body[end..].starts_with('m') {
+                return Some(AnsiSegment::Sgr(&body[..end]));
+            }
+        }
+    }
+}
+
+/// Returns the RGB value of the color of the 256-color palette.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_COLORS[usize::from(index)],
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { v * 40 + 55 };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let v = (index - 232) * 10 + 8;
+            (v, v, v)
+        }
+    }
+}
+
+/// Returns the CSS color of the RGB value.
+fn css_rgb((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Converts text with SGR escape sequences into HTML.
+///
+/// Styled text is wrapped with `<span style="...">` elements, and HTML special characters are
+/// escaped.
+/// Other escape sequences are removed.
+/// The result is intended to be put into a `<pre>` element.
+///
+/// This is useful to put trees rendered with colors (such as highlighted labels) into HTML
+/// reports.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{ansi_to_html, Highlight, RenderConfig, TreeNode};
+///
+/// let mut root = TreeNode::new("root");
+/// root.push_child(TreeNode::new("<match>"));
+/// root.highlight("match", &Highlight::Ansi);
+///
+/// let rendered = root.render(String::new(), &RenderConfig::new())?;
+/// let got = ansi_to_html(&rendered);
+///
+/// let expected = "root\n\
+///                 `-- <span style=\"color:Canvas;background-color:CanvasText;\">\
+///                 &lt;match&gt;</span>\n";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+pub fn ansi_to_html(input: &str) -> String {
+    let mut html = String::new();
+    write_ansi_as_html(&mut html, input).expect("Should never fail: writing to `String`");
+    html
+}
+
+/// Writes the text with SGR escape sequences as HTML.
+fn write_ansi_as_html<W: fmt::Write>(writer: &mut W, input: &str) -> fmt::Result {
+    let mut state = SgrState::default();
+    let mut span_open = false;
+    for segment in AnsiSegments::new(input) {
+        let params = match segment {
+            AnsiSegment::Text(text) => {
+                write_escaped(writer, text)?;
+                continue;
+            }
+            AnsiSegment::Sgr(params) => params,
+        };
+
+        let prev = state.clone();
+        state.apply(params);
+        if state == prev {
+            continue;
+        }
+        if span_open {
+            writer.write_str("</span>")?;
+        }
+        let css = state.css();
+        span_open = !css.is_empty();
+        if span_open {
+            write!(writer, "<span style=\"{}\">", css)?;
+        }
+    }
+    if span_open {
+        writer.write_str("</span>")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_and_reset() {
+        let got = ansi_to_html("\x1b[1;31mA\x1b[38;5;196;48;2;0;0;255mB\x1b[mC\x1b[2KD");
+        let expected = "<span style=\"color:#cd0000;font-weight:bold;\">A</span>\
+                        <span style=\"color:#ff0000;background-color:#0000ff;font-weight:bold;\">\
+                        B</span>CD";
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn color_sequences() -> fmt::Result {
+        let mut buf = String::new();
+        Color::Red.write_fg(&mut buf)?;
+        Color::BrightWhite.write_fg(&mut buf)?;
+        Color::Indexed(208).write_fg(&mut buf)?;
+        Color::Rgb(1, 2, 3).write_fg(&mut buf)?;
+        assert_eq!(buf, "\x1b[31m\x1b[97m\x1b[38;5;208m\x1b[38;2;1;2;3m");
+        Ok(())
+    }
+
+    #[test]
+    fn text_styles() -> fmt::Result {
+        let mut buf = String::new();
+        TextStyle::new()
+            .italic(true)
+            .bg(Some(Color::BrightBlue))
+            .write_styled(&mut buf, "foo")?;
+        TextStyle::new().write_styled(&mut buf, "bar")?;
+        TextStyle::new()
+            .bg(Some(Color::Indexed(17)))
+            .write_styled(&mut buf, "baz")?;
+        assert_eq!(buf, "\x1b[3;104mfoo\x1b[23;49mbar\x1b[48;5;17mbaz\x1b[49m");
+        Ok(())
+    }
+
+    #[test]
+    fn auto_color_mode() {
+        let set = |v: &'static str| Some(OsStr::new(v));
+        assert!(auto_color_enabled(None, None, true));
+        assert!(!auto_color_enabled(None, None, false));
+        assert!(!auto_color_enabled(set("1"), None, true));
+        assert!(auto_color_enabled(set(""), None, true));
+        assert!(auto_color_enabled(None, set("1"), false));
+        assert!(!auto_color_enabled(None, set("0"), false));
+        assert!(!auto_color_enabled(set("1"), set("1"), true));
+    }
+
+    #[test]
+    fn color_levels() {
+        let set = |v: &'static str| Some(OsStr::new(v));
+        assert_eq!(
+            color_level_for_environment(set("truecolor"), set("xterm-256color")),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            color_level_for_environment(None, set("xterm-256color")),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(
+            color_level_for_environment(None, set("xterm")),
+            ColorLevel::Basic
+        );
+        assert_eq!(
+            Color::Rgb(0x80, 0x80, 0x80).downgrade(ColorLevel::Ansi256),
+            Color::Indexed(244)
+        );
+        assert_eq!(
+            Color::Indexed(232).downgrade(ColorLevel::Basic),
+            Color::Black
+        );
+    }
+
+    #[test]
+    fn palette() {
+        assert_eq!(css_rgb(indexed_rgb(9)), "#ff0000");
+        assert_eq!(css_rgb(indexed_rgb(16)), "#000000");
+        assert_eq!(css_rgb(indexed_rgb(208)), "#ff8700");
+        assert_eq!(css_rgb(indexed_rgb(231)), "#ffffff");
+        assert_eq!(css_rgb(indexed_rgb(232)), "#080808");
+        assert_eq!(css_rgb(indexed_rgb(255)), "#eeeeee");
+    }
+}
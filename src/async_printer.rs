@@ -0,0 +1,283 @@
+//! Async tree printers.
+
+use std::{
+    fmt,
+    future::poll_fn,
+    io, mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    config::{ItemStyle, TreeConfig},
+    tree_printer::{Result, TreePrinter},
+};
+
+/// Destination of the output of an [`AsyncBackendPrinter`].
+trait AsyncBackend {
+    /// Attempts to write the bytes, and returns the number of the written bytes.
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to flush the destination.
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// Tree printer writing the output of each method to the async backend before the method
+/// returns.
+///
+/// Mirrored and bottom-up trees are buffered until finalized.
+struct AsyncBackendPrinter<B> {
+    /// Tree printer writing to the pending output.
+    printer: TreePrinter<String>,
+    /// Backend.
+    backend: B,
+}
+
+impl<B: AsyncBackend> AsyncBackendPrinter<B> {
+    /// Creates a new `AsyncBackendPrinter`.
+    fn new(backend: B, opts: TreeConfig) -> Self {
+        Self {
+            printer: TreePrinter::new(String::new(), opts),
+            backend,
+        }
+    }
+
+    /// Opens a new node with the given content.
+    async fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content)?;
+        self.write_pending().await
+    }
+
+    /// Closes a node.
+    async fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node()?;
+        self.write_pending().await
+    }
+
+    /// Finishes writing the tree, flushes the backend, and returns it.
+    async fn finalize(mut self) -> Result<B> {
+        self.printer.finish()?;
+        self.write_pending().await?;
+        let backend = &mut self.backend;
+        poll_fn(|cx| backend.poll_flush(cx)).await?;
+        Ok(self.backend)
+    }
+
+    /// Writes the pending output to the backend.
+    async fn write_pending(&mut self) -> Result<()> {
+        let pending = mem::take(self.printer.inner_mut());
+        let backend = &mut self.backend;
+        let mut buf = pending.as_bytes();
+        while !buf.is_empty() {
+            let len = poll_fn(|cx| backend.poll_write(cx, buf)).await?;
+            if len == 0 {
+                return Err(io::Error::from(io::ErrorKind::WriteZero).into());
+            }
+            buf = &buf[len..];
+        }
+        Ok(())
+    }
+}
+
+/// Backend writing to a `tokio::io::AsyncWrite`.
+#[cfg(feature = "tokio")]
+struct TokioBackend<W>(W);
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> AsyncBackend for TokioBackend<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Tree printer writing to a `tokio::io::AsyncWrite`.
+///
+/// This is the same as [`IoTreePrinter`] except that the methods are async and the tree is
+/// written to the async writer.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{AsyncTreePrinter, EdgeConfig, ItemStyle, TreeConfig};
+///
+/// # tokio::runtime::Builder::new_current_thread().build()?.block_on(async {
+/// let mut printer = AsyncTreePrinter::new(Vec::new(), TreeConfig::new());
+/// printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo").await?;
+/// printer.close_node().await?;
+/// printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar").await?;
+/// let buf = printer.finalize().await?;
+///
+/// assert_eq!(buf, b"|-- foo\n`-- bar\n");
+/// # plaintextree::Result::Ok(())
+/// # })?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`IoTreePrinter`]: struct.IoTreePrinter.html
+#[cfg(feature = "tokio")]
+pub struct AsyncTreePrinter<W> {
+    /// Tree printer.
+    printer: AsyncBackendPrinter<TokioBackend<W>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> AsyncTreePrinter<W> {
+    /// Creates a new `AsyncTreePrinter`.
+    pub fn new(writer: W, opts: TreeConfig) -> Self {
+        Self {
+            printer: AsyncBackendPrinter::new(TokioBackend(writer), opts),
+        }
+    }
+
+    /// Opens a new node with the given content.
+    ///
+    /// See [`TreePrinter::open_node()`].
+    ///
+    /// [`TreePrinter::open_node()`]: struct.TreePrinter.html#method.open_node
+    pub async fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content).await
+    }
+
+    /// Closes a node.
+    ///
+    /// See [`TreePrinter::close_node()`].
+    ///
+    /// [`TreePrinter::close_node()`]: struct.TreePrinter.html#method.close_node
+    pub async fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node().await
+    }
+
+    /// Finishes writing the tree, flushes the inner writer, and returns it.
+    pub async fn finalize(self) -> Result<W> {
+        Ok(self.printer.finalize().await?.0)
+    }
+}
+
+/// Backend writing to a `futures_io::AsyncWrite`.
+#[cfg(feature = "futures-io")]
+struct FuturesBackend<W>(W);
+
+#[cfg(feature = "futures-io")]
+impl<W: futures_io::AsyncWrite + Unpin> AsyncBackend for FuturesBackend<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Tree printer writing to a `futures_io::AsyncWrite`.
+///
+/// This is the same as [`AsyncTreePrinter`] except that the tree is written to
+/// `futures_io::AsyncWrite`, which does not depend on any specific async runtime.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{EdgeConfig, FuturesTreePrinter, ItemStyle, TreeConfig};
+///
+/// # async fn print() -> plaintextree::Result<Vec<u8>> {
+/// let mut printer = FuturesTreePrinter::new(Vec::new(), TreeConfig::new());
+/// printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo").await?;
+/// printer.close_node().await?;
+/// printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar").await?;
+/// let buf = printer.finalize().await?;
+///
+/// assert_eq!(buf, b"|-- foo\n`-- bar\n");
+/// # Ok(buf)
+/// # }
+/// ```
+///
+/// [`AsyncTreePrinter`]: struct.AsyncTreePrinter.html
+#[cfg(feature = "futures-io")]
+pub struct FuturesTreePrinter<W> {
+    /// Tree printer.
+    printer: AsyncBackendPrinter<FuturesBackend<W>>,
+}
+
+#[cfg(feature = "futures-io")]
+impl<W: futures_io::AsyncWrite + Unpin> FuturesTreePrinter<W> {
+    /// Creates a new `FuturesTreePrinter`.
+    pub fn new(writer: W, opts: TreeConfig) -> Self {
+        Self {
+            printer: AsyncBackendPrinter::new(FuturesBackend(writer), opts),
+        }
+    }
+
+    /// Opens a new node with the given content.
+    ///
+    /// See [`TreePrinter::open_node()`].
+    ///
+    /// [`TreePrinter::open_node()`]: struct.TreePrinter.html#method.open_node
+    pub async fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content).await
+    }
+
+    /// Closes a node.
+    ///
+    /// See [`TreePrinter::close_node()`].
+    ///
+    /// [`TreePrinter::close_node()`]: struct.TreePrinter.html#method.close_node
+    pub async fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node().await
+    }
+
+    /// Finishes writing the tree, flushes the inner writer, and returns it.
+    pub async fn finalize(self) -> Result<W> {
+        Ok(self.printer.finalize().await?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::EdgeConfig;
+
+    /// Returns the styles and labels of the test tree.
+    fn test_items() -> [(ItemStyle, &'static str); 2] {
+        [
+            (ItemStyle::non_last(EdgeConfig::Ascii), "foo\nfoo2"),
+            (ItemStyle::last(EdgeConfig::Ascii), "bar"),
+        ]
+    }
+
+    /// Returns the test tree emitted by the sync printer.
+    fn expected_output() -> Result<String> {
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        for (style, label) in test_items() {
+            printer.open_node(style, label)?;
+        }
+        printer.finalize()
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_same_as_sync_printer() -> Result<()> {
+        let mut printer = AsyncTreePrinter::new(Vec::new(), TreeConfig::new());
+        for (style, label) in test_items() {
+            printer.open_node(style, label).await?;
+        }
+        let got = printer.finalize().await?;
+        assert_eq!(got, expected_output()?.as_bytes());
+        Ok(())
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[tokio::test]
+    async fn futures_same_as_sync_printer() -> Result<()> {
+        let mut printer = FuturesTreePrinter::new(Vec::new(), TreeConfig::new());
+        for (style, label) in test_items() {
+            printer.open_node(style, label).await?;
+        }
+        let got = printer.finalize().await?;
+        assert_eq!(got, expected_output()?.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+//! Benchmark comparison tree.
+
+#[cfg(feature = "ansi")]
+use crate::ansi::{Color, ColorMode, TextStyle};
+use crate::{
+    config::{ColumnAlign, EdgeConfig, MetadataColumn, RenderConfigBuilder, TreeConfig},
+    tree_node::TreeNode,
+    tree_printer::Result,
+};
+
+/// A single benchmark result with baseline and current values.
+#[derive(Debug, Clone)]
+pub struct Bench {
+    /// Name.
+    name: String,
+    /// Baseline value.
+    baseline: f64,
+    /// Current value.
+    current: f64,
+}
+
+impl Bench {
+    /// Creates a new `Bench`.
+    pub fn new(name: impl Into<String>, baseline: f64, current: f64) -> Self {
+        Self {
+            name: name.into(),
+            baseline,
+            current,
+        }
+    }
+
+    /// Returns the relative change from the baseline in percent.
+    ///
+    /// Returns `None` if the baseline is zero.
+    pub fn delta_percent(&self) -> Option<f64> {
+        if self.baseline == 0.0 {
+            return None;
+        }
+        Some((self.current - self.baseline) / self.baseline * 100.0)
+    }
+}
+
+/// Benchmark group entry.
+#[derive(Debug, Clone)]
+enum BenchEntry {
+    /// Nested group.
+    Group(BenchGroup),
+    /// Benchmark.
+    Bench(Bench),
+}
+
+/// A named group of benchmarks and nested groups.
+#[derive(Debug, Clone)]
+pub struct BenchGroup {
+    /// Name.
+    name: String,
+    /// Entries.
+    entries: Vec<BenchEntry>,
+}
+
+impl BenchGroup {
+    /// Creates a new empty `BenchGroup`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds a nested group.
+    pub fn push_group(&mut self, group: BenchGroup) -> &mut Self {
+        self.entries.push(BenchEntry::Group(group));
+        self
+    }
+
+    /// Adds a benchmark.
+    pub fn push_bench(&mut self, bench: Bench) -> &mut Self {
+        self.entries.push(BenchEntry::Bench(bench));
+        self
+    }
+}
+
+/// Benchmark comparison renderer.
+///
+/// Renders benchmark groups as a tree, with baseline and current values and their delta in
+/// aligned columns.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{Bench, BenchComparison, BenchGroup};
+///
+/// let mut parse = BenchGroup::new("parse");
+/// parse
+///     .push_bench(Bench::new("small", 12.0, 11.0))
+///     .push_bench(Bench::new("large", 120.0, 130.0));
+/// let mut render = BenchGroup::new("render");
+/// render.push_bench(Bench::new("tree", 1.5, 1.5));
+///
+/// let got = BenchComparison::new().unit("ns").render(&[parse, render])?;
+///
+/// let expected = "\
+/// benchmark       baseline    current   delta
+/// |-- parse
+/// |   |-- small   12.00 ns   11.00 ns  -8.33%
+/// |   `-- large  120.00 ns  130.00 ns  +8.33%
+/// `-- render
+///     `-- tree     1.50 ns    1.50 ns  +0.00%
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BenchComparison {
+    /// Edge config.
+    edge: EdgeConfig,
+    /// Title of the name column.
+    title: String,
+    /// Unit of the values.
+    unit: String,
+    /// Number of digits after the decimal point.
+    precision: usize,
+    /// Tree config.
+    tree_config: TreeConfig,
+    /// When to color the delta column.
+    #[cfg(feature = "ansi")]
+    color_mode: ColorMode,
+    /// Style of deltas of regressions.
+    #[cfg(feature = "ansi")]
+    regression_style: TextStyle,
+    /// Style of deltas of improvements.
+    #[cfg(feature = "ansi")]
+    improvement_style: TextStyle,
+}
+
+impl Default for BenchComparison {
+    fn default() -> Self {
+        Self {
+            edge: EdgeConfig::default(),
+            title: "benchmark".to_owned(),
+            unit: String::new(),
+            precision: 2,
+            tree_config: TreeConfig::new(),
+            #[cfg(feature = "ansi")]
+            color_mode: ColorMode::Never,
+            #[cfg(feature = "ansi")]
+            regression_style: *TextStyle::new().fg(Some(Color::Red)),
+            #[cfg(feature = "ansi")]
+            improvement_style: *TextStyle::new().fg(Some(Color::Green)),
+        }
+    }
+}
+
+impl BenchComparison {
+    /// Creates a new `BenchComparison` with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the edge config.
+    ///
+    /// The value is `EdgeConfig::Ascii` by default.
+    pub fn edge(&mut self, edge: EdgeConfig) -> &mut Self {
+        self.edge = edge;
+        self
+    }
+
+    /// Sets the title of the name column.
+    ///
+    /// The value is `"benchmark"` by default.
+    pub fn title(&mut self, title: impl Into<String>) -> &mut Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the unit appended to the baseline and current values.
+    ///
+    /// The value is empty by default.
+    pub fn unit(&mut self, unit: impl Into<String>) -> &mut Self {
+        self.unit = unit.into();
+        self
+    }
+
+    /// Sets the number of digits after the decimal point.
+    ///
+    /// The value is `2` by default.
+    pub fn precision(&mut self, precision: usize) -> &mut Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the tree config to render the tree with.
+    ///
+    /// This is useful to set the width oracle and the width of ambiguous width characters.
+    ///
+    /// The value is `TreeConfig::new()` by default.
+    pub fn tree_config(&mut self, tree_config: TreeConfig) -> &mut Self {
+        self.tree_config = tree_config;
+        self
+    }
+
+    /// Sets when to color the delta column.
+    ///
+    /// Regressions (positive deltas) and improvements (negative deltas) are styled by
+    /// [`regression_style()`][regression_style] and [`improvement_style()`][improvement_style].
+    /// `ColorMode::Auto` treats the destination as not a terminal, since the output is returned
+    /// as a string.
+    ///
+    /// The value is `ColorMode::Never` by default.
+    ///
+    /// [regression_style]: #method.regression_style
+    /// [improvement_style]: #method.improvement_style
+    #[cfg(feature = "ansi")]
+    pub fn color_mode(&mut self, mode: ColorMode) -> &mut Self {
+        self.color_mode = mode;
+        self
+    }
+
+    /// Sets the style of deltas of regressions.
+    ///
+    /// The value is the red foreground by default.
+    #[cfg(feature = "ansi")]
+    pub fn regression_style(&mut self, style: TextStyle) -> &mut Self {
+        self.regression_style = style;
+        self
+    }
+
+    /// Sets the style of deltas of improvements.
+    ///
+    /// The value is the green foreground by default.
+    #[cfg(feature = "ansi")]
+    pub fn improvement_style(&mut self, style: TextStyle) -> &mut Self {
+        self.improvement_style = style;
+        self
+    }
+
+    /// Renders the given groups.
+    pub fn render(&self, groups: &[BenchGroup]) -> Result<String> {
+        // The root row is the header row.
+        let mut root = TreeNode::new(self.title.clone());
+        root.set_metadata(vec!["baseline", "current", "delta"]);
+        *root.children_mut() = groups.iter().map(|group| self.group_node(group)).collect();
+
+        let config = RenderConfigBuilder::new()
+            .edge(self.edge.clone())
+            .tree_config(self.tree_config.clone())
+            .metadata_columns(vec![MetadataColumn::new(ColumnAlign::Right); 3])
+            .build();
+        root.render_table(String::new(), &config, &[])
+    }
+
+    /// Returns the node of the group.
+    fn group_node(&self, group: &BenchGroup) -> TreeNode {
+        let mut node = TreeNode::new(group.name.clone());
+        *node.children_mut() = group
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                BenchEntry::Group(group) => self.group_node(group),
+                BenchEntry::Bench(bench) => self.bench_node(bench),
+            })
+            .collect();
+        node
+    }
+
+    /// Returns the node of the benchmark.
+    fn bench_node(&self, bench: &Bench) -> TreeNode {
+        let mut node = TreeNode::new(bench.name.clone());
+        node.set_metadata(vec![
+            self.format_value(bench.baseline),
+            self.format_value(bench.current),
+            self.delta_cell(bench),
+        ]);
+        node
+    }
+
+    /// Formats the delta of the benchmark, styled if colors are enabled.
+    fn delta_cell(&self, bench: &Bench) -> String {
+        let delta = match bench.delta_percent() {
+            Some(delta) => delta,
+            None => return "n/a".to_owned(),
+        };
+        let formatted = format!("{:+.*}%", self.precision, delta);
+        #[cfg(feature = "ansi")]
+        {
+            // Use the formatted value so that tiny deltas shown as zero are not colored.
+            let rounded = formatted
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .unwrap_or(delta);
+            let style = if rounded > 0.0 {
+                self.regression_style
+            } else if rounded < 0.0 {
+                self.improvement_style
+            } else {
+                TextStyle::new()
+            };
+            if self.color_mode.is_enabled(false) {
+                let mut styled = String::new();
+                style
+                    .write_styled(&mut styled, &formatted)
+                    .expect("Should never fail: writing to `String`");
+                return styled;
+            }
+        }
+        formatted
+    }
+
+    /// Formats a value with the unit.
+    fn format_value(&self, value: f64) -> String {
+        if self.unit.is_empty() {
+            format!("{:.*}", self.precision, value)
+        } else {
+            format!("{:.*} {}", self.precision, value, self.unit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{config::TreeConfigBuilder, width::AmbiWidth};
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn colored_delta() -> Result<()> {
+        let mut group = BenchGroup::new("g");
+        group
+            .push_bench(Bench::new("slow", 1.0, 2.0))
+            .push_bench(Bench::new("fast", 2.0, 1.0))
+            .push_bench(Bench::new("same", 1.0, 1.0));
+
+        let got = BenchComparison::new()
+            .precision(0)
+            .color_mode(ColorMode::Always)
+            .improvement_style(*TextStyle::new().bold(true))
+            .render(&[group])?;
+
+        let expected = "benchmark     baseline  current  delta\n\
+                        `-- g\n    \
+                            |-- slow         1        2  \x1b[31m+100%\x1b[39m\n    \
+                            |-- fast         2        1   \x1b[1m-50%\x1b[22m\n    \
+                            `-- same         1        1    +0%\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn zero_baseline() -> Result<()> {
+        let mut group = BenchGroup::new("g");
+        group.push_bench(Bench::new("new", 0.0, 3.0));
+
+        let got = BenchComparison::new()
+            .edge(EdgeConfig::UnicodeSingleWidth)
+            .title("bench")
+            .precision(1)
+            .render(&[group.clone()])?;
+
+        let expected = "bench        baseline  current  delta\n\
+                        └── g\n    \
+                            └── new       0.0      3.0    n/a\n";
+        assert_eq!(got, expected);
+
+        let got = BenchComparison::new()
+            .edge(EdgeConfig::UnicodeSingleWidth)
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .ambiguous_width(AmbiWidth::Wide)
+                    .build(),
+            )
+            .title("bench")
+            .precision(1)
+            .render(&[group])?;
+
+        // Box drawing characters are double width.
+        let expected = "bench              baseline  current  delta\n\
+                        └── g\n       \
+                               └── new       0.0      3.0    n/a\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+}
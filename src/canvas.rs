@@ -0,0 +1,470 @@
+//! Character grid buffer.
+
+use std::fmt;
+
+use crate::{config::TreeConfig, width};
+
+/// Vertical alignment of canvases put side by side.
+///
+/// See [`Canvas::side_by_side()`].
+///
+/// [`Canvas::side_by_side()`]: struct.Canvas.html#method.side_by_side
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalAlign {
+    /// Aligned to the top.
+    #[default]
+    Top,
+    /// Centered, rounding toward the top.
+    Middle,
+    /// Aligned to the bottom.
+    Bottom,
+}
+
+/// Cell of a canvas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cell {
+    /// Blank cell, shown as a space.
+    Blank,
+    /// Grapheme cluster with the escape sequences around it, and its display width.
+    Text(String, usize),
+    /// Cell covered by the wide grapheme cluster on the left.
+    Continuation,
+}
+
+/// Column-addressable character grid.
+///
+/// Text written to the canvas is placed on the grid of cells, where each cell has a single
+/// column width and wide characters occupy multiple cells.
+/// This is useful to compose rendered trees (overlays, cropping, side-by-side placement)
+/// before flushing them to text.
+///
+/// The canvas implements `fmt::Write`, so trees can be rendered into it directly.
+/// Written text is placed at the cursor, and `\n` moves the cursor to the head of the next row.
+/// Carriage returns are ignored, and escape sequences are kept with the adjacent characters.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{Canvas, RenderConfig, TreeConfig, TreeNode};
+///
+/// let mut left = TreeNode::new("old");
+/// left.push_child(TreeNode::new("foo"));
+/// let mut right = TreeNode::new("new");
+/// right.push_child(TreeNode::new("foo"));
+/// right.push_child(TreeNode::new("bar"));
+///
+/// let config = RenderConfig::new();
+/// let mut canvas = left.render(Canvas::new(&TreeConfig::new()), &config)?;
+/// let right = right.render(Canvas::new(&TreeConfig::new()), &config)?;
+/// canvas.put_canvas(canvas.width() + 2, 0, &right);
+///
+/// let expected = "old      new\n\
+///                 `-- foo  |-- foo\n\
+///                 \x20        `-- bar\n";
+/// assert_eq!(canvas.to_string(), expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    /// Rows of cells.
+    rows: Vec<Vec<Cell>>,
+    /// Column of the cursor.
+    cursor_column: usize,
+    /// Row of the cursor.
+    cursor_row: usize,
+    /// Escape sequences and zero-width characters to be put before the next character.
+    pending: String,
+    /// Options to measure and emit the text.
+    opts: TreeConfig,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new(&TreeConfig::default())
+    }
+}
+
+impl Canvas {
+    /// Creates a new empty canvas.
+    ///
+    /// The widths of characters are measured with the tree config, and rows are separated by
+    /// its newline when the canvas is shown.
+    pub fn new(opts: &TreeConfig) -> Self {
+        Self {
+            rows: Vec::new(),
+            cursor_column: 0,
+            cursor_row: 0,
+            pending: String::new(),
+            opts: opts.clone(),
+        }
+    }
+
+    /// Returns the number of columns.
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the text at the given cell.
+    ///
+    /// Returns `None` for blank cells, cells out of the canvas, and cells covered by wide
+    /// characters on the left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Canvas, TreeConfig};
+    ///
+    /// let mut canvas = Canvas::new(&TreeConfig::new());
+    /// canvas.put_str(1, 1, "ab");
+    ///
+    /// assert_eq!(canvas.get(2, 1), Some("b"));
+    /// assert_eq!(canvas.get(0, 1), None);
+    /// assert_eq!(canvas.get(0, 5), None);
+    /// ```
+    pub fn get(&self, column: usize, row: usize) -> Option<&str> {
+        match self.rows.get(row)?.get(column)? {
+            Cell::Text(text, _) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Puts the text at the given position, overwriting the cells.
+    ///
+    /// Each line of the text is put at the given column of the successive rows.
+    /// The cursor for `fmt::Write` is not moved.
+    pub fn put_str(&mut self, column: usize, row: usize, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            let mut column = column;
+            let mut pending = String::new();
+            for (unit, is_escape) in width::units(line) {
+                if unit == "\r" {
+                    continue;
+                }
+                let unit_width = if is_escape {
+                    0
+                } else {
+                    self.opts.str_width(unit)
+                };
+                if unit_width == 0 {
+                    pending.push_str(unit);
+                    continue;
+                }
+                pending.push_str(unit);
+                self.put_cell(column, row + i, Cell::Text(pending, unit_width));
+                pending = String::new();
+                column += unit_width;
+            }
+            if !pending.is_empty() {
+                self.append_to_last(column, row + i, &pending);
+            }
+        }
+    }
+
+    /// Puts the other canvas at the given position.
+    ///
+    /// Blank cells of the other canvas are transparent.
+    pub fn put_canvas(&mut self, column: usize, row: usize, other: &Self) {
+        for (y, cells) in other.rows.iter().enumerate() {
+            for (x, cell) in cells.iter().enumerate() {
+                if let Cell::Text(..) = cell {
+                    self.put_cell(column + x, row + y, cell.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns a new canvas with the given canvases put next to each other.
+    ///
+    /// The gutter is put on every row between the adjacent canvases, and shorter canvases are
+    /// aligned vertically with the given alignment.
+    /// The options of the first canvas are used for the new canvas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Canvas, RenderConfig, TreeConfig, TreeNode, VerticalAlign};
+    ///
+    /// let mut before = TreeNode::new("before");
+    /// before.push_child(TreeNode::new("foo"));
+    /// let mut after = TreeNode::new("after");
+    /// after.push_child(TreeNode::new("foo"));
+    /// after.push_child(TreeNode::new("bar"));
+    ///
+    /// let config = RenderConfig::new();
+    /// let canvases = [
+    ///     before.render(Canvas::new(&TreeConfig::new()), &config)?,
+    ///     after.render(Canvas::new(&TreeConfig::new()), &config)?,
+    /// ];
+    /// let canvas = Canvas::side_by_side(&canvases, " | ", VerticalAlign::Bottom);
+    ///
+    /// let expected = "\x20       | after\n\
+    ///                 before  | |-- foo\n\
+    ///                 `-- foo | `-- bar\n";
+    /// assert_eq!(canvas.to_string(), expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn side_by_side(canvases: &[Self], gutter: &str, align: VerticalAlign) -> Self {
+        let mut combined = match canvases.first() {
+            Some(first) => Self::new(&first.opts),
+            None => return Self::default(),
+        };
+        let height = canvases.iter().map(Self::height).max().unwrap_or(0);
+        let gutter_width = combined.opts.str_width(gutter);
+        let mut column = 0;
+        for (i, canvas) in canvases.iter().enumerate() {
+            if i != 0 {
+                for row in 0..height {
+                    combined.put_str(column, row, gutter);
+                }
+                column += gutter_width;
+            }
+            let margin = height - canvas.height();
+            let row = match align {
+                VerticalAlign::Top => 0,
+                VerticalAlign::Middle => margin / 2,
+                VerticalAlign::Bottom => margin,
+            };
+            combined.put_canvas(column, row, canvas);
+            column += canvas.width();
+        }
+        if combined.rows.len() < height {
+            combined.rows.resize_with(height, Vec::new);
+        }
+        combined
+    }
+
+    /// Returns the given rectangular region of the canvas.
+    ///
+    /// Wide characters crossing the edges of the region are replaced with blanks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Canvas, TreeConfig};
+    ///
+    /// let mut canvas = Canvas::new(&TreeConfig::new());
+    /// canvas.put_str(0, 0, "foo\nbar\nbaz");
+    ///
+    /// assert_eq!(canvas.crop(1, 1, 5, 2).to_string(), "ar\naz\n");
+    /// ```
+    pub fn crop(&self, column: usize, row: usize, width: usize, height: usize) -> Self {
+        let mut cropped = Self::new(&self.opts);
+        for (y, cells) in self.rows.iter().skip(row).take(height).enumerate() {
+            let mut x = 0;
+            while x < width {
+                match cells.get(column + x) {
+                    Some(Cell::Text(text, w)) if x + w <= width => {
+                        cropped.put_cell(x, y, Cell::Text(text.clone(), *w));
+                        x += w;
+                    }
+                    Some(_) => x += 1,
+                    None => break,
+                }
+            }
+            if cropped.rows.len() <= y {
+                cropped.rows.resize_with(y + 1, Vec::new);
+            }
+        }
+        cropped
+    }
+
+    /// Returns the rows as strings, without newlines.
+    ///
+    /// Trailing whitespace of rows is removed unless emitted by the tree config.
+    pub fn to_lines(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|cells| {
+                let mut line = String::with_capacity(cells.len());
+                for cell in cells {
+                    match cell {
+                        Cell::Blank => line.push(' '),
+                        Cell::Text(text, _) => line.push_str(text),
+                        Cell::Continuation => {}
+                    }
+                }
+                if !self.opts.emit_trailing_whitespace() {
+                    line.truncate(line.trim_end().len());
+                }
+                line
+            })
+            .collect()
+    }
+
+    /// Puts the cell at the given position, clearing the wide characters partially overwritten.
+    fn put_cell(&mut self, column: usize, row: usize, cell: Cell) {
+        let width = match cell {
+            Cell::Text(_, width) => width,
+            _ => 1,
+        };
+        if self.rows.len() <= row {
+            self.rows.resize_with(row + 1, Vec::new);
+        }
+        let cells = &mut self.rows[row];
+        if cells.len() < column + width {
+            cells.resize(column + width, Cell::Blank);
+        }
+        for x in column..column + width {
+            clear_cell(cells, x);
+        }
+        cells[column] = cell;
+        for covered in &mut cells[column + 1..column + width] {
+            *covered = Cell::Continuation;
+        }
+    }
+
+    /// Appends the escape sequences to the last character before the given column.
+    fn append_to_last(&mut self, column: usize, row: usize, s: &str) {
+        let last = self.rows.get_mut(row).and_then(|cells| {
+            cells
+                .iter_mut()
+                .take(column)
+                .rev()
+                .find_map(|cell| match cell {
+                    Cell::Text(text, _) => Some(text),
+                    _ => None,
+                })
+        });
+        match last {
+            Some(text) => text.push_str(s),
+            None => self.pending.push_str(s),
+        }
+    }
+}
+
+/// Clears the cell and the rest of the wide character at the cell.
+fn clear_cell(cells: &mut [Cell], column: usize) {
+    let start = (0..=column)
+        .rev()
+        .find(|&x| cells[x] != Cell::Continuation)
+        .unwrap_or(column);
+    let end = match &cells[start] {
+        Cell::Text(_, width) => start + width,
+        _ => start + 1,
+    };
+    if end <= column {
+        return;
+    }
+    for cell in cells.iter_mut().take(end).skip(start) {
+        *cell = Cell::Blank;
+    }
+}
+
+impl fmt::Write for Canvas {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i != 0 {
+                self.cursor_row += 1;
+                self.cursor_column = 0;
+                if self.rows.len() < self.cursor_row {
+                    self.rows.resize_with(self.cursor_row, Vec::new);
+                }
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let mut text = std::mem::take(&mut self.pending);
+            text.push_str(line);
+            let (column, row) = (self.cursor_column, self.cursor_row);
+            self.put_str(column, row, &text);
+            self.cursor_column += self.opts.str_width(&text.replace('\r', ""));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.to_lines() {
+            f.write_str(&line)?;
+            f.write_str(self.opts.newline())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fmt::Write;
+
+    use crate::{config::TreeConfigBuilder, width::Width};
+
+    /// Treats non-ASCII characters as double width.
+    #[derive(Debug)]
+    struct WideNonAscii;
+
+    impl Width for WideNonAscii {
+        fn str_width(&self, s: &str) -> usize {
+            s.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+        }
+    }
+
+    #[test]
+    fn write_and_overwrite() -> fmt::Result {
+        let mut canvas = Canvas::default();
+        canvas.write_str("foo\n\nb")?;
+        canvas.write_str("ar\n")?;
+        assert_eq!(canvas.to_lines(), ["foo", "", "bar"]);
+
+        canvas.put_str(1, 0, "xy\nz");
+        assert_eq!(canvas.to_lines(), ["fxy", "\x20z", "bar"]);
+        assert_eq!((canvas.width(), canvas.height()), (3, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn wide_characters() {
+        let opts = TreeConfigBuilder::new().width(WideNonAscii).build();
+        let mut canvas = Canvas::new(&opts);
+        canvas.put_str(0, 0, "\u{3042}\u{3044}");
+        assert_eq!(canvas.width(), 4);
+        assert_eq!(canvas.get(2, 0), Some("\u{3044}"));
+        assert_eq!(canvas.get(3, 0), None);
+
+        // Overwriting half of a wide character clears the other half.
+        canvas.put_str(1, 0, "x");
+        assert_eq!(canvas.to_lines(), ["\x20x\u{3044}"]);
+        assert_eq!(canvas.crop(3, 0, 2, 1).to_lines(), [""]);
+    }
+
+    #[test]
+    fn escape_sequences() -> fmt::Result {
+        let mut canvas = Canvas::default();
+        canvas.write_str("\x1b[31mab\x1b[0m\nc")?;
+        assert_eq!(canvas.get(0, 0), Some("\x1b[31ma"));
+        assert_eq!(canvas.get(1, 0), Some("b\x1b[0m"));
+        assert_eq!(canvas.to_string(), "\x1b[31mab\x1b[0m\nc\n");
+        Ok(())
+    }
+
+    #[test]
+    fn side_by_side() {
+        let mut short = Canvas::default();
+        short.put_str(0, 0, "a");
+        let mut tall = Canvas::default();
+        tall.put_str(0, 0, "b\nbb\nb");
+        let canvases = [short.clone(), tall, short];
+
+        let got = Canvas::side_by_side(&canvases, " ", VerticalAlign::Middle);
+        assert_eq!(got.to_lines(), ["\x20 b", "a bb a", "\x20 b"]);
+        assert!(Canvas::side_by_side(&[], " ", VerticalAlign::Top)
+            .to_lines()
+            .is_empty());
+    }
+
+    #[test]
+    fn newline() -> fmt::Result {
+        let opts = TreeConfigBuilder::new().newline("\r\n").build();
+        let mut canvas = Canvas::new(&opts);
+        canvas.write_str("a\r\nb\r\n")?;
+        assert_eq!(canvas.to_string(), "a\r\nb\r\n");
+        Ok(())
+    }
+}
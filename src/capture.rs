@@ -0,0 +1,196 @@
+//! Trees of captured frames, such as span traces.
+
+use crate::tree_node::TreeNode;
+
+/// Frame of a capture hierarchy.
+///
+/// Frames are rendered by [`capture_tree()`].
+///
+/// [`capture_tree()`]: fn.capture_tree.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureFrame {
+    /// Label.
+    label: String,
+    /// Source location, such as `src/main.rs:42`.
+    location: Option<String>,
+    /// Index of the parent frame.
+    parent: Option<usize>,
+}
+
+impl CaptureFrame {
+    /// Creates a new `CaptureFrame` without parents.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            location: None,
+            parent: None,
+        }
+    }
+
+    /// Sets the source location shown as the annotation, such as `src/main.rs:42`.
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Sets the index of the parent frame.
+    ///
+    /// The parent should precede the frame.
+    pub fn with_parent(mut self, parent: usize) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Returns the label.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the source location.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// Returns the index of the parent frame.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// Creates a tree of the captured frames.
+///
+/// Frames are children of their parents, and frames without parents are children of the root.
+/// Frames are labeled with their labels, and annotated with their locations.
+/// Siblings are in the order of the frames.
+///
+/// Frames whose parents do not precede them are treated as frames without parents, so that
+/// every frame appears exactly once.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{capture_tree, CaptureFrame};
+///
+/// let frames = [
+///     CaptureFrame::new("main").with_location("src/main.rs:10"),
+///     CaptureFrame::new("load_config").with_location("src/config.rs:24").with_parent(0),
+///     CaptureFrame::new("read_file").with_parent(1),
+///     CaptureFrame::new("connect").with_location("src/net.rs:7").with_parent(0),
+/// ];
+/// let tree = capture_tree("captured", &frames);
+///
+/// let expected = "\
+/// captured
+/// `-- main             src/main.rs:10
+///     |-- load_config  src/config.rs:24
+///     |   `-- read_file
+///     `-- connect      src/net.rs:7
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// ```
+pub fn capture_tree(root_label: impl Into<String>, frames: &[CaptureFrame]) -> TreeNode {
+    let mut nodes = frames
+        .iter()
+        .map(|frame| {
+            let mut node = TreeNode::new(frame.label.clone());
+            node.set_annotation(frame.location.clone());
+            Some(node)
+        })
+        .collect::<Vec<_>>();
+    let mut root = TreeNode::new(root_label);
+    // Children follow their parents, so each node is complete when visited in reverse order.
+    for (i, frame) in frames.iter().enumerate().rev() {
+        let node = nodes[i]
+            .take()
+            .expect("Should never fail: each node is taken once");
+        match frame.parent.filter(|&parent| parent < i) {
+            Some(parent) => nodes[parent]
+                .as_mut()
+                .expect("Should never fail: parents precede their children")
+                .children_mut()
+                .insert(0, node),
+            None => root.children_mut().insert(0, node),
+        }
+    }
+    root
+}
+
+/// Creates a tree of the span trace.
+///
+/// The outermost span is the child of the root, and each span is the child of the span which
+/// encloses it.
+/// Spans are labeled with their names and fields, such as `request{id=42}`, and annotated with
+/// their source locations.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::span_trace_tree;
+/// use tracing_error::{ErrorLayer, SpanTrace};
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+/// let trace = tracing::subscriber::with_default(subscriber, || {
+///     let _request = tracing::info_span!("request", id = 42).entered();
+///     let _query = tracing::info_span!("query").entered();
+///     SpanTrace::capture()
+/// });
+/// let tree = span_trace_tree("span trace", &trace);
+///
+/// let lines = tree.to_string();
+/// assert!(lines.starts_with("span trace\n`-- request{id=42}  "));
+/// assert!(lines.contains("\n    `-- query  "));
+/// ```
+#[cfg(feature = "tracing-error")]
+pub fn span_trace_tree(
+    root_label: impl Into<String>,
+    trace: &tracing_error::SpanTrace,
+) -> TreeNode {
+    let mut frames = Vec::new();
+    trace.with_spans(|metadata, fields| {
+        let label = if fields.is_empty() {
+            metadata.name().to_owned()
+        } else {
+            format!("{}{{{}}}", metadata.name(), fields)
+        };
+        let mut frame = CaptureFrame::new(label);
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            frame = frame.with_location(format!("{}:{}", file, line));
+        }
+        frames.push(frame);
+        true
+    });
+    // Spans are visited from the innermost one.
+    frames.reverse();
+    let frames = frames
+        .into_iter()
+        .enumerate()
+        .map(|(i, frame)| match i.checked_sub(1) {
+            Some(parent) => frame.with_parent(parent),
+            None => frame,
+        })
+        .collect::<Vec<_>>();
+    capture_tree(root_label, &frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_parents() {
+        let frames = [
+            CaptureFrame::new("a").with_parent(1),
+            CaptureFrame::new("b").with_parent(1),
+            CaptureFrame::new("c").with_parent(0),
+            CaptureFrame::new("d").with_parent(42),
+        ];
+        let got = capture_tree(".", &frames).to_string();
+        let expected = ".\n\
+                        |-- a\n\
+                        |   `-- c\n\
+                        |-- b\n\
+                        `-- d\n";
+        assert_eq!(got, expected);
+    }
+}
@@ -0,0 +1,375 @@
+//! Trees of Cargo dependency graphs.
+
+use std::collections::{HashMap, HashSet};
+
+use cargo_metadata::{DependencyKind, Metadata, Node, Package, PackageId};
+
+use crate::tree_node::TreeNode;
+
+/// Options for rendering Cargo dependency graphs as trees.
+#[derive(Debug, Clone)]
+pub struct CargoTreeOptions {
+    /// Maximum depth of the packages to show.
+    max_depth: Option<usize>,
+    /// Whether to show the dependencies of repeated packages only once.
+    dedupe: bool,
+    /// Whether to annotate packages with their enabled features.
+    features: bool,
+    /// Whether to show build dependencies.
+    build_dependencies: bool,
+    /// Whether to show dev dependencies of workspace members.
+    dev_dependencies: bool,
+}
+
+impl Default for CargoTreeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            dedupe: true,
+            features: true,
+            build_dependencies: true,
+            dev_dependencies: true,
+        }
+    }
+}
+
+impl CargoTreeOptions {
+    /// Creates a new default `CargoTreeOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum depth of the packages to show, as `cargo tree --depth` does.
+    ///
+    /// The direct dependencies of the root package have depth 1.
+    ///
+    /// Default is `None`, that is, all dependencies are shown.
+    pub fn max_depth(&mut self, max: Option<usize>) -> &mut Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Sets whether to show the dependencies of repeated packages only once.
+    ///
+    /// Repeated packages with dependencies are marked with `(*)` and have no children, as
+    /// `cargo tree` does.
+    /// Otherwise only the packages depending on themselves are marked, to avoid infinite
+    /// trees.
+    ///
+    /// Default is `true`.
+    pub fn dedupe(&mut self, v: bool) -> &mut Self {
+        self.dedupe = v;
+        self
+    }
+
+    /// Sets whether to annotate packages with their enabled features.
+    ///
+    /// The features are separated by commas, as `cargo tree --format '{f}'` does.
+    ///
+    /// Default is `true`.
+    pub fn features(&mut self, v: bool) -> &mut Self {
+        self.features = v;
+        self
+    }
+
+    /// Sets whether to show build dependencies.
+    ///
+    /// Build dependencies are shown under `[build-dependencies]` nodes.
+    ///
+    /// Default is `true`.
+    pub fn build_dependencies(&mut self, v: bool) -> &mut Self {
+        self.build_dependencies = v;
+        self
+    }
+
+    /// Sets whether to show dev dependencies of workspace members.
+    ///
+    /// Dev dependencies are shown under `[dev-dependencies]` nodes.
+    /// Cargo resolves dev dependencies only for workspace members.
+    ///
+    /// Default is `true`.
+    pub fn dev_dependencies(&mut self, v: bool) -> &mut Self {
+        self.dev_dependencies = v;
+        self
+    }
+}
+
+/// Builder of the tree of a dependency graph.
+struct CargoTree<'a> {
+    /// Options.
+    opts: &'a CargoTreeOptions,
+    /// Workspace members.
+    members: &'a [PackageId],
+    /// Packages.
+    packages: HashMap<&'a PackageId, &'a Package>,
+    /// Nodes of the resolved graph.
+    nodes: HashMap<&'a PackageId, &'a Node>,
+    /// Packages whose dependencies are shown already.
+    expanded: HashSet<&'a PackageId>,
+    /// Packages being expanded.
+    ancestors: Vec<&'a PackageId>,
+}
+
+impl<'a> CargoTree<'a> {
+    /// Returns the label of the package, such as `serde v1.0.0`.
+    fn label(&self, id: &PackageId) -> String {
+        match self.packages.get(id) {
+            Some(package) => format!("{} v{}", package.name, package.version),
+            None => id.repr.clone(),
+        }
+    }
+
+    /// Returns the dependencies of the package grouped into sections.
+    ///
+    /// Each section has its header label (or `None` for normal dependencies) and the
+    /// dependencies sorted by names and versions.
+    fn sections(&self, id: &PackageId) -> Vec<(Option<&'static str>, Vec<&'a PackageId>)> {
+        let node = match self.nodes.get(id) {
+            Some(node) => node,
+            None => return Vec::new(),
+        };
+        let mut kinds = vec![(None, DependencyKind::Normal)];
+        if self.opts.build_dependencies {
+            kinds.push((Some("[build-dependencies]"), DependencyKind::Build));
+        }
+        if self.opts.dev_dependencies && self.members.contains(id) {
+            kinds.push((Some("[dev-dependencies]"), DependencyKind::Development));
+        }
+        kinds
+            .into_iter()
+            .filter_map(|(header, kind)| {
+                let mut deps = node
+                    .deps
+                    .iter()
+                    // `dep_kinds` is empty in the metadata from Cargo older than 1.41.
+                    .filter(|dep| match &dep.dep_kinds[..] {
+                        [] => kind == DependencyKind::Normal,
+                        infos => infos.iter().any(|info| info.kind == kind),
+                    })
+                    .map(|dep| &dep.pkg)
+                    .collect::<Vec<_>>();
+                deps.sort_by_key(|dep| {
+                    self.packages
+                        .get(dep)
+                        .map(|package| (&package.name, &package.version))
+                });
+                deps.dedup();
+                (!deps.is_empty()).then_some((header, deps))
+            })
+            .collect()
+    }
+
+    /// Returns the tree node for the package at the given depth.
+    fn node(&mut self, id: &'a PackageId, depth: usize) -> TreeNode {
+        let mut node = TreeNode::new(self.label(id));
+        if self.opts.features {
+            let features = self.nodes.get(id).map_or(&[][..], |node| &node.features);
+            if !features.is_empty() {
+                node.set_annotation(Some(features.join(",")));
+            }
+        }
+        if self.opts.max_depth.is_some_and(|max| depth >= max) {
+            return node;
+        }
+        let sections = self.sections(id);
+        if sections.is_empty() {
+            return node;
+        }
+        if self.ancestors.contains(&id) || (self.opts.dedupe && !self.expanded.insert(id)) {
+            node.set_label(format!("{} (*)", node.label()));
+            return node;
+        }
+
+        self.ancestors.push(id);
+        for (header, deps) in sections {
+            let children = deps
+                .into_iter()
+                .map(|dep| self.node(dep, depth + 1))
+                .collect::<Vec<_>>();
+            match header {
+                Some(header) => {
+                    let mut section = TreeNode::new(header);
+                    *section.children_mut() = children;
+                    node.push_child(section);
+                }
+                None => node.children_mut().extend(children),
+            }
+        }
+        self.ancestors.pop();
+        node
+    }
+}
+
+/// Creates a tree of the resolved dependencies of the package, as `cargo tree` does.
+///
+/// Each package is labeled with its name and version, such as `serde v1.0.0`, and annotated
+/// with its enabled features.
+/// Normal dependencies are children of the package, and build and dev dependencies are
+/// children of `[build-dependencies]` and `[dev-dependencies]` nodes.
+/// Dependencies of all target platforms are shown.
+///
+/// If `package` is `None`, the root package of the workspace is used.
+/// Returns `None` if the metadata has no resolved dependencies, or the package is not found.
+///
+/// # Examples
+///
+/// ```
+/// use cargo_metadata::MetadataCommand;
+/// use plaintextree::{cargo_dependency_tree, CargoTreeOptions};
+///
+/// # const METADATA: &str = r#"{
+/// #     "packages": [
+/// #         {"name": "app", "version": "0.1.0", "id": "app", "dependencies": [],
+/// #          "targets": [], "features": {}, "manifest_path": "/app/Cargo.toml"},
+/// #         {"name": "serde", "version": "1.0.0", "id": "serde", "dependencies": [],
+/// #          "targets": [], "features": {}, "manifest_path": "/serde/Cargo.toml"},
+/// #         {"name": "serde_derive", "version": "1.0.0", "id": "serde_derive",
+/// #          "dependencies": [], "targets": [], "features": {},
+/// #          "manifest_path": "/serde_derive/Cargo.toml"},
+/// #         {"name": "serde_json", "version": "1.0.0", "id": "serde_json", "dependencies": [],
+/// #          "targets": [], "features": {}, "manifest_path": "/serde_json/Cargo.toml"},
+/// #         {"name": "cc", "version": "1.0.0", "id": "cc", "dependencies": [],
+/// #          "targets": [], "features": {}, "manifest_path": "/cc/Cargo.toml"}
+/// #     ],
+/// #     "workspace_members": ["app"],
+/// #     "workspace_default_members": ["app"],
+/// #     "resolve": {
+/// #         "nodes": [
+/// #             {"id": "app", "dependencies": [], "features": [], "deps": [
+/// #                 {"name": "serde", "pkg": "serde", "dep_kinds": [{"kind": null}]},
+/// #                 {"name": "serde_json", "pkg": "serde_json", "dep_kinds": [{"kind": "dev"}]},
+/// #                 {"name": "cc", "pkg": "cc", "dep_kinds": [{"kind": "build"}]}
+/// #             ]},
+/// #             {"id": "serde", "dependencies": [], "features": ["default", "derive", "std"],
+/// #              "deps": [
+/// #                 {"name": "serde_derive", "pkg": "serde_derive", "dep_kinds": [{"kind": null}]}
+/// #             ]},
+/// #             {"id": "serde_derive", "dependencies": [], "features": ["default"], "deps": []},
+/// #             {"id": "serde_json", "dependencies": [], "features": ["std"], "deps": [
+/// #                 {"name": "serde", "pkg": "serde", "dep_kinds": [{"kind": null}]}
+/// #             ]},
+/// #             {"id": "cc", "dependencies": [], "features": [], "deps": []}
+/// #         ],
+/// #         "root": "app"
+/// #     },
+/// #     "workspace_root": "/app",
+/// #     "target_directory": "/app/target",
+/// #     "version": 1
+/// # }"#;
+/// // Usually obtained by `MetadataCommand::new().exec()`.
+/// let metadata = MetadataCommand::parse(METADATA)?;
+/// let tree = cargo_dependency_tree(&metadata, None, &CargoTreeOptions::new())
+///     .expect("the root package exists");
+///
+/// let expected = "\
+/// app v0.1.0
+/// |-- serde v1.0.0              default,derive,std
+/// |   `-- serde_derive v1.0.0   default
+/// |-- [build-dependencies]
+/// |   `-- cc v1.0.0
+/// `-- [dev-dependencies]
+///     `-- serde_json v1.0.0     std
+///         `-- serde v1.0.0 (*)  default,derive,std
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// # Ok::<_, cargo_metadata::Error>(())
+/// ```
+pub fn cargo_dependency_tree(
+    metadata: &Metadata,
+    package: Option<&PackageId>,
+    opts: &CargoTreeOptions,
+) -> Option<TreeNode> {
+    let resolve = metadata.resolve.as_ref()?;
+    let root = package.or(resolve.root.as_ref())?;
+    let mut tree = CargoTree {
+        opts,
+        members: &metadata.workspace_members,
+        packages: metadata
+            .packages
+            .iter()
+            .map(|package| (&package.id, package))
+            .collect(),
+        nodes: resolve.nodes.iter().map(|node| (&node.id, node)).collect(),
+        expanded: HashSet::new(),
+        ancestors: Vec::new(),
+    };
+    if !tree.nodes.contains_key(root) {
+        return None;
+    }
+    Some(tree.node(root, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cargo_metadata::MetadataCommand;
+
+    /// Returns the metadata of the workspace with the given packages and normal dependencies.
+    ///
+    /// The first package is the root.
+    fn metadata(packages: &[(&str, &[&str])]) -> Metadata {
+        let mut package_list = Vec::new();
+        let mut nodes = Vec::new();
+        for (name, deps) in packages {
+            package_list.push(format!(
+                r#"{{"name": "{0}", "version": "1.0.0", "id": "{0}", "dependencies": [],
+                    "targets": [], "features": {{}}, "manifest_path": "/{0}/Cargo.toml"}}"#,
+                name
+            ));
+            let deps = deps
+                .iter()
+                .map(|dep| {
+                    format!(
+                        r#"{{"name": "{0}", "pkg": "{0}", "dep_kinds": [{{"kind": null}}]}}"#,
+                        dep
+                    )
+                })
+                .collect::<Vec<_>>();
+            nodes.push(format!(
+                r#"{{"id": "{}", "dependencies": [], "deps": [{}]}}"#,
+                name,
+                deps.join(",")
+            ));
+        }
+        let root = packages[0].0;
+        let json = format!(
+            r#"{{"packages": [{}], "workspace_members": ["{root}"],
+                "workspace_default_members": ["{root}"],
+                "resolve": {{"nodes": [{}], "root": "{root}"}},
+                "workspace_root": "/", "target_directory": "/target", "version": 1}}"#,
+            package_list.join(","),
+            nodes.join(","),
+            root = root
+        );
+        MetadataCommand::parse(json).expect("valid metadata")
+    }
+
+    #[test]
+    fn cycles_and_depth() {
+        let metadata = metadata(&[("a", &["b", "c"]), ("b", &["c"]), ("c", &["b"])]);
+
+        let mut opts = CargoTreeOptions::new();
+        opts.dedupe(false);
+        let got = cargo_dependency_tree(&metadata, None, &opts)
+            .expect("root exists")
+            .to_string();
+        let expected = "a v1.0.0\n\
+                        |-- b v1.0.0\n\
+                        |   `-- c v1.0.0\n\
+                        |       `-- b v1.0.0 (*)\n\
+                        `-- c v1.0.0\n    \
+                        `-- b v1.0.0\n        \
+                        `-- c v1.0.0 (*)\n";
+        assert_eq!(got, expected);
+
+        opts.max_depth(Some(2));
+        let b = PackageId {
+            repr: "b".to_owned(),
+        };
+        let got = cargo_dependency_tree(&metadata, Some(&b), &opts)
+            .expect("package exists")
+            .to_string();
+        assert_eq!(got, "b v1.0.0\n`-- c v1.0.0\n    `-- b v1.0.0\n");
+    }
+}
@@ -0,0 +1,151 @@
+//! Top-down chart layout.
+
+use crate::config::{BoxChars, TreeConfig};
+
+/// Number of spaces between sibling subtrees.
+const SIBLING_GAP: usize = 2;
+
+/// Rendered subtree of a chart.
+#[derive(Debug, Clone)]
+pub(crate) struct Block {
+    /// Lines, padded to the width of the block.
+    lines: Vec<String>,
+    /// Display width of the block.
+    width: usize,
+    /// Column of the center of the top box, where the line from the parent is connected.
+    anchor: usize,
+}
+
+impl Block {
+    /// Creates a block of the boxed label.
+    pub(crate) fn boxed(label: &str, chars: &BoxChars, config: &TreeConfig) -> Self {
+        let label = label.strip_suffix('\n').unwrap_or(label);
+        let lines = chars
+            .wrap(label, config)
+            .split('\n')
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        let width = config.str_width(&lines[0]);
+        Self {
+            lines,
+            width,
+            anchor: width / 2,
+        }
+    }
+
+    /// Puts the child blocks under the block, and connects them with lines.
+    ///
+    /// The block is centered above the children.
+    pub(crate) fn with_children(mut self, children: Vec<Self>, chars: &BoxChars) -> Self {
+        if children.is_empty() {
+            return self;
+        }
+
+        let mut anchors = Vec::with_capacity(children.len());
+        let mut children_width = 0;
+        for child in &children {
+            if children_width != 0 {
+                children_width += SIBLING_GAP;
+            }
+            anchors.push(children_width + child.anchor);
+            children_width += child.width;
+        }
+        let center = (anchors[0] + anchors[anchors.len() - 1]) / 2;
+        let (box_offset, children_offset) = if center >= self.anchor {
+            (center - self.anchor, 0)
+        } else {
+            (0, self.anchor - center)
+        };
+        let width = (box_offset + self.width).max(children_offset + children_width);
+        let anchor = box_offset + self.anchor;
+
+        let mut lines = Vec::new();
+        if let Some(bottom) = self.lines.last_mut() {
+            replace_char(bottom, self.anchor, chars.junction(false, true, true, true));
+        }
+        for line in &self.lines {
+            lines.push(pad(line, box_offset, self.width, width));
+        }
+
+        let anchors = anchors
+            .into_iter()
+            .map(|child_anchor| children_offset + child_anchor)
+            .collect::<Vec<_>>();
+        let first = anchors[0].min(anchor);
+        let last = anchors[anchors.len() - 1].max(anchor);
+        let mut connector = " ".repeat(first);
+        connector.extend((first..=last).map(|column| {
+            let down = anchors.contains(&column);
+            chars.junction(column == anchor, down, column > first, column < last)
+        }));
+        lines.push(pad(&connector, 0, last + 1, width));
+
+        let height = children
+            .iter()
+            .map(|child| child.lines.len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..height {
+            let mut line = " ".repeat(children_offset);
+            for (i, child) in children.iter().enumerate() {
+                if i != 0 {
+                    line.extend(std::iter::repeat(' ').take(SIBLING_GAP));
+                }
+                match child.lines.get(row) {
+                    Some(child_line) if row == 0 => {
+                        let mut top = child_line.clone();
+                        replace_char(
+                            &mut top,
+                            child.anchor,
+                            chars.junction(true, false, true, true),
+                        );
+                        line.push_str(&top);
+                    }
+                    Some(child_line) => line.push_str(child_line),
+                    None => line.extend(std::iter::repeat(' ').take(child.width)),
+                }
+            }
+            lines.push(pad(&line, 0, children_offset + children_width, width));
+        }
+
+        self.lines = lines;
+        self.width = width;
+        self.anchor = anchor;
+        self
+    }
+
+    /// Returns the lines of the block joined with the newline.
+    pub(crate) fn into_string(self, config: &TreeConfig) -> String {
+        let newline = config.newline();
+        let mut s = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i != 0 {
+                s.push_str(newline);
+            }
+            if config.emit_trailing_whitespace() {
+                s.push_str(line);
+            } else {
+                s.push_str(line.trim_end());
+            }
+        }
+        if config.emit_trailing_newline() {
+            s.push_str(newline);
+        }
+        s
+    }
+}
+
+/// Returns the line with the given number of spaces before it, padded to the given width.
+fn pad(line: &str, left: usize, line_width: usize, width: usize) -> String {
+    let mut padded = " ".repeat(left);
+    padded.push_str(line);
+    padded.extend(std::iter::repeat(' ').take(width - left - line_width));
+    padded
+}
+
+/// Replaces the character at the given index of the border line.
+fn replace_char(line: &mut String, index: usize, c: char) {
+    if let Some((pos, old)) = line.char_indices().nth(index) {
+        line.replace_range(pos..pos + old.len_utf8(), c.encode_utf8(&mut [0; 4]));
+    }
+}
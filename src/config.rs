@@ -1,8 +1,14 @@
 //! Config types.
 
-use std::fmt;
+use std::{borrow::Cow, env, error, ffi::OsStr, fmt, sync::Arc};
 
-use crate::item_writer::{ItemState, ItemWriter};
+#[cfg(feature = "ansi")]
+use crate::ansi::{Color, ColorLevel, ColorMode, TextStyle};
+use crate::{
+    item_writer::{ItemState, ItemWriter, LineWrite},
+    tree_node::TreeNode,
+    width::{self, is_box_drawing, AmbiAwareWidth, AmbiWidth, Width},
+};
 
 /// Part of a prefix.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,7 +20,7 @@ pub(crate) enum PrefixPart {
 }
 
 /// Edge config.
-#[derive(Debug, Clone)]
+#[derive(Default, Debug, Clone)]
 #[non_exhaustive]
 pub enum EdgeConfig {
     /// Standard ASCII tree.
@@ -35,6 +41,7 @@ pub enum EdgeConfig {
     /// ```
     ///
     /// [unix-tree]: http://mama.indstate.edu/users/ice/tree/
+    #[default]
     Ascii,
     /// Unicode assuming ruled line characters are single width (half width).
     ///
@@ -89,9 +96,298 @@ pub enum EdgeConfig {
     ///
     /// [UAX-11]: https://unicode.org/reports/tr11/
     UnicodeDoubleWidth,
+    /// Indentation only, without any edge characters.
+    ///
+    /// The given string is used as an indent for each nest level.
+    /// This would be useful for environments which cannot show ruled lines or even `|`.
+    ///
+    /// With `EdgeConfig::Indent("  ".to_owned())`:
+    ///
+    /// ```text
+    /// .
+    ///   foo
+    ///     bar
+    ///       baz
+    ///
+    ///       baz2
+    ///     qux
+    ///       quux
+    ///   corge
+    ///   grault
+    /// ```
+    Indent(String),
+    /// List items marked with the given marker, such as `"\u{2022} "` and `"- [ ] "`.
+    ///
+    /// Continuation lines are indented by the width of the marker, and so are the child items.
+    ///
+    /// With `EdgeConfig::bullet()`:
+    ///
+    /// ```text
+    /// .
+    /// • foo
+    ///   • bar
+    ///     • baz
+    ///
+    ///       baz2
+    ///   • qux
+    ///     • quux
+    /// • corge
+    /// • grault
+    /// ```
+    Bullet(String),
+    /// User-defined edge strings.
+    ///
+    /// See [`CustomEdgeConfigBuilder`] for the strings to be configured.
+    ///
+    /// [`CustomEdgeConfigBuilder`]: struct.CustomEdgeConfigBuilder.html
+    Custom(CustomEdgeConfig),
 }
 
 impl EdgeConfig {
+    /// Creates a bullet list style with the `•` (U+2022 BULLET) marker.
+    pub fn bullet() -> Self {
+        Self::Bullet("\u{2022} ".to_owned())
+    }
+
+    /// Creates a task list style with a checkbox marker (`- [ ] ` or `- [x] `).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// printer.open_node(ItemStyle::non_last(EdgeConfig::checkbox(true)), "write docs")?;
+    /// printer.close_node()?;
+    /// printer.open_node(ItemStyle::last(EdgeConfig::checkbox(false)), "release\nv0.1.0")?;
+    /// let got = printer.finalize()?;
+    ///
+    /// assert_eq!(got, "- [x] write docs\n- [ ] release\n      v0.1.0\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn checkbox(checked: bool) -> Self {
+        Self::Bullet(if checked { "- [x] " } else { "- [ ] " }.to_owned())
+    }
+
+    /// Creates a single-width Unicode style with a rounded corner for the last child.
+    ///
+    /// ```text
+    /// .
+    /// ├── foo
+    /// │   ╰── bar
+    /// ╰── baz
+    /// ```
+    pub fn rounded() -> Self {
+        Self::preset(
+            "\u{251C}\u{2500}\u{2500}",
+            "\u{2570}\u{2500}\u{2500}",
+            "\u{2502}",
+        )
+    }
+
+    /// Creates a single-width Unicode style with heavy lines.
+    ///
+    /// ```text
+    /// .
+    /// ┣━━ foo
+    /// ┃   ┗━━ bar
+    /// ┗━━ baz
+    /// ```
+    pub fn bold() -> Self {
+        Self::preset(
+            "\u{2523}\u{2501}\u{2501}",
+            "\u{2517}\u{2501}\u{2501}",
+            "\u{2503}",
+        )
+    }
+
+    /// Creates a single-width Unicode style with double lines.
+    ///
+    /// ```text
+    /// .
+    /// ╠══ foo
+    /// ║   ╚══ bar
+    /// ╚══ baz
+    /// ```
+    pub fn double() -> Self {
+        Self::preset(
+            "\u{2560}\u{2550}\u{2550}",
+            "\u{255A}\u{2550}\u{2550}",
+            "\u{2551}",
+        )
+    }
+
+    /// Creates a single-width Unicode style with dashed lines.
+    ///
+    /// ```text
+    /// .
+    /// ├┄┄ foo
+    /// ┆   └┄┄ bar
+    /// └┄┄ baz
+    /// ```
+    pub fn dotted() -> Self {
+        Self::preset(
+            "\u{251C}\u{2504}\u{2504}",
+            "\u{2514}\u{2504}\u{2504}",
+            "\u{2506}",
+        )
+    }
+
+    /// Creates an ASCII style with `+--` corners for both non-last and last children.
+    ///
+    /// ```text
+    /// .
+    /// +-- foo
+    /// |   +-- bar
+    /// +-- baz
+    /// ```
+    pub fn ascii_plus() -> Self {
+        Self::preset("+--", "+--", "|")
+    }
+
+    /// Creates a compact single-width Unicode style where each level consumes only 2 columns.
+    ///
+    /// ```text
+    /// .
+    /// ├ foo
+    /// │ └ bar
+    /// └ baz
+    /// ```
+    pub fn compact() -> Self {
+        Self::compact_preset("\u{251C}", "\u{2514}", "\u{2502}")
+    }
+
+    /// Creates a compact ASCII style where each level consumes only 2 columns.
+    ///
+    /// ```text
+    /// .
+    /// | foo
+    /// | ` bar
+    /// ` baz
+    /// ```
+    pub fn compact_ascii() -> Self {
+        Self::compact_preset("|", "`", "|")
+    }
+
+    /// Chooses the edge config for the locale and the terminal of the current environment.
+    ///
+    /// As the [`tree` command][unix-tree] does, ruled line characters are used only if the
+    /// character encoding of the locale is UTF-8.
+    /// The locale is taken from the first non-empty one of `LC_ALL`, `LC_CTYPE`, and `LANG`
+    /// environment variables.
+    /// `EdgeConfig::Ascii` is also chosen if `TERM` environment variable is `dumb`.
+    ///
+    /// For East Asian locales (Chinese, Japanese, and Korean), `EdgeConfig::UnicodeDoubleWidth`
+    /// is chosen since fonts for them usually have double-width ruled line characters.
+    /// Otherwise `EdgeConfig::UnicodeSingleWidth` is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let edge = EdgeConfig::from_environment();
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// writer.open_node(ItemStyle::last(edge), "foo")?;
+    /// let _ = writer.finalize()?;
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [unix-tree]: http://mama.indstate.edu/users/ice/tree/
+    pub fn from_environment() -> Self {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(env::var_os)
+            .find(|locale| !locale.is_empty());
+        edge_for_environment(locale.as_deref(), env::var_os("TERM").as_deref())
+    }
+
+    /// Returns the closest edge config consisting of ASCII characters with the same widths.
+    ///
+    /// This is useful to degrade gracefully when the destination encoding or font cannot show
+    /// ruled line characters.
+    /// Ruled line characters are replaced with `|`, `-`, `` ` ``, or `+` depending on their
+    /// shapes, and other non-ASCII characters are replaced with similar ASCII characters as
+    /// many as their display widths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let edge = EdgeConfig::rounded().to_ascii_fallback();
+    ///
+    /// let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// printer.open_node(ItemStyle::non_last(edge.clone()), "foo")?;
+    /// printer.open_node(ItemStyle::last(edge.clone()), "bar")?;
+    /// printer.close_node()?;
+    /// printer.close_node()?;
+    /// printer.open_node(ItemStyle::last(EdgeConfig::bullet().to_ascii_fallback()), "baz")?;
+    /// let got = printer.finalize()?;
+    ///
+    /// assert_eq!(got, "|-- foo\n|   `-- bar\n* baz\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn to_ascii_fallback(&self) -> Self {
+        match self {
+            Self::Ascii | Self::UnicodeSingleWidth => Self::Ascii,
+            // Ruled line characters are double width for this config.
+            Self::UnicodeDoubleWidth => Self::Custom(CustomEdgeConfig {
+                parts: Arc::new(CustomEdgeParts {
+                    branch: "|---".to_owned(),
+                    last_branch: "`---".to_owned(),
+                    branch_padding: " ".to_owned(),
+                    continuation: "|".to_owned(),
+                    continuation_padding: "    ".to_owned(),
+                    last_continuation: String::new(),
+                    last_continuation_padding: "     ".to_owned(),
+                }),
+            }),
+            Self::Indent(indent) => Self::Indent(ascii_fallback(indent)),
+            Self::Bullet(marker) => Self::Bullet(ascii_fallback(marker)),
+            Self::Custom(custom) => {
+                let parts = &*custom.parts;
+                Self::Custom(CustomEdgeConfig {
+                    parts: Arc::new(CustomEdgeParts {
+                        branch: ascii_fallback(&parts.branch),
+                        last_branch: ascii_fallback(&parts.last_branch),
+                        branch_padding: ascii_fallback(&parts.branch_padding),
+                        continuation: ascii_fallback(&parts.continuation),
+                        continuation_padding: ascii_fallback(&parts.continuation_padding),
+                        last_continuation: ascii_fallback(&parts.last_continuation),
+                        last_continuation_padding: ascii_fallback(&parts.last_continuation_padding),
+                    }),
+                })
+            }
+        }
+    }
+
+    /// Creates a style with the given 1-column branches and continuation.
+    fn compact_preset(branch: &str, last_branch: &str, continuation: &str) -> Self {
+        Self::Custom(
+            CustomEdgeConfigBuilder::new()
+                .branch(branch)
+                .last_branch(last_branch)
+                .continuation(continuation)
+                .continuation_padding(" ")
+                .last_continuation_padding("  ")
+                .build()
+                .expect("Should never fail: preset edges have consistent widths"),
+        )
+    }
+
+    /// Creates a style with the given 3-column branches and 1-column continuation.
+    fn preset(branch: &str, last_branch: &str, continuation: &str) -> Self {
+        Self::Custom(
+            CustomEdgeConfigBuilder::new()
+                .branch(branch)
+                .last_branch(last_branch)
+                .continuation(continuation)
+                .build()
+                .expect("Should never fail: preset edges have consistent widths"),
+        )
+    }
+
     /// Writes the prefix or padding with the given config.
     pub(crate) fn write_edge<W: fmt::Write>(
         &self,
@@ -130,7 +426,74 @@ impl EdgeConfig {
                 (false, false, Prefix) => writer.write_str("\u{2502}"),
                 (false, false, Padding) => writer.write_str("   "),
             },
+            Self::Indent(indent) => match fragment {
+                Prefix => Ok(()),
+                Padding => writer.write_str(indent),
+            },
+            Self::Custom(custom) => writer.write_str(custom.part(last_child, first_line, fragment)),
+            Self::Bullet(marker) => {
+                let mark = marker.trim_end();
+                match (first_line, fragment) {
+                    (true, Prefix) => writer.write_str(mark),
+                    (true, Padding) => writer.write_str(&marker[mark.len()..]),
+                    (false, Prefix) => Ok(()),
+                    (false, Padding) => {
+                        write!(writer, "{:width$}", "", width = width::str_width(marker))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the display width of the prefix or padding with the given width oracle.
+    pub(crate) fn edge_width(
+        &self,
+        last_child: bool,
+        first_line: bool,
+        fragment: PrefixPart,
+        width: &dyn Width,
+    ) -> usize {
+        let mut buf = String::new();
+        self.write_edge(&mut buf, last_child, first_line, fragment)
+            .expect("Should never fail: writing to `String`");
+        match self {
+            // Ruled line characters are double width for this config.
+            Self::UnicodeDoubleWidth => buf
+                .chars()
+                .map(|c| if is_box_drawing(c) { 2 } else { 1 })
+                .sum(),
+            _ => width::visible_width(&buf, width),
+        }
+    }
+
+    /// Returns the box characters matching the lines of the edge.
+    pub(crate) fn box_chars(&self) -> BoxChars {
+        let mut buf = String::new();
+        for &last_child in &[true, false] {
+            self.write_edge(&mut buf, last_child, true, PrefixPart::Prefix)
+                .expect("Should never fail: writing to `String`");
         }
+        match buf.chars().find(|&c| is_box_drawing(c)) {
+            None => BoxChars::ASCII,
+            Some('\u{256D}'..='\u{2570}') => BoxChars::ROUNDED,
+            Some(
+                '\u{2501}' | '\u{2503}' | '\u{250F}' | '\u{2513}' | '\u{2517}' | '\u{251B}'
+                | '\u{2523}' | '\u{252B}',
+            ) => BoxChars::HEAVY,
+            Some('\u{2550}'..='\u{256C}') => BoxChars::DOUBLE,
+            Some(_) => BoxChars::LIGHT,
+        }
+    }
+
+    /// Returns the display width of the prefix and padding.
+    pub(crate) fn prefix_and_padding_width(
+        &self,
+        last_child: bool,
+        first_line: bool,
+        width: &dyn Width,
+    ) -> usize {
+        self.edge_width(last_child, first_line, PrefixPart::Prefix, width)
+            + self.edge_width(last_child, first_line, PrefixPart::Padding, width)
     }
 
     /// Returns whether the prefix and padding consist of whitespaces.
@@ -142,215 +505,3596 @@ impl EdgeConfig {
             Self::Ascii | Self::UnicodeSingleWidth | Self::UnicodeDoubleWidth => {
                 last_child && !first_line
             }
+            Self::Indent(indent) => indent.chars().all(char::is_whitespace),
+            Self::Bullet(marker) => !first_line || marker.chars().all(char::is_whitespace),
+            Self::Custom(custom) => [PrefixPart::Prefix, PrefixPart::Padding]
+                .iter()
+                .flat_map(|&fragment| custom.part(last_child, first_line, fragment).chars())
+                .all(char::is_whitespace),
         }
     }
 }
 
-impl Default for EdgeConfig {
-    fn default() -> Self {
-        EdgeConfig::Ascii
+/// Strings of a custom edge config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CustomEdgeParts {
+    /// Prefix of the first line of a non-last child.
+    branch: String,
+    /// Prefix of the first line of the last child.
+    last_branch: String,
+    /// Padding of the first line.
+    branch_padding: String,
+    /// Prefix of the following lines of a non-last child.
+    continuation: String,
+    /// Padding of the following lines of a non-last child.
+    continuation_padding: String,
+    /// Prefix of the following lines of the last child.
+    last_continuation: String,
+    /// Padding of the following lines of the last child.
+    last_continuation_padding: String,
+}
+
+/// User-defined edge strings for [`EdgeConfig::Custom`].
+///
+/// This is created by [`CustomEdgeConfigBuilder`].
+///
+/// [`EdgeConfig::Custom`]: enum.EdgeConfig.html#variant.Custom
+/// [`CustomEdgeConfigBuilder`]: struct.CustomEdgeConfigBuilder.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomEdgeConfig {
+    /// Strings.
+    parts: Arc<CustomEdgeParts>,
+}
+
+impl CustomEdgeConfig {
+    /// Returns the string for the given position.
+    fn part(&self, last_child: bool, first_line: bool, fragment: PrefixPart) -> &str {
+        use PrefixPart::{Padding, Prefix};
+
+        let parts = &*self.parts;
+        match (first_line, last_child, fragment) {
+            (true, false, Prefix) => &parts.branch,
+            (true, true, Prefix) => &parts.last_branch,
+            (true, _, Padding) => &parts.branch_padding,
+            (false, false, Prefix) => &parts.continuation,
+            (false, false, Padding) => &parts.continuation_padding,
+            (false, true, Prefix) => &parts.last_continuation,
+            (false, true, Padding) => &parts.last_continuation_padding,
+        }
     }
 }
 
-/// Item style.
+/// `CustomEdgeConfig` builder.
+///
+/// All strings default to the ones of `EdgeConfig::Ascii`.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{CustomEdgeConfigBuilder, EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+///
+/// let edge = EdgeConfig::Custom(
+///     CustomEdgeConfigBuilder::new()
+///         .branch("\u{2523}\u{2501}")
+///         .last_branch("\u{2517}\u{2501}")
+///         .continuation("\u{2503}")
+///         .continuation_padding("  ")
+///         .last_continuation_padding("   ")
+///         .build()?,
+/// );
+///
+/// let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+/// printer.open_node(ItemStyle::non_last(edge.clone()), "foo\nfoo2")?;
+/// printer.open_node(ItemStyle::last(edge.clone()), "bar")?;
+/// printer.close_node()?;
+/// printer.close_node()?;
+/// printer.open_node(ItemStyle::last(edge), "baz")?;
+/// let got = printer.finalize()?;
+///
+/// let expected = "\
+/// ┣━ foo
+/// ┃  foo2
+/// ┃  ┗━ bar
+/// ┗━ baz
+/// ";
+/// assert_eq!(got, expected);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Strings with inconsistent widths are rejected.
+///
+/// ```
+/// use plaintextree::CustomEdgeConfigBuilder;
+///
+/// let err = CustomEdgeConfigBuilder::new()
+///     .branch("+-")
+///     .build()
+///     .unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Inconsistent edge widths: `branch` + `branch_padding` has width 3, \
+///      but `last_branch` + `branch_padding` has width 4"
+/// );
+/// ```
 #[derive(Debug, Clone)]
-pub struct ItemStyle {
-    /// Whether the item is the last child.
-    is_last_child: bool,
-    /// Edge config.
-    edge: EdgeConfig,
+pub struct CustomEdgeConfigBuilder {
+    /// Current strings.
+    parts: CustomEdgeParts,
 }
 
-impl ItemStyle {
-    /// Creates a new `ItemStyle`.
-    ///
-    /// Prefer [`last()`][last] and [`non_last()`][non_last] when `is_last_child` argument is
-    /// constant.
-    ///
-    /// [last]: #method.last
-    /// [non_last]: #method.non_last
-    pub fn new(is_last_child: bool, edge: EdgeConfig) -> Self {
+impl Default for CustomEdgeConfigBuilder {
+    fn default() -> Self {
         Self {
-            is_last_child,
-            edge,
+            parts: CustomEdgeParts {
+                branch: "|--".to_owned(),
+                last_branch: "`--".to_owned(),
+                branch_padding: " ".to_owned(),
+                continuation: "|".to_owned(),
+                continuation_padding: "   ".to_owned(),
+                last_continuation: "".to_owned(),
+                last_continuation_padding: "    ".to_owned(),
+            },
         }
     }
+}
 
-    /// Creates a new `ItemStyle` for the last child.
-    ///
-    /// This is same as `ItemStyle::new(true, edge)`.
-    pub fn last(edge: EdgeConfig) -> Self {
-        Self::new(true, edge)
+impl CustomEdgeConfigBuilder {
+    /// Creates a new `CustomEdgeConfigBuilder`.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Creates a new `ItemStyle` for a non-last child.
-    ///
-    /// This is same as `ItemStyle::new(false, edge)`.
-    pub fn non_last(edge: EdgeConfig) -> Self {
-        Self::new(false, edge)
+    /// Sets the prefix of the first line of a non-last child (such as `|--`).
+    pub fn branch(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.branch = s.into();
+        self
     }
 
-    /// Returns whether the item is the last child.
-    pub(crate) fn is_last_child(&self) -> bool {
-        self.is_last_child
+    /// Sets the prefix of the first line of the last child (such as `` `-- ``).
+    pub fn last_branch(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.last_branch = s.into();
+        self
     }
 
-    /// Returns the edge config.
-    pub(crate) fn edge(&self) -> &EdgeConfig {
-        &self.edge
+    /// Sets the padding between the prefix and the content at the first line (such as ` `).
+    pub fn branch_padding(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.branch_padding = s.into();
+        self
     }
-}
 
-/// `TreeConfig` builder.
-#[derive(Default, Debug, Clone, Copy)]
-pub struct TreeConfigBuilder {
-    /// Current config.
-    config: TreeConfig,
-}
+    /// Sets the prefix of the following lines of a non-last child (such as `|`).
+    pub fn continuation(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.continuation = s.into();
+        self
+    }
 
-impl TreeConfigBuilder {
-    /// Creates a new `TreeConfig`.
-    pub fn new() -> Self {
-        Self::default()
+    /// Sets the padding of the following lines of a non-last child (such as `   `).
+    pub fn continuation_padding(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.continuation_padding = s.into();
+        self
     }
 
-    /// Let the writer emit trailing whitespace if the line has no content.
-    ///
-    /// The value is `false` by default.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
-    ///
-    /// let opts = TreeConfigBuilder::new()
-    ///     .emit_trailing_whitespace(true)
-    ///     .build();
-    ///
-    /// let mut writer = TreePrinter::new(String::new(), opts);
-    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n\nbar")?;
-    /// let buf = writer.finalize()?;
-    ///
-    /// // Note that `"    "` is emitted for an empty line between "foo" and "bar".
-    /// assert_eq!(buf, "`-- foo\n    \n    bar\n");
-    /// # plaintextree::Result::Ok(())
-    /// ```
-    pub fn emit_trailing_whitespace(&mut self, v: bool) -> &mut Self {
-        self.config.emit_trailing_whitespace = v;
+    /// Sets the prefix of the following lines of the last child (usually empty).
+    pub fn last_continuation(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.last_continuation = s.into();
         self
     }
 
-    /// Let the writer emit trailing newline automatically at the end of the tree.
-    ///
-    /// The value is `true` by default.
-    ///
-    /// # Examples
-    ///
-    /// By default, the tree printer will put a newline at the end of the tree, if there is not.
-    ///
-    /// ```
-    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
-    ///
-    /// let opts = TreeConfig::new();
-    ///
-    /// let mut writer = TreePrinter::new(String::new(), opts);
-    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
-    /// let buf = writer.finalize()?;
-    ///
-    /// // Note that the newline character at the end of the output.
-    /// assert_eq!(buf, "`-- foo\n");
-    /// # plaintextree::Result::Ok(())
-    /// ```
+    /// Sets the padding of the following lines of the last child (such as `    `).
+    pub fn last_continuation_padding(&mut self, s: impl Into<String>) -> &mut Self {
+        self.parts.last_continuation_padding = s.into();
+        self
+    }
+
+    /// Builds a `CustomEdgeConfig`.
     ///
-    /// If there are already a newline, the printer does not emit an additional newline.
+    /// Returns an error if the prefix and padding combinations have different display widths,
+    /// since such edges make the tree misaligned.
+    /// The widths are measured with the default width oracle, and ambiguous width characters
+    /// are single width.
+    /// Use [`build_for()`](#method.build_for) for trees with a custom width oracle or
+    /// [`AmbiWidth::Wide`].
     ///
-    /// ```
-    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    /// [`AmbiWidth::Wide`]: enum.AmbiWidth.html#variant.Wide
+    pub fn build(&self) -> Result<CustomEdgeConfig, EdgeWidthError> {
+        self.build_with(&AmbiAwareWidth::new(
+            &*width::default_width(),
+            AmbiWidth::Narrow,
+        ))
+    }
+
+    /// Builds a `CustomEdgeConfig` for trees rendered with the given config.
     ///
-    /// let opts = TreeConfig::new();
+    /// This is same as [`build()`](#method.build), except that the widths are measured with the
+    /// width oracle and the ambiguous width setting of the config.
     ///
-    /// let mut writer = TreePrinter::new(String::new(), opts);
-    /// // Feed a trailing newline explicitly.
-    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n")?;
-    /// let buf = writer.finalize()?;
+    /// # Examples
     ///
-    /// // Note that there are only one newline character at the end of the output.
-    /// assert_eq!(buf, "`-- foo\n");
-    /// # plaintextree::Result::Ok(())
     /// ```
+    /// use plaintextree::{AmbiWidth, CustomEdgeConfigBuilder, TreeConfigBuilder};
     ///
-    /// With this flag unset, the tree printer does not emit an additional newline.
-    ///
-    /// ```
-    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    /// let mut builder = CustomEdgeConfigBuilder::new();
+    /// builder
+    ///     .branch("\u{251C}\u{2500}")
+    ///     .last_branch("\u{2514}\u{2500}")
+    ///     .continuation("\u{2502}")
+    ///     .continuation_padding("  ")
+    ///     .last_continuation_padding("   ");
+    /// assert!(builder.build().is_ok());
     ///
-    /// let opts = TreeConfigBuilder::new()
-    ///     .emit_trailing_newline(false)
+    /// // Box drawing characters are double width, but the paddings are not.
+    /// let config = TreeConfigBuilder::new()
+    ///     .ambiguous_width(AmbiWidth::Wide)
     ///     .build();
+    /// assert!(builder.build_for(&config).is_err());
+    /// ```
+    pub fn build_for(&self, config: &TreeConfig) -> Result<CustomEdgeConfig, EdgeWidthError> {
+        self.build_with(&config.width_oracle())
+    }
+
+    /// Builds a `CustomEdgeConfig`, measuring the widths with the given width oracle.
+    fn build_with(&self, width: &dyn Width) -> Result<CustomEdgeConfig, EdgeWidthError> {
+        let parts = &self.parts;
+        let combinations = [
+            (
+                "branch",
+                &parts.branch,
+                "branch_padding",
+                &parts.branch_padding,
+            ),
+            (
+                "last_branch",
+                &parts.last_branch,
+                "branch_padding",
+                &parts.branch_padding,
+            ),
+            (
+                "continuation",
+                &parts.continuation,
+                "continuation_padding",
+                &parts.continuation_padding,
+            ),
+            (
+                "last_continuation",
+                &parts.last_continuation,
+                "last_continuation_padding",
+                &parts.last_continuation_padding,
+            ),
+        ];
+        let widths = combinations
+            .iter()
+            .map(|(_, prefix, _, padding)| {
+                width::visible_width(prefix, width) + width::visible_width(padding, width)
+            })
+            .collect::<Vec<_>>();
+        if let Some(i) = widths.iter().position(|&w| w != widths[0]) {
+            return Err(EdgeWidthError {
+                expected: (combinations[0].0, combinations[0].2, widths[0]),
+                actual: (combinations[i].0, combinations[i].2, widths[i]),
+            });
+        }
+
+        Ok(CustomEdgeConfig {
+            parts: Arc::new(parts.clone()),
+        })
+    }
+}
+
+/// Error for edge strings with inconsistent display widths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeWidthError {
+    /// Names of the reference prefix and padding, and their total width.
+    expected: (&'static str, &'static str, usize),
+    /// Names of the mismatched prefix and padding, and their total width.
+    actual: (&'static str, &'static str, usize),
+}
+
+impl fmt::Display for EdgeWidthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (expected_prefix, expected_padding, expected_width) = self.expected;
+        let (actual_prefix, actual_padding, actual_width) = self.actual;
+        write!(
+            f,
+            "Inconsistent edge widths: `{}` + `{}` has width {}, but `{}` + `{}` has width {}",
+            expected_prefix,
+            expected_padding,
+            expected_width,
+            actual_prefix,
+            actual_padding,
+            actual_width
+        )
+    }
+}
+
+impl error::Error for EdgeWidthError {}
+
+/// Returns the heavier variant of the edge character.
+///
+/// Horizontal lines become heavy, and junctions become ones with a light vertical line and a
+/// heavy horizontal line.
+/// Characters without heavier variants are returned as is.
+pub(crate) fn emphasized_char(c: char) -> char {
+    match c {
+        '-' => '=',
+        '\u{2500}' => '\u{2501}',
+        '\u{2504}' => '\u{2505}',
+        '\u{2508}' => '\u{2509}',
+        '\u{251C}' => '\u{251D}',
+        '\u{2514}' | '\u{2570}' => '\u{2515}',
+        c => c,
+    }
+}
+
+/// Returns the string with non-ASCII characters replaced with ASCII characters of the same
+/// display width.
+fn ascii_fallback(s: &str) -> String {
+    let mut fallback = String::with_capacity(s.len());
+    let mut buf = [0; 4];
+    for c in s.chars() {
+        if c.is_ascii() {
+            fallback.push(c);
+            continue;
+        }
+        let replacement = ascii_fallback_char(c);
+        let width = width::str_width(c.encode_utf8(&mut buf));
+        fallback.extend(std::iter::repeat(replacement).take(width));
+    }
+    fallback
+}
+
+/// Returns the ASCII character which looks similar to the given non-ASCII character.
+fn ascii_fallback_char(c: char) -> char {
+    match c {
+        // Vertical lines.
+        '\u{2502}' | '\u{2503}' | '\u{2506}' | '\u{2507}' | '\u{250A}' | '\u{250B}'
+        | '\u{254E}' | '\u{254F}' | '\u{2551}' | '\u{2575}' | '\u{2577}' | '\u{2579}'
+        | '\u{257B}' | '\u{257D}' | '\u{257F}' => '|',
+        // Horizontal lines.
+        '\u{2500}' | '\u{2501}' | '\u{2504}' | '\u{2505}' | '\u{2508}' | '\u{2509}'
+        | '\u{254C}' | '\u{254D}' | '\u{2550}' | '\u{2574}' | '\u{2576}' | '\u{2578}'
+        | '\u{257A}' | '\u{257C}' | '\u{257E}' => '-',
+        // Tees opening to the right.
+        '\u{251C}'..='\u{2523}' | '\u{255E}'..='\u{2560}' => '|',
+        // Up and right corners.
+        '\u{2514}'..='\u{2517}' | '\u{2558}'..='\u{255A}' | '\u{2570}' => '`',
+        c if is_box_drawing(c) => '+',
+        '\u{25E6}' => 'o',
+        '\u{2023}' | '\u{2192}' | '\u{25B6}' | '\u{25B8}' => '>',
+        c if c.is_whitespace() => ' ',
+        _ => '*',
+    }
+}
+
+/// Returns the edge config for the given locale and terminal.
+///
+/// The locale has the form of `language[_territory][.codeset][@modifier]`.
+fn edge_for_environment(locale: Option<&OsStr>, term: Option<&OsStr>) -> EdgeConfig {
+    if term.is_some_and(|term| term == "dumb") {
+        return EdgeConfig::Ascii;
+    }
+    let locale = match locale.and_then(OsStr::to_str) {
+        Some(v) => v,
+        None => return EdgeConfig::Ascii,
+    };
+    let locale = locale.split('@').next().unwrap_or(locale);
+    let (language, codeset) = match locale.split_once('.') {
+        Some((language, codeset)) => (language, codeset),
+        None => return EdgeConfig::Ascii,
+    };
+    if !codeset.eq_ignore_ascii_case("utf-8") && !codeset.eq_ignore_ascii_case("utf8") {
+        return EdgeConfig::Ascii;
+    }
+    let language = language.split('_').next().unwrap_or(language);
+    match language {
+        "ja" | "ko" | "zh" => EdgeConfig::UnicodeDoubleWidth,
+        _ => EdgeConfig::UnicodeSingleWidth,
+    }
+}
+
+/// Characters of a box drawn around item content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BoxChars {
+    /// Top left corner.
+    top_left: char,
+    /// Top right corner.
+    top_right: char,
+    /// Bottom left corner.
+    bottom_left: char,
+    /// Bottom right corner.
+    bottom_right: char,
+    /// Horizontal line.
+    horizontal: char,
+    /// Vertical line.
+    vertical: char,
+    /// Junction of the horizontal line and the line downward.
+    tee_down: char,
+    /// Junction of the horizontal line and the line upward.
+    tee_up: char,
+    /// Junction of the vertical line and the line rightward.
+    tee_right: char,
+    /// Junction of the vertical line and the line leftward.
+    tee_left: char,
+    /// Crossing of the horizontal and vertical lines.
+    cross: char,
+}
+
+impl BoxChars {
+    /// ASCII box.
+    const ASCII: Self = Self::new(['+', '+', '+', '+', '-', '|', '+', '+', '+', '+', '+']);
+    /// Box with light lines.
+    const LIGHT: Self = Self::new([
+        '\u{250C}', '\u{2510}', '\u{2514}', '\u{2518}', '\u{2500}', '\u{2502}', '\u{252C}',
+        '\u{2534}', '\u{251C}', '\u{2524}', '\u{253C}',
+    ]);
+    /// Box with light lines and rounded corners.
+    const ROUNDED: Self = Self::new([
+        '\u{256D}', '\u{256E}', '\u{2570}', '\u{256F}', '\u{2500}', '\u{2502}', '\u{252C}',
+        '\u{2534}', '\u{251C}', '\u{2524}', '\u{253C}',
+    ]);
+    /// Box with heavy lines.
+    const HEAVY: Self = Self::new([
+        '\u{250F}', '\u{2513}', '\u{2517}', '\u{251B}', '\u{2501}', '\u{2503}', '\u{2533}',
+        '\u{253B}', '\u{2523}', '\u{252B}', '\u{254B}',
+    ]);
+    /// Box with double lines.
+    const DOUBLE: Self = Self::new([
+        '\u{2554}', '\u{2557}', '\u{255A}', '\u{255D}', '\u{2550}', '\u{2551}', '\u{2566}',
+        '\u{2569}', '\u{2560}', '\u{2563}', '\u{256C}',
+    ]);
+
+    /// Creates a new `BoxChars` from the corners (top left, top right, bottom left, and bottom
+    /// right), the horizontal line, the vertical line, the junctions (downward, upward,
+    /// rightward, and leftward), and the crossing.
+    const fn new(chars: [char; 11]) -> Self {
+        Self {
+            top_left: chars[0],
+            top_right: chars[1],
+            bottom_left: chars[2],
+            bottom_right: chars[3],
+            horizontal: chars[4],
+            vertical: chars[5],
+            tee_down: chars[6],
+            tee_up: chars[7],
+            tee_right: chars[8],
+            tee_left: chars[9],
+            cross: chars[10],
+        }
+    }
+
+    /// Returns the character connecting lines in the given directions.
+    ///
+    /// Returns a space if no lines are connected.
+    pub(crate) fn junction(&self, up: bool, down: bool, left: bool, right: bool) -> char {
+        match (up, down, left, right) {
+            (false, false, false, false) => ' ',
+            (_, _, false, false) => self.vertical,
+            (false, false, _, _) => self.horizontal,
+            (false, true, false, true) => self.top_left,
+            (false, true, true, false) => self.top_right,
+            (true, false, false, true) => self.bottom_left,
+            (true, false, true, false) => self.bottom_right,
+            (false, true, true, true) => self.tee_down,
+            (true, false, true, true) => self.tee_up,
+            (true, true, false, true) => self.tee_right,
+            (true, true, true, false) => self.tee_left,
+            (true, true, true, true) => self.cross,
+        }
+    }
+
+    /// Returns the content surrounded by the box.
+    ///
+    /// A trailing newline of the content is kept outside of the box.
+    pub(crate) fn wrap(&self, content: &str, config: &TreeConfig) -> String {
+        let (body, newline) = match content.strip_suffix('\n') {
+            Some(body) => (body, "\n"),
+            None => (content, ""),
+        };
+        let width = body
+            .split('\n')
+            .map(|line| config.str_width(line))
+            .max()
+            .unwrap_or(0);
+        let rule = || std::iter::repeat(self.horizontal).take(width + 2);
+
+        let mut boxed = String::new();
+        boxed.push(self.top_left);
+        boxed.extend(rule());
+        boxed.push(self.top_right);
+        for line in body.split('\n') {
+            boxed.push('\n');
+            boxed.push(self.vertical);
+            boxed.push(' ');
+            boxed.push_str(line);
+            boxed.extend(std::iter::repeat(' ').take(width - config.str_width(line) + 1));
+            boxed.push(self.vertical);
+        }
+        boxed.push('\n');
+        boxed.push(self.bottom_left);
+        boxed.extend(rule());
+        boxed.push(self.bottom_right);
+        boxed.push_str(newline);
+        boxed
+    }
+}
+
+/// Kind of a node, shown as a suffix of the label.
+///
+/// Suffixes follow the conventions of `tree -F` and `ls -F`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Directory, shown with `/`.
+    Directory,
+    /// Executable file, shown with `*`.
+    Executable,
+    /// Symbolic link, shown with `@`.
+    Symlink,
+    /// Named pipe, shown with `|`.
+    Fifo,
+    /// Socket, shown with `=`.
+    Socket,
+    /// Custom kind, shown with the given suffix.
+    Custom(String),
+}
+
+impl NodeKind {
+    /// Returns the suffix put after the label.
+    pub fn suffix(&self) -> &str {
+        match self {
+            Self::Directory => "/",
+            Self::Executable => "*",
+            Self::Symlink => "@",
+            Self::Fifo => "|",
+            Self::Socket => "=",
+            Self::Custom(suffix) => suffix,
+        }
+    }
+}
+
+/// Item style.
+#[derive(Debug, Clone)]
+pub struct ItemStyle {
+    /// Whether the item is the last child.
+    is_last_child: bool,
+    /// Edge config.
+    ///
+    /// `None` means the edge is chosen by the printer from the depth of the item.
+    edge: Option<EdgeConfig>,
+    /// Whether the unspecified edge is taken from the parent item.
+    inherit_edge: bool,
+    /// Whether continuation lines have no prefixes and paddings of the item.
+    flush_left: bool,
+    /// Whether the connector of the first line is emphasized.
+    emphasized: bool,
+    /// Whether the content is surrounded by a box.
+    boxed: bool,
+    /// Annotation put at the right of the first line.
+    annotation: Option<String>,
+    /// Kind of the item, shown as a suffix of the first line.
+    kind: Option<NodeKind>,
+    /// Icon put between the connector and the label.
+    icon: Option<String>,
+    /// Length of the end of the first line of the label which is not truncated or wrapped.
+    label_tail_len: usize,
+    /// Color of the edge.
+    ///
+    /// `None` means the color is chosen by the printer.
+    #[cfg(feature = "ansi")]
+    edge_color: Option<Color>,
+    /// Text style of the label.
+    #[cfg(feature = "ansi")]
+    label_style: TextStyle,
+}
+
+impl ItemStyle {
+    /// Creates a new `ItemStyle`.
+    ///
+    /// Prefer [`last()`][last] and [`non_last()`][non_last] when `is_last_child` argument is
+    /// constant.
+    ///
+    /// [last]: #method.last
+    /// [non_last]: #method.non_last
+    pub fn new(is_last_child: bool, edge: EdgeConfig) -> Self {
+        Self {
+            is_last_child,
+            edge: Some(edge),
+            inherit_edge: false,
+            flush_left: false,
+            emphasized: false,
+            boxed: false,
+            annotation: None,
+            kind: None,
+            icon: None,
+            label_tail_len: 0,
+            #[cfg(feature = "ansi")]
+            edge_color: None,
+            #[cfg(feature = "ansi")]
+            label_style: TextStyle::new(),
+        }
+    }
+
+    /// Creates a new `ItemStyle` whose edge config is chosen by the printer.
+    ///
+    /// The printer uses the edge config for the depth of the item, which is set by
+    /// [`TreeConfigBuilder::depth_edges()`][depth_edges].
+    ///
+    /// [depth_edges]: struct.TreeConfigBuilder.html#method.depth_edges
+    pub fn by_depth(is_last_child: bool) -> Self {
+        Self {
+            is_last_child,
+            edge: None,
+            inherit_edge: false,
+            flush_left: false,
+            emphasized: false,
+            boxed: false,
+            annotation: None,
+            kind: None,
+            icon: None,
+            label_tail_len: 0,
+            #[cfg(feature = "ansi")]
+            edge_color: None,
+            #[cfg(feature = "ansi")]
+            label_style: TextStyle::new(),
+        }
+    }
+
+    /// Creates a new `ItemStyle` whose edge config is the same as the parent item.
+    ///
+    /// This is useful for trees with different edges for each subtree, since only the root of
+    /// each subtree needs to specify the edge.
+    /// Top-level items use the edge config for the depth, as [`by_depth()`][by_depth] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeSingleWidth), "foo")?;
+    /// writer.open_node(ItemStyle::inherit(false), "bar")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::inherit(true), "baz")?;
+    /// writer.open_node(ItemStyle::inherit(true), "qux")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::inherit(true), "quux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{251C}\u{2500}\u{2500} foo\n\
+    ///                 \u{2502}   \u{251C}\u{2500}\u{2500} bar\n\
+    ///                 \u{2502}   \u{2514}\u{2500}\u{2500} baz\n\
+    ///                 \u{2502}       \u{2514}\u{2500}\u{2500} qux\n\
+    ///                 `-- quux\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [by_depth]: #method.by_depth
+    pub fn inherit(is_last_child: bool) -> Self {
+        Self {
+            inherit_edge: true,
+            ..Self::by_depth(is_last_child)
+        }
+    }
+
+    /// Creates a new `ItemStyle` for the last child.
+    ///
+    /// This is same as `ItemStyle::new(true, edge)`.
+    pub fn last(edge: EdgeConfig) -> Self {
+        Self::new(true, edge)
+    }
+
+    /// Creates a new `ItemStyle` for a non-last child.
+    ///
+    /// This is same as `ItemStyle::new(false, edge)`.
+    pub fn non_last(edge: EdgeConfig) -> Self {
+        Self::new(false, edge)
+    }
+
+    /// Renders continuation lines of the label without the prefix and padding of the item.
+    ///
+    /// Only the guides of the ancestors are put before the second and subsequent lines of the
+    /// label, so that embedded code blocks or quoted output keep their original left margin
+    /// relative to the item.
+    /// Lines of descendants are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_flush_left(true);
+    /// writer.open_node(style, "code:\nfn main() {\n    run();\n}")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- foo\n\
+    ///                 |   `-- code:\n\
+    ///                 |   fn main() {\n\
+    ///                 |       run();\n\
+    ///                 |   }\n\
+    ///                 `-- bar\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn with_flush_left(mut self, v: bool) -> Self {
+        self.flush_left = v;
+        self
+    }
+
+    /// Emphasizes the item by drawing the connector of the first line with heavier lines.
+    ///
+    /// Horizontal lines of the connector are replaced with heavy ones (`-` with `=` for ASCII
+    /// edges), and junctions are replaced with corresponding ones, so that the vertical guide
+    /// of the siblings stays light.
+    /// This is useful to mark the "current" item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// let edge = EdgeConfig::UnicodeSingleWidth;
+    /// writer.open_node(ItemStyle::non_last(edge.clone()).emphasized(), "foo")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(edge.clone()).emphasized(), "bar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{251D}\u{2501}\u{2501} foo\n\
+    ///                 \u{2515}\u{2501}\u{2501} bar\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn emphasized(mut self) -> Self {
+        self.emphasized = true;
+        self
+    }
+
+    /// Draws a box around the content.
+    ///
+    /// The connector is attached to the top border of the box.
+    /// Lines of the box match the edge: ASCII edges get `+-+` boxes, and Unicode edges get
+    /// boxes of the same line style, such as rounded or heavy ones.
+    /// This is useful to emphasize embedded snippets or error payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// let edge = EdgeConfig::rounded();
+    /// writer.open_node(ItemStyle::non_last(edge.clone()), "build")?;
+    /// writer.open_node(ItemStyle::last(edge.clone()).boxed(), "error: oops\n  at main.rs:3")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(edge.clone()), "test")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{251C}\u{2500}\u{2500} build\n\
+    ///                 \u{2502}   \u{2570}\u{2500}\u{2500} \u{256D}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{256E}\n\
+    ///                 \u{2502}       \u{2502} error: oops    \u{2502}\n\
+    ///                 \u{2502}       \u{2502}   at main.rs:3 \u{2502}\n\
+    ///                 \u{2502}       \u{2570}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{256F}\n\
+    ///                 \u{2570}\u{2500}\u{2500} test\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn boxed(mut self) -> Self {
+        self.boxed = true;
+        self
+    }
+
+    /// Sets the annotation put at the right of the first line of the content.
+    ///
+    /// The annotation is right-aligned to the column set by
+    /// [`TreeConfigBuilder::annotation_column()`][annotation_column], considering the width
+    /// of the line prefixes, and the space before it is filled with
+    /// [`TreeConfigBuilder::annotation_leader()`][annotation_leader].
+    /// This is useful to show sizes, counts, durations, and so on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().annotation_column(24).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// let style = ItemStyle::non_last(EdgeConfig::Ascii).with_annotation("4 KB");
+    /// writer.open_node(style, "src")?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("1.2 MB");
+    /// writer.open_node(style, "lib.rs")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("320 B");
+    /// writer.open_node(style, "Cargo.toml")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- src             4 KB\n\
+    ///                 |   `-- lib.rs    1.2 MB\n\
+    ///                 `-- Cargo.toml     320 B\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [annotation_column]: struct.TreeConfigBuilder.html#method.annotation_column
+    /// [annotation_leader]: struct.TreeConfigBuilder.html#method.annotation_leader
+    pub fn with_annotation(mut self, annotation: impl Into<String>) -> Self {
+        self.annotation = Some(annotation.into());
+        self
+    }
+
+    /// Sets the color of the edge, overriding [`TreeConfigBuilder::edge_color()`][edge_color].
+    ///
+    /// Only non-whitespace parts of the line prefixes are colored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_edge_color(Color::Blue);
+    /// writer.open_node(style, "foo\nbar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "\x1b[34m`--\x1b[39m foo\n    bar\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [edge_color]: struct.TreeConfigBuilder.html#method.edge_color
+    #[cfg(feature = "ansi")]
+    pub fn with_edge_color(mut self, color: Color) -> Self {
+        self.edge_color = Some(color);
+        self
+    }
+
+    /// Sets the kind of the item, whose suffix is put at the end of the first line of the label.
+    ///
+    /// The suffix is put before the annotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, NodeKind, TreeConfig, TreePrinter};
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), TreeConfig::new());
+    /// let style = ItemStyle::non_last(EdgeConfig::Ascii).with_kind(NodeKind::Directory);
+    /// writer.open_node(style, "src")?;
+    /// writer.close_node()?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_kind(NodeKind::Executable);
+    /// writer.open_node(style, "run.sh\nstarts the server")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- src/\n\
+    ///                 `-- run.sh*\n\
+    ///                 \x20   starts the server\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn with_kind(mut self, kind: NodeKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Sets the icon put between the connector and the label, such as an emoji.
+    ///
+    /// A space is put between the icon and the label, and the following lines of the label are
+    /// indented to the text of the first line.
+    /// The display width of the icon is measured by the width oracle of the config, so that
+    /// annotations and wrapped lines keep the alignment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().annotation_column(16).build();
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// let style = ItemStyle::non_last(EdgeConfig::Ascii)
+    ///     .with_icon(">")
+    ///     .with_annotation("1");
+    /// writer.open_node(style, "src\nsources")?;
+    /// writer.close_node()?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("2");
+    /// writer.open_node(style, "README")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- > src      1\n\
+    ///                 |     sources\n\
+    ///                 `-- README     2\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Sets the text style of the label.
+    ///
+    /// The style is applied to each non-empty line of the label separately, so edges of the
+    /// following lines are not affected.
+    /// See [`TextStyle`] for an example.
+    ///
+    /// [`TextStyle`]: struct.TextStyle.html
+    #[cfg(feature = "ansi")]
+    pub fn with_label_style(mut self, style: TextStyle) -> Self {
+        self.label_style = style;
+        self
+    }
+
+    /// Returns whether the item is the last child.
+    pub(crate) fn is_last_child(&self) -> bool {
+        self.is_last_child
+    }
+
+    /// Returns whether continuation lines have no prefixes and paddings of the item.
+    pub(crate) fn is_flush_left(&self) -> bool {
+        self.flush_left
+    }
+
+    /// Returns whether the connector of the first line is emphasized.
+    pub(crate) fn is_emphasized(&self) -> bool {
+        self.emphasized
+    }
+
+    /// Returns whether the content is surrounded by a box.
+    pub(crate) fn is_boxed(&self) -> bool {
+        self.boxed
+    }
+
+    /// Returns the annotation put at the right of the first line.
+    pub(crate) fn annotation(&self) -> Option<&str> {
+        self.annotation.as_deref()
+    }
+
+    /// Returns the kind of the item.
+    pub(crate) fn kind(&self) -> Option<&NodeKind> {
+        self.kind.as_ref()
+    }
+
+    /// Returns the icon of the item.
+    pub(crate) fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    /// Keeps the end of the first line of the label with the given length in bytes from
+    /// truncation and wrapping, such as the target of an alias.
+    pub(crate) fn with_label_tail_len(mut self, len: usize) -> Self {
+        self.label_tail_len = len;
+        self
+    }
+
+    /// Returns the length of the end of the first line which is not truncated or wrapped.
+    pub(crate) fn label_tail_len(&self) -> usize {
+        self.label_tail_len
+    }
+
+    /// Returns the color of the edge.
+    #[cfg(feature = "ansi")]
+    pub(crate) fn edge_color(&self) -> Option<Color> {
+        self.edge_color
+    }
+
+    /// Returns the text style of the label.
+    #[cfg(feature = "ansi")]
+    pub(crate) fn label_style(&self) -> &TextStyle {
+        &self.label_style
+    }
+
+    /// Returns the edge config.
+    ///
+    /// `EdgeConfig::Ascii` is returned if the edge is not resolved yet.
+    pub(crate) fn edge(&self) -> &EdgeConfig {
+        /// Fallback edge config.
+        static DEFAULT_EDGE: EdgeConfig = EdgeConfig::Ascii;
+
+        self.edge.as_ref().unwrap_or(&DEFAULT_EDGE)
+    }
+
+    /// Fills unspecified styles with the defaults of the tree for the given depth.
+    ///
+    /// `parent_edge` is the edge config of the parent item, if any.
+    pub(crate) fn resolve(
+        mut self,
+        config: &TreeConfig,
+        depth: usize,
+        parent_edge: Option<&EdgeConfig>,
+    ) -> Self {
+        if self.edge.is_none() {
+            self.edge = match parent_edge {
+                Some(edge) if self.inherit_edge => Some(edge.clone()),
+                _ => Some(config.edge_for_depth(depth)),
+            };
+        }
+        #[cfg(feature = "ansi")]
+        {
+            if config.color_mode == ColorMode::Never {
+                self.edge_color = None;
+                self.label_style = TextStyle::new();
+            } else {
+                self.edge_color = self
+                    .edge_color
+                    .or_else(|| config.edge_color_for_depth(depth))
+                    .map(|color| color.downgrade(config.color_level));
+                self.label_style = self.label_style.downgrade(config.color_level);
+            }
+        }
+        self
+    }
+}
+
+/// `TreeConfig` builder.
+///
+/// This is not `Copy`, since [`TreeConfig`] is not `Copy`.
+///
+/// [`TreeConfig`]: struct.TreeConfig.html
+#[derive(Default, Debug, Clone)]
+pub struct TreeConfigBuilder {
+    /// Current config.
+    config: TreeConfig,
+    /// Whether to limit the width of lines to the terminal width.
+    #[cfg(feature = "terminal-size")]
+    fit_terminal_width: bool,
+}
+
+impl TreeConfigBuilder {
+    /// Creates a new `TreeConfig`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let the writer emit trailing whitespace if the line has no content.
+    ///
+    /// Guides of ancestors are drawn on empty lines of multiline labels regardless of this flag.
+    /// This flag only controls whether whitespace after the last visible guide is emitted.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .emit_trailing_whitespace(true)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n\nbar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// // Note that `"    "` is emitted for an empty line between "foo" and "bar".
+    /// assert_eq!(buf, "`-- foo\n    \n    bar\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn emit_trailing_whitespace(&mut self, v: bool) -> &mut Self {
+        self.config.emit_trailing_whitespace = v;
+        self
+    }
+
+    /// Let the writer emit trailing newline automatically at the end of the tree.
+    ///
+    /// The value is `true` by default.
+    ///
+    /// # Examples
+    ///
+    /// By default, the tree printer will put a newline at the end of the tree, if there is not.
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let opts = TreeConfig::new();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// // Note that the newline character at the end of the output.
+    /// assert_eq!(buf, "`-- foo\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// If there are already a newline, the printer does not emit an additional newline.
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let opts = TreeConfig::new();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// // Feed a trailing newline explicitly.
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// // Note that there are only one newline character at the end of the output.
+    /// assert_eq!(buf, "`-- foo\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// With this flag unset, the tree printer does not emit an additional newline.
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .emit_trailing_newline(false)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// // Note that there are no newline characters at the end of the output.
+    /// assert_eq!(buf, "`-- foo");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn emit_trailing_newline(&mut self, v: bool) -> &mut Self {
+        self.config.emit_trailing_newline = v;
+        self
+    }
+
+    /// Sets the newline sequence emitted at the end of each output line.
+    ///
+    /// Line breaks in labels are also emitted as this sequence.
+    /// This is useful for output written to Windows files or to protocols requiring CRLF.
+    ///
+    /// The value is `"\n"` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().newline("\r\n").build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\nbar")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "|-- foo\r\n|   bar\r\n`-- baz\r\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn newline(&mut self, newline: impl Into<String>) -> &mut Self {
+        self.config.newline = newline.into();
+        self
+    }
+
+    /// Let the printer emit a summary line such as `2 branches, 3 leaves` after the tree.
+    ///
+    /// A node with children is counted as a branch, and a node without children is counted as
+    /// a leaf.
+    /// The summary is separated from the tree by an empty line, as the [`tree`
+    /// command][unix-tree] does.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().emit_summary_footer(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(".\n".to_owned(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, ".\n|-- foo\n|   `-- bar\n`-- baz\n\n1 branch, 2 leaves\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [unix-tree]: http://mama.indstate.edu/users/ice/tree/
+    pub fn emit_summary_footer(&mut self, v: bool) -> &mut Self {
+        self.config.emit_summary_footer = v;
+        self
+    }
+
+    /// Let the printer emit a row with guides only after a subtree, if the next sibling follows.
+    ///
+    /// Spacer rows are emitted after non-last nodes with children.
+    /// This improves readability of trees with large multiline nodes.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().emit_spacer_rows(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeSingleWidth), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "bar")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "baz")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{251C}\u{2500}\u{2500} foo\n\
+    ///                 \u{2502}   \u{2514}\u{2500}\u{2500} bar\n\
+    ///                 \u{2502}\n\
+    ///                 \u{2514}\u{2500}\u{2500} baz\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn emit_spacer_rows(&mut self, v: bool) -> &mut Self {
+        self.config.emit_spacer_rows = v;
+        self
+    }
+
+    /// Aligns the second and subsequent lines of labels to the text of the first line.
+    ///
+    /// With this flag set, the paddings of continuation lines are computed from the display
+    /// width of the emitted first line prefix and padding, instead of the fixed paddings of the
+    /// edge config.
+    /// This is useful for edge configs whose paddings assume a specific terminal, such as
+    /// `EdgeConfig::UnicodeDoubleWidth` rendered on terminals with narrow box drawing
+    /// characters.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().hanging_indent(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeDoubleWidth), "foo\nbar")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeDoubleWidth), "baz\nqux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{251C}\u{2500} foo\n\
+    ///                 \u{2502}  bar\n\
+    ///                 \u{2514}\u{2500} baz\n   \
+    ///                 qux\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn hanging_indent(&mut self, v: bool) -> &mut Self {
+        self.config.hanging_indent = v;
+        self
+    }
+
+    /// Sets the display width of East Asian ambiguous width characters.
+    ///
+    /// This applies to all width computations: the widths of labels, annotations, truncation,
+    /// wrapping, and the edges including the built-in presets.
+    /// With `AmbiWidth::Wide`, paddings of continuation lines are computed from the display
+    /// width of the emitted first line prefix, as [`hanging_indent()`][hanging_indent] does, so
+    /// that edge configs designed for narrow box drawing characters keep the alignment.
+    ///
+    /// The value is `AmbiWidth::Narrow` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{AmbiWidth, EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .ambiguous_width(AmbiWidth::Wide)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeSingleWidth), "foo\nbar")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "baz\nqux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// // Each ruled line character occupies 2 columns.
+    /// let expected = "\u{251C}\u{2500}\u{2500} foo\n\
+    ///                 \u{2502}     bar\n\
+    ///                 \u{2514}\u{2500}\u{2500} baz\n       \
+    ///                 qux\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [hanging_indent]: #method.hanging_indent
+    pub fn ambiguous_width(&mut self, ambiguous: AmbiWidth) -> &mut Self {
+        self.config.ambiguous_width = ambiguous;
+        self
+    }
+
+    /// Sets a function to generate a gutter put at the very start of each line of the tree.
+    ///
+    /// The function receives the zero-based index of the line and the depth of the item the
+    /// line belongs to (`0` for top-level items), and returns the string to be emitted before
+    /// the line prefix.
+    /// This is useful to prepend timestamps, log levels, or markers uniformly.
+    /// The gutter is not put before the summary footer.
+    ///
+    /// The value is `None` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .line_gutter(Some(|line, depth| format!("{:>2}:{} ", line + 1, depth)))
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar\nbaz")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "qux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = " 1:0 |-- foo\n\
+    ///                 \x202:1 |   `-- bar\n\
+    ///                 \x203:1 |       baz\n\
+    ///                 \x204:0 `-- qux\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn line_gutter(&mut self, gutter: Option<fn(usize, usize) -> String>) -> &mut Self {
+        self.config.line_gutter = gutter;
+        self
+    }
+
+    /// Sets the oracle to measure the display width of labels and edges.
+    ///
+    /// The oracle is used to align annotations, metadata columns, and boxes.
+    /// See [`Width`] for an example.
+    ///
+    /// The value is [`UnicodeWidth`] if `unicode-width` feature is enabled, and
+    /// [`CharCountWidth`] otherwise.
+    ///
+    /// [`Width`]: trait.Width.html
+    /// [`UnicodeWidth`]: struct.UnicodeWidth.html
+    /// [`CharCountWidth`]: struct.CharCountWidth.html
+    pub fn width(&mut self, width: impl Width + 'static) -> &mut Self {
+        self.config.width = Arc::new(width);
+        self
+    }
+
+    /// Sets the column where annotations set by [`ItemStyle::with_annotation()`] end.
+    ///
+    /// The column is counted from the start of the tree prefixes, and the annotations are
+    /// right-aligned to it.
+    /// If the line is too long, the annotation is put after the minimum space described in
+    /// [`annotation_leader()`][annotation_leader].
+    ///
+    /// The value is `80` by default.
+    ///
+    /// [`ItemStyle::with_annotation()`]: struct.ItemStyle.html#method.with_annotation
+    /// [annotation_leader]: #method.annotation_leader
+    pub fn annotation_column(&mut self, column: usize) -> &mut Self {
+        self.config.annotation_column = column;
+        self
+    }
+
+    /// Sets the character to fill the space between labels and annotations.
+    ///
+    /// Annotations are set by [`ItemStyle::with_annotation()`] and
+    /// [`TreeNode::set_annotation()`].
+    /// With a space, at least two spaces are put between labels and annotations.
+    /// With a non-space leader, leaders are separated from labels and annotations by a space,
+    /// and at least two leaders are put (`foo ........ 1.2 MB`).
+    ///
+    /// The value is `' '` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .annotation_column(20)
+    ///     .annotation_leader('.')
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// let style = ItemStyle::non_last(EdgeConfig::Ascii).with_annotation("4 KB");
+    /// writer.open_node(style, "src")?;
+    /// writer.close_node()?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("320 B");
+    /// writer.open_node(style, "Cargo.toml")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- src ....... 4 KB\n\
+    ///                 `-- Cargo.toml .. 320 B\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ItemStyle::with_annotation()`]: struct.ItemStyle.html#method.with_annotation
+    /// [`TreeNode::set_annotation()`]: struct.TreeNode.html#method.set_annotation
+    pub fn annotation_leader(&mut self, leader: char) -> &mut Self {
+        self.config.annotation_leader = leader;
+        self
+    }
+
+    /// Let the printer quote labels which cannot be parsed back unambiguously.
+    ///
+    /// Labels containing newlines or other control characters, labels starting with `"` or
+    /// edge-like characters, and labels with leading or trailing whitespaces are enclosed by `"`,
+    /// and special characters in them are escaped by backslashes.
+    /// Such output can be parsed back losslessly using [`ParseOptions::unquote_labels()`].
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().quote_labels(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\nbar")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "say \"hi\"")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "|-- \"foo\\nbar\"\n`-- say \"hi\"\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ParseOptions::unquote_labels()`]: struct.ParseOptions.html#method.unquote_labels
+    pub fn quote_labels(&mut self, v: bool) -> &mut Self {
+        self.config.quote_labels = v;
+        self
+    }
+
+    /// Sets the width of tab stops to expand tabs in labels.
+    ///
+    /// Tab stops are counted from the start of the content of each line, so that the expanded
+    /// labels keep their alignment regardless of the line prefixes.
+    /// Zero is the same as `None`, since tab stops cannot be zero columns apart.
+    /// Tabs in labels quoted by [`quote_labels()`][quote_labels] are escaped instead.
+    ///
+    /// The value is `None` (tabs are emitted as is) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().tab_width(Some(4)).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "key\tvalue\nlonger\tvalue")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "`-- key value\n\
+    ///                 \x20   longer  value\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [quote_labels]: #method.quote_labels
+    pub fn tab_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.config.tab_width = width;
+        self
+    }
+
+    /// Sets whether to strip carriage returns which are not part of CRLF line breaks.
+    ///
+    /// CRLF line breaks in labels are always treated as single line breaks.
+    /// Stray carriage returns move the cursor of terminals to the head of the line and break the
+    /// tree, so they can be removed by this option.
+    /// Carriage returns in labels quoted by [`quote_labels()`][quote_labels] are escaped instead.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().strip_carriage_returns(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\r\nbar\r\n")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "progress\r100%")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- foo\n\
+    ///                 |   bar\n\
+    ///                 `-- progress100%\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [quote_labels]: #method.quote_labels
+    pub fn strip_carriage_returns(&mut self, strip: bool) -> &mut Self {
+        self.config.strip_carriage_returns = strip;
+        self
+    }
+
+    /// Sets whether to wrap each non-empty line of labels in bidirectional isolates.
+    ///
+    /// Each line is put between FSI (`U+2068`) and PDI (`U+2069`), so that right-to-left text
+    /// such as Arabic and Hebrew names cannot visually reorder the tree edges around it.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().isolate_bidi(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "\u{5E9}\u{5DC}\u{5D5}\u{5DD}\n\nfoo")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "`-- \u{2068}\u{5E9}\u{5DC}\u{5D5}\u{5DD}\u{2069}\n\
+    ///                 \n\
+    ///                 \x20   \u{2068}foo\u{2069}\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn isolate_bidi(&mut self, isolate: bool) -> &mut Self {
+        self.config.isolate_bidi = isolate;
+        self
+    }
+
+    /// Lets the printer emit a mirrored tree, whose edges are on the right side of labels.
+    ///
+    /// Line prefixes are mirrored horizontally and put after the content of each line, and the
+    /// lines are aligned to the right, so that nesting grows leftward.
+    /// This is suitable for right-to-left locales and side-by-side comparison views.
+    ///
+    /// The whole tree is buffered until [`TreePrinter::finalize()`] is called, since the width
+    /// of the tree is necessary to align the lines.
+    /// Line gutters set by [`line_gutter()`][line_gutter] are not emitted for mirrored trees.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().mirrored(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeSingleWidth), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "bar\nbar2")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "baz")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\x20    foo \u{2500}\u{2500}\u{2524}\n\
+    ///                 \x20bar \u{2500}\u{2500}\u{2518}   \u{2502}\n\
+    ///                 bar2       \u{2502}\n\
+    ///                 \x20    baz \u{2500}\u{2500}\u{2518}\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreePrinter::finalize()`]: struct.TreePrinter.html#method.finalize
+    /// [line_gutter]: #method.line_gutter
+    pub fn mirrored(&mut self, v: bool) -> &mut Self {
+        self.config.mirrored = v;
+        self
+    }
+
+    /// Lets the printer emit a bottom-up tree, where children are put before their parents.
+    ///
+    /// The tree is flipped vertically: the order of siblings is reversed, and the corners of the
+    /// edges are flipped (for example, `\u{250C}\u{2500}\u{2500}` instead of
+    /// `\u{2514}\u{2500}\u{2500}`).
+    /// Lines of each label are kept in order.
+    /// This is suitable for cause chains and "leads to" views of dependencies.
+    ///
+    /// The whole tree is buffered until [`TreePrinter::finalize()`] is called.
+    /// Since top-level items are emitted as the last lines, the root label should be written
+    /// after the tree.
+    /// Line gutters set by [`line_gutter()`][line_gutter] are not emitted for bottom-up trees.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().bottom_up(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::UnicodeSingleWidth), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "bar\nbar2")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::UnicodeSingleWidth), "baz")?;
+    /// let mut buf = writer.finalize()?;
+    /// buf.push_str("root\n");
+    ///
+    /// let expected = "\u{250C}\u{2500}\u{2500} baz\n\
+    ///                 \u{2502}   \u{250C}\u{2500}\u{2500} bar\n\
+    ///                 \u{2502}   \u{2502}   bar2\n\
+    ///                 \u{251C}\u{2500}\u{2500} foo\n\
+    ///                 root\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreePrinter::finalize()`]: struct.TreePrinter.html#method.finalize
+    /// [line_gutter]: #method.line_gutter
+    pub fn bottom_up(&mut self, v: bool) -> &mut Self {
+        self.config.bottom_up = v;
+        self
+    }
+
+    /// Sets the maximum display width of each line of labels.
+    ///
+    /// Longer lines are truncated at grapheme cluster boundaries (or character boundaries without
+    /// `unicode-width` feature), and the ellipsis set by [`ellipsis()`][ellipsis] is appended.
+    /// See [`label_overflow()`][label_overflow] to wrap them instead.
+    ///
+    /// The value is `None` (no limits) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().max_label_width(Some(8)).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "short")?;
+    /// writer.close_node()?;
+    /// let style = ItemStyle::last(EdgeConfig::Ascii);
+    /// writer.open_node(style, "a very long label\nsecond line")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- short\n\
+    ///                 `-- a very \u{2026}\n\
+    ///                 \x20   second \u{2026}\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [ellipsis]: #method.ellipsis
+    /// [label_overflow]: #method.label_overflow
+    pub fn max_label_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.config.max_label_width = width;
+        self
+    }
+
+    /// Sets the maximum display width of output lines.
+    ///
+    /// Labels are truncated so that no lines exceed the given width, considering the widths of
+    /// line prefixes, kind suffixes, boxes, and annotations.
+    /// Deeper items have less room for labels.
+    /// See [`label_overflow()`][label_overflow] to wrap them instead.
+    /// Line gutters set by [`line_gutter()`][line_gutter] are not counted.
+    ///
+    /// The value is `None` (no limits) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().max_width(Some(12)).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "parent item")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "child item\nmore")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "`-- parent \u{2026}\n\
+    ///                 \x20   `-- chi\u{2026}\n\
+    ///                 \x20       more\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [line_gutter]: #method.line_gutter
+    /// [label_overflow]: #method.label_overflow
+    pub fn max_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.config.max_width = width;
+        self
+    }
+
+    /// Limits the number of lines written by the printer.
+    ///
+    /// After the limit is reached, the rest of the tree is discarded and a marker line telling
+    /// the number of nodes not shown is emitted instead, such as
+    /// `\u{2026} output truncated, 2 nodes not shown`.
+    /// The marker line is not counted, and the summary footer is also discarded if it does not
+    /// fit.
+    /// Lines of retained trees rendered by [`TreeNode::render()`] are also limited, in the
+    /// indented layout.
+    ///
+    /// The value is `None` (no limits) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().max_lines(Some(2)).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "baz")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "qux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- foo\n\
+    ///                 |   `-- bar\n\
+    ///                 \u{2026} output truncated, 2 nodes not shown\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreeNode::render()`]: struct.TreeNode.html#method.render
+    pub fn max_lines(&mut self, max: Option<usize>) -> &mut Self {
+        self.config.max_lines = max;
+        self
+    }
+
+    /// Sets how to handle lines of labels longer than the limits.
+    ///
+    /// The limits are [`max_width()`][max_width] and [`max_label_width()`][max_label_width].
+    /// Wrapped lines get the usual continuation prefixes.
+    ///
+    /// The value is `LabelOverflow::Truncate` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, LabelOverflow, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .max_width(Some(16))
+    ///     .label_overflow(LabelOverflow::WordWrap)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "the quick brown fox jumps")?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "over the lazy dog")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "|-- the quick\n\
+    ///                 |   brown fox\n\
+    ///                 |   jumps\n\
+    ///                 `-- over the\n\
+    ///                 \x20   lazy dog\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// Hard wrapping is useful for content without spaces, such as long paths and hashes.
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, LabelOverflow, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .max_width(Some(12))
+    ///     .label_overflow(LabelOverflow::HardWrap)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "0123456789abcdef")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "`-- 01234567\n\
+    ///                 \x20   \u{21AA} 89abcd\n\
+    ///                 \x20   \u{21AA} ef\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [max_width]: #method.max_width
+    /// [max_label_width]: #method.max_label_width
+    pub fn label_overflow(&mut self, overflow: LabelOverflow) -> &mut Self {
+        self.config.label_overflow = overflow;
+        self
+    }
+
+    /// Sets the marker put at the start of lines split by `LabelOverflow::HardWrap`.
+    ///
+    /// The value is `"\u{21AA} "` (`↪ `) by default.
+    pub fn hard_wrap_marker(&mut self, marker: impl Into<String>) -> &mut Self {
+        self.config.hard_wrap_marker = marker.into();
+        self
+    }
+
+    /// Sets the ellipsis appended to truncated labels.
+    ///
+    /// The value is `"\u{2026}"` (`…`) by default.
+    pub fn ellipsis(&mut self, ellipsis: impl Into<String>) -> &mut Self {
+        self.config.ellipsis = ellipsis.into();
+        self
+    }
+
+    /// Sets the edge configs used for items created by [`ItemStyle::by_depth()`].
+    ///
+    /// The first edge config is used for top-level items, the second one for their children,
+    /// and so on.
+    /// Items deeper than the list use the last edge config, or the list is repeated if
+    /// [`cycle_depth_edges()`][cycle_depth_edges] is set.
+    /// If the list is empty, `EdgeConfig::Ascii` is used.
+    ///
+    /// The value is empty by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .depth_edges(vec![EdgeConfig::bold(), EdgeConfig::UnicodeSingleWidth])
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::by_depth(false), "foo")?;
+    /// writer.open_node(ItemStyle::by_depth(false), "bar")?;
+    /// writer.open_node(ItemStyle::by_depth(true), "baz")?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.close_node()?;
+    /// writer.open_node(ItemStyle::by_depth(true), "qux")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\u{2523}\u{2501}\u{2501} foo\n\
+    ///                 \u{2503}   \u{251C}\u{2500}\u{2500} bar\n\
+    ///                 \u{2503}   \u{2502}   \u{2514}\u{2500}\u{2500} baz\n\
+    ///                 \u{2517}\u{2501}\u{2501} qux\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ItemStyle::by_depth()`]: struct.ItemStyle.html#method.by_depth
+    /// [cycle_depth_edges]: #method.cycle_depth_edges
+    pub fn depth_edges(&mut self, edges: Vec<EdgeConfig>) -> &mut Self {
+        self.config.depth_edges = edges.into();
+        self
+    }
+
+    /// Repeats the edge configs set by [`depth_edges()`][depth_edges] for items deeper than the
+    /// list.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// [depth_edges]: #method.depth_edges
+    pub fn cycle_depth_edges(&mut self, v: bool) -> &mut Self {
+        self.config.cycle_depth_edges = v;
+        self
+    }
+
+    /// Sets the color of edges.
+    ///
+    /// Each item can override this with [`ItemStyle::with_edge_color()`].
+    /// Only non-whitespace parts of the line prefixes are colored, and the foreground color is
+    /// reset right after them so that labels are not affected.
+    ///
+    /// The value is `None` (no colors) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .edge_color(Some(Color::BrightBlack))
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\x1b[90m|--\x1b[39m foo\n\
+    ///                 \x1b[90m|\x1b[39m   \x1b[90m`--\x1b[39m bar\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ItemStyle::with_edge_color()`]: struct.ItemStyle.html#method.with_edge_color
+    #[cfg(feature = "ansi")]
+    pub fn edge_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.config.edge_color = color;
+        self
+    }
+
+    /// Colors edges of each nesting level with a distinct color from the palette.
+    ///
+    /// Edges of top-level items use the first color, edges of their children use the second
+    /// color, and so on.
+    /// The palette is repeated for items deeper than the palette.
+    /// This takes precedence over [`edge_color()`][edge_color], and each item can override this
+    /// with [`ItemStyle::with_edge_color()`].
+    /// An empty palette disables this mode.
+    ///
+    /// The value is empty by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .rainbow_edges(vec![Color::Red, Color::Green])
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+    /// writer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "bar")?;
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// let expected = "\x1b[31m`--\x1b[39m foo\n    \
+    ///                 \x1b[32m|--\x1b[39m bar\n    \
+    ///                 \x1b[32m|\x1b[39m   \x1b[31m`--\x1b[39m baz\n";
+    /// assert_eq!(buf, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [edge_color]: #method.edge_color
+    /// [`ItemStyle::with_edge_color()`]: struct.ItemStyle.html#method.with_edge_color
+    #[cfg(feature = "ansi")]
+    pub fn rainbow_edges(&mut self, palette: Vec<Color>) -> &mut Self {
+        self.config.edge_palette = palette.into();
+        self
+    }
+
+    /// Sets when to emit colors and text styles.
+    ///
+    /// With `ColorMode::Never`, edge colors and label styles are ignored.
+    /// `ColorMode::Auto` is resolved when a printer is created or a tree is rendered.
+    /// [`TreePrinter`], async printers, and [`TreeNode`] rendering write to destinations which
+    /// may not be terminals, so they treat the destination as not a terminal.
+    /// [`IoTreePrinter::with_terminal_detection()`] and [`WriteColorTreePrinter`] check their
+    /// writers.
+    ///
+    /// The value is `ColorMode::Always` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, ColorMode, EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .edge_color(Some(Color::Red))
+    ///     .color_mode(ColorMode::Never)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "`-- foo\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreePrinter`]: struct.TreePrinter.html
+    /// [`TreeNode`]: struct.TreeNode.html
+    /// [`IoTreePrinter::with_terminal_detection()`]:
+    /// struct.IoTreePrinter.html#method.with_terminal_detection
+    /// [`WriteColorTreePrinter`]: struct.WriteColorTreePrinter.html
+    #[cfg(feature = "ansi")]
+    pub fn color_mode(&mut self, mode: ColorMode) -> &mut Self {
+        self.config.color_mode = mode;
+        self
+    }
+
+    /// Sets the color capability of the destination.
+    ///
+    /// Edge colors and label colors beyond the capability are replaced with the closest
+    /// available colors.
+    /// Use [`ColorLevel::from_environment()`] to guess the capability of the terminal.
+    ///
+    /// The value is `ColorLevel::TrueColor` by default, that is, colors are emitted as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, ColorLevel, EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new()
+    ///     .edge_color(Some(Color::Rgb(0xff, 0x87, 0x00)))
+    ///     .color_level(ColorLevel::Ansi256)
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "\x1b[38;5;208m`--\x1b[39m foo\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ColorLevel::from_environment()`]: enum.ColorLevel.html#method.from_environment
+    #[cfg(feature = "ansi")]
+    pub fn color_level(&mut self, level: ColorLevel) -> &mut Self {
+        self.config.color_level = level;
+        self
+    }
+
+    /// Renders the second and subsequent lines of multiline labels in dim (faint) style.
+    ///
+    /// This visually distinguishes wrapped content from new nodes.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// let opts = TreeConfigBuilder::new().dim_continuation_lines(true).build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\nbar")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "`-- foo\n    \x1b[2mbar\x1b[22m\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    #[cfg(feature = "ansi")]
+    pub fn dim_continuation_lines(&mut self, v: bool) -> &mut Self {
+        self.config.dim_continuation_lines = v;
+        self
+    }
+
+    /// Limits the width of output lines to the terminal width if no explicit limit is set.
+    ///
+    /// The width is detected by [`terminal_width()`] when the config is built, and used as
+    /// [`max_width()`][max_width] unless it is set explicitly.
+    /// Nothing is changed if the width cannot be detected, for example when the standard output
+    /// is redirected to a file.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter};
+    ///
+    /// // An explicit limit takes precedence over the terminal width.
+    /// let opts = TreeConfigBuilder::new()
+    ///     .fit_terminal_width(true)
+    ///     .max_width(Some(8))
+    ///     .build();
+    ///
+    /// let mut writer = TreePrinter::new(String::new(), opts);
+    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "long label")?;
+    /// let buf = writer.finalize()?;
+    ///
+    /// assert_eq!(buf, "`-- lon\u{2026}\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`terminal_width()`]: fn.terminal_width.html
+    /// [max_width]: #method.max_width
+    #[cfg(feature = "terminal-size")]
+    pub fn fit_terminal_width(&mut self, v: bool) -> &mut Self {
+        self.fit_terminal_width = v;
+        self
+    }
+
+    /// Builds a `TreeConfig`.
+    pub fn build(&self) -> TreeConfig {
+        #[cfg(feature = "terminal-size")]
+        if self.fit_terminal_width && self.config.max_width.is_none() {
+            let mut config = self.config.clone();
+            config.max_width = crate::terminal::terminal_width();
+            return config;
+        }
+        self.config.clone()
+    }
+}
+
+/// Options common for a tree.
+///
+/// This is not `Copy`, since it owns the per-depth edges, the width oracle, and strings such as
+/// the newline sequence and the ellipsis.
+/// Clone the config to pass the same options to multiple printers.
+#[derive(Debug, Clone)]
+pub struct TreeConfig {
+    /// Whether to emit trailing whitespace.
+    ///
+    /// Default is `false`.
+    emit_trailing_whitespace: bool,
+    /// Whether to emit a newline automatically at the tail of the tree.
+    ///
+    /// Default is `true`.
+    emit_trailing_newline: bool,
+    /// Newline sequence.
+    ///
+    /// Default is `"\n"`.
+    newline: String,
+    /// Whether to emit a summary line after the tree.
+    ///
+    /// Default is `false`.
+    emit_summary_footer: bool,
+    /// Whether to emit spacer rows between sibling subtrees.
+    ///
+    /// Default is `false`.
+    emit_spacer_rows: bool,
+    /// Whether to align continuation lines to the text of the first line.
+    ///
+    /// Default is `false`.
+    hanging_indent: bool,
+    /// Display width of East Asian ambiguous width characters.
+    ///
+    /// Default is `AmbiWidth::Narrow`.
+    ambiguous_width: AmbiWidth,
+    /// Function to generate the gutter of each line.
+    ///
+    /// Default is `None`.
+    line_gutter: Option<fn(usize, usize) -> String>,
+    /// Column where annotations end.
+    ///
+    /// Default is `80`.
+    annotation_column: usize,
+    /// Character to fill the space between labels and annotations.
+    ///
+    /// Default is `' '`.
+    annotation_leader: char,
+    /// Whether to quote labels which cannot be parsed back unambiguously.
+    ///
+    /// Default is `false`.
+    quote_labels: bool,
+    /// Width of tab stops.
+    ///
+    /// Default is `None`.
+    tab_width: Option<usize>,
+    /// Whether to strip carriage returns not followed by a line feed.
+    ///
+    /// Default is `false`.
+    strip_carriage_returns: bool,
+    /// Whether to wrap each line of labels in bidirectional isolates.
+    ///
+    /// Default is `false`.
+    isolate_bidi: bool,
+    /// Whether to emit a mirrored tree.
+    ///
+    /// Default is `false`.
+    mirrored: bool,
+    /// Whether to emit a bottom-up tree.
+    ///
+    /// Default is `false`.
+    bottom_up: bool,
+    /// Whether to record the lines where items start.
+    ///
+    /// This is only set internally. Default is `false`.
+    mark_items: bool,
+    /// Maximum display width of each line of labels.
+    ///
+    /// Default is `None`.
+    max_label_width: Option<usize>,
+    /// Maximum display width of output lines.
+    ///
+    /// Default is `None`.
+    max_width: Option<usize>,
+    /// Maximum number of output lines.
+    ///
+    /// Default is `None`.
+    max_lines: Option<usize>,
+    /// How to handle lines of labels longer than the limits.
+    ///
+    /// Default is `LabelOverflow::Truncate`.
+    label_overflow: LabelOverflow,
+    /// Marker put at the start of hard-wrapped lines.
+    ///
+    /// Default is `"\u{21AA} "`.
+    hard_wrap_marker: String,
+    /// Ellipsis appended to truncated labels.
+    ///
+    /// Default is `"\u{2026}"`.
+    ellipsis: String,
+    /// Oracle to measure the display width of labels.
+    width: Arc<dyn Width>,
+    /// Edge configs for each depth.
+    ///
+    /// Default is empty.
+    depth_edges: Arc<[EdgeConfig]>,
+    /// Whether to repeat `depth_edges` for deeper items.
+    ///
+    /// Default is `false`.
+    cycle_depth_edges: bool,
+    /// Color of edges.
+    ///
+    /// Default is `None`.
+    #[cfg(feature = "ansi")]
+    edge_color: Option<Color>,
+    /// Colors of edges for each depth.
+    ///
+    /// Default is empty.
+    #[cfg(feature = "ansi")]
+    edge_palette: Arc<[Color]>,
+    /// When to emit colors.
+    ///
+    /// Default is `ColorMode::Always`.
+    #[cfg(feature = "ansi")]
+    color_mode: ColorMode,
+    /// Color capability of the destination.
+    ///
+    /// Default is `ColorLevel::TrueColor`.
+    #[cfg(feature = "ansi")]
+    color_level: ColorLevel,
+    /// Whether to render continuation lines of labels in dim style.
+    ///
+    /// Default is `false`.
+    #[cfg(feature = "ansi")]
+    dim_continuation_lines: bool,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            emit_trailing_whitespace: false,
+            emit_trailing_newline: true,
+            newline: "\n".to_owned(),
+            emit_summary_footer: false,
+            emit_spacer_rows: false,
+            hanging_indent: false,
+            ambiguous_width: AmbiWidth::default(),
+            line_gutter: None,
+            annotation_column: 80,
+            annotation_leader: ' ',
+            quote_labels: false,
+            tab_width: None,
+            strip_carriage_returns: false,
+            isolate_bidi: false,
+            mirrored: false,
+            bottom_up: false,
+            mark_items: false,
+            max_label_width: None,
+            max_width: None,
+            max_lines: None,
+            label_overflow: LabelOverflow::default(),
+            hard_wrap_marker: "\u{21AA} ".to_owned(),
+            ellipsis: "\u{2026}".to_owned(),
+            width: width::default_width(),
+            depth_edges: Arc::new([]),
+            cycle_depth_edges: false,
+            #[cfg(feature = "ansi")]
+            edge_color: None,
+            #[cfg(feature = "ansi")]
+            edge_palette: Arc::new([]),
+            #[cfg(feature = "ansi")]
+            color_mode: ColorMode::default(),
+            #[cfg(feature = "ansi")]
+            color_level: ColorLevel::default(),
+            #[cfg(feature = "ansi")]
+            dim_continuation_lines: false,
+        }
+    }
+}
+
+impl TreeConfig {
+    /// Creates a new default `TreeConfig`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the writer should emit trailing whitespace if the line has no content.
+    pub(crate) fn emit_trailing_whitespace(&self) -> bool {
+        self.emit_trailing_whitespace
+    }
+
+    /// Returns whether the writer should emit trailing newline at the tail of the tree.
+    pub(crate) fn emit_trailing_newline(&self) -> bool {
+        self.emit_trailing_newline
+    }
+
+    /// Returns the newline sequence.
+    pub(crate) fn newline(&self) -> &str {
+        &self.newline
+    }
+
+    /// Returns whether the printer should emit a summary line after the tree.
+    pub(crate) fn emit_summary_footer(&self) -> bool {
+        self.emit_summary_footer
+    }
+
+    /// Returns whether the printer should emit spacer rows between sibling subtrees.
+    pub(crate) fn emit_spacer_rows(&self) -> bool {
+        self.emit_spacer_rows
+    }
+
+    /// Returns the display width of East Asian ambiguous width characters.
+    pub(crate) fn ambiguous_width(&self) -> AmbiWidth {
+        self.ambiguous_width
+    }
+
+    /// Returns whether continuation lines are aligned to the text of the first line by
+    /// measuring the emitted prefixes.
+    ///
+    /// This is necessary when the paddings of edge configs may not match the display width of
+    /// ambiguous width characters in the prefixes.
+    pub(crate) fn aligns_continuation_lines(&self) -> bool {
+        self.hanging_indent || self.ambiguous_width == AmbiWidth::Wide
+    }
+
+    /// Returns the function to generate the gutter of each line.
+    pub(crate) fn line_gutter(&self) -> Option<fn(usize, usize) -> String> {
+        self.line_gutter
+    }
+
+    /// Returns the column where annotations end.
+    pub(crate) fn annotation_column(&self) -> usize {
+        self.annotation_column
+    }
+
+    /// Returns the minimum width of the space between labels and annotations.
+    pub(crate) fn annotation_gap(&self) -> usize {
+        if self.annotation_leader == ' ' {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Returns the width oracle with the ambiguous width setting applied.
+    pub(crate) fn width_oracle(&self) -> AmbiAwareWidth<'_> {
+        AmbiAwareWidth::new(&*self.width, self.ambiguous_width)
+    }
+
+    /// Returns the display width of the given label text.
+    ///
+    /// Escape sequences in pre-styled labels are not counted.
+    pub(crate) fn str_width(&self, s: &str) -> usize {
+        width::visible_width(s, &self.width_oracle())
+    }
+
+    /// Expands tabs in the label if requested.
+    pub(crate) fn expand_tabs<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        match self.tab_width {
+            Some(tab_width) if !self.quote_labels => {
+                width::expand_tabs(label, tab_width, &self.width_oracle())
+            }
+            _ => Cow::Borrowed(label),
+        }
+    }
+
+    /// Normalizes CRLF line breaks in the label into LF, and strips stray carriage returns if
+    /// requested.
+    ///
+    /// Labels quoted by `quote_labels` are kept as is, since the line breaks are escaped.
+    pub(crate) fn normalize_newlines<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        if self.quote_labels || !label.contains('\r') {
+            return Cow::Borrowed(label);
+        }
+        let normalized = label.replace("\r\n", "\n");
+        if self.strip_carriage_returns {
+            Cow::Owned(normalized.replace('\r', ""))
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+
+    /// Returns whether stray carriage returns in labels are stripped.
+    pub(crate) fn strips_carriage_returns(&self) -> bool {
+        self.strip_carriage_returns
+    }
+
+    /// Returns whether each line of labels is wrapped in bidirectional isolates.
+    pub(crate) fn isolates_bidi(&self) -> bool {
+        self.isolate_bidi
+    }
+
+    /// Returns whether to emit a mirrored tree.
+    pub(crate) fn is_mirrored(&self) -> bool {
+        self.mirrored
+    }
+
+    /// Returns whether to emit a bottom-up tree.
+    pub(crate) fn is_bottom_up(&self) -> bool {
+        self.bottom_up
+    }
+
+    /// Returns whether to record the lines where items start.
+    pub(crate) fn marks_items(&self) -> bool {
+        self.bottom_up || self.mark_items
+    }
+
+    /// Sets whether to record the lines where items start.
+    pub(crate) fn set_mark_items(&mut self, v: bool) {
+        self.mark_items = v;
+    }
+
+    /// Returns whether tabs in labels are expanded.
+    pub(crate) fn expands_tabs(&self) -> bool {
+        self.tab_width.is_some() && !self.quote_labels
+    }
+
+    /// Returns the maximum display width of each line of labels.
+    pub(crate) fn max_label_width(&self) -> Option<usize> {
+        self.max_label_width
+    }
+
+    /// Returns the maximum display width of output lines.
+    pub(crate) fn max_width(&self) -> Option<usize> {
+        self.max_width
+    }
+
+    /// Returns the maximum number of output lines.
+    pub(crate) fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// Sets the maximum number of output lines.
+    pub(crate) fn set_max_lines(&mut self, max: Option<usize>) {
+        self.max_lines = max;
+    }
+
+    /// Fits the first line and the following lines of the label to the given display widths,
+    /// by truncating or wrapping them.
+    pub(crate) fn fit_lines(&self, label: &str, first_max: usize, rest_max: usize) -> String {
+        let mut lines = Vec::new();
+        for (i, line) in label.split('\n').enumerate() {
+            let max_width = if i == 0 { first_max } else { rest_max };
+            match self.label_overflow {
+                LabelOverflow::Truncate => {
+                    let line =
+                        width::truncate(line, max_width, &self.ellipsis, &self.width_oracle());
+                    lines.push(line.into_owned());
+                }
+                LabelOverflow::WordWrap => {
+                    lines.extend(width::wrap(line, max_width, rest_max, &self.width_oracle()))
+                }
+                LabelOverflow::HardWrap => lines.extend(width::hard_wrap(
+                    line,
+                    max_width,
+                    rest_max,
+                    &self.hard_wrap_marker,
+                    &self.width_oracle(),
+                )),
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Fits the label as [`fit_lines()`](#method.fit_lines) does, except that the end of the
+    /// first line with the given length in bytes is kept as long as possible.
+    ///
+    /// The kept part is put after the fitted first line, and its width is not available for the
+    /// first line.
+    /// The rest of the first line is kept at least one cell wide (plus the ellipsis on
+    /// truncation), and the kept part is truncated if the line still exceeds `first_max`.
+    pub(crate) fn fit_lines_with_tail(
+        &self,
+        label: &str,
+        tail_len: usize,
+        first_max: usize,
+        rest_max: usize,
+    ) -> String {
+        let first_line_end = label.find('\n').unwrap_or(label.len());
+        let tail_start = first_line_end.saturating_sub(tail_len);
+        if tail_len == 0 || !label.is_char_boundary(tail_start) {
+            return self.fit_lines(label, first_max, rest_max);
+        }
+        let tail = &label[tail_start..first_line_end];
+        let head = format!("{}{}", &label[..tail_start], &label[first_line_end..]);
+        let head_width = self.str_width(&label[..tail_start]);
+        let min_head_width = match self.label_overflow {
+            LabelOverflow::Truncate => head_width.min(1 + self.str_width(&self.ellipsis)),
+            LabelOverflow::WordWrap | LabelOverflow::HardWrap => head_width.min(1),
+        };
+        let head_max = first_max
+            .saturating_sub(self.str_width(tail))
+            .max(min_head_width.min(first_max));
+        let mut fitted = self.fit_lines(&head, head_max, rest_max);
+        let fitted_first_line_end = fitted.find('\n').unwrap_or(fitted.len());
+        let tail_max = first_max.saturating_sub(self.str_width(&fitted[..fitted_first_line_end]));
+        let tail = width::truncate(tail, tail_max, &self.ellipsis, &self.width_oracle());
+        fitted.insert_str(fitted_first_line_end, &tail);
+        fitted
+    }
+
+    /// Puts the annotation after the first line of the label, filling the space with the given
+    /// width between them with the leader.
+    pub(crate) fn annotate_first_line(
+        &self,
+        label: &str,
+        annotation: &str,
+        width: usize,
+    ) -> String {
+        let first_line_end = label.find('\n').unwrap_or(label.len());
+        let mut annotated = String::with_capacity(label.len() + width + annotation.len());
+        annotated.push_str(&label[..first_line_end]);
+        if self.annotation_leader == ' ' || width < 2 {
+            annotated.extend(std::iter::repeat(' ').take(width));
+        } else {
+            annotated.push(' ');
+            annotated.extend(std::iter::repeat(self.annotation_leader).take(width - 2));
+            annotated.push(' ');
+        }
+        annotated.push_str(annotation);
+        annotated.push_str(&label[first_line_end..]);
+        annotated
+    }
+
+    /// Puts the icon before the first line of the label, and indents the following lines to the
+    /// text of the first line.
+    pub(crate) fn prefix_icon(&self, label: &str, icon: &str) -> String {
+        let indent = self.str_width(icon) + 1;
+        let mut prefixed = String::with_capacity(label.len() + icon.len() + 1);
+        for (i, line) in label.split('\n').enumerate() {
+            if i == 0 {
+                prefixed.push_str(icon);
+                prefixed.push(' ');
+            } else {
+                prefixed.push('\n');
+                if !line.is_empty() {
+                    prefixed.extend(std::iter::repeat(' ').take(indent));
+                }
+            }
+            prefixed.push_str(line);
+        }
+        prefixed
+    }
+
+    /// Returns whether to quote labels which cannot be parsed back unambiguously.
+    pub(crate) fn quote_labels(&self) -> bool {
+        self.quote_labels
+    }
+
+    /// Returns the edge config for items at the given depth.
+    ///
+    /// Top-level items have depth 0.
+    pub(crate) fn edge_for_depth(&self, depth: usize) -> EdgeConfig {
+        let edges = &self.depth_edges;
+        let index = if self.cycle_depth_edges && !edges.is_empty() {
+            depth % edges.len()
+        } else {
+            depth.min(edges.len().saturating_sub(1))
+        };
+        edges.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Returns whether to render continuation lines of labels in dim style.
+    #[cfg(feature = "ansi")]
+    pub(crate) fn dim_continuation_lines(&self) -> bool {
+        self.dim_continuation_lines && self.color_mode != ColorMode::Never
+    }
+
+    /// Resolves `ColorMode::Auto` into a concrete mode.
+    ///
+    /// `is_terminal` is whether the destination is a terminal.
+    #[cfg(feature = "ansi")]
+    pub(crate) fn resolve_color_mode(&mut self, is_terminal: bool) {
+        self.color_mode = self.color_mode.resolve(is_terminal);
+    }
+
+    /// Returns the edge color for items at the given depth.
+    #[cfg(feature = "ansi")]
+    fn edge_color_for_depth(&self, depth: usize) -> Option<Color> {
+        if self.edge_palette.is_empty() {
+            return self.edge_color;
+        }
+        Some(self.edge_palette[depth % self.edge_palette.len()])
+    }
+
+    /// Creates a new `ItemWriter`.
+    pub(crate) fn writer<'a, W: LineWrite>(
+        &'a self,
+        writer: &'a mut W,
+        states: &'a mut [ItemState],
+        line_index: &'a mut usize,
+    ) -> ItemWriter<'a, W> {
+        ItemWriter::new(writer, states, self, line_index)
+    }
+}
+
+/// Handling of lines of labels longer than the limits.
+///
+/// See [`TreeConfigBuilder::label_overflow()`].
+///
+/// [`TreeConfigBuilder::label_overflow()`]:
+/// struct.TreeConfigBuilder.html#method.label_overflow
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum LabelOverflow {
+    /// Truncates the lines and appends the ellipsis.
+    #[default]
+    Truncate,
+    /// Wraps the lines at spaces.
+    ///
+    /// Words wider than the available width are broken at grapheme cluster boundaries (or
+    /// character boundaries without `unicode-width` feature).
+    WordWrap,
+    /// Wraps the lines at the width limit, and puts the marker at the start of the split lines.
+    HardWrap,
+}
+
+/// Order of nodes in a rendered retained tree.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Depth-first order, where descendants are shown under their ancestors.
+    #[default]
+    DepthFirst,
+    /// Breadth-first order, where nodes are listed level by level under depth headings.
+    BreadthFirst,
+}
+
+/// Layout of a rendered retained tree.
+///
+/// See [`RenderConfigBuilder::layout()`].
+///
+/// [`RenderConfigBuilder::layout()`]: struct.RenderConfigBuilder.html#method.layout
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeLayout {
+    /// Indented list with edges on the left of labels.
+    #[default]
+    Indented,
+    /// Top-down chart of boxed labels connected by lines, such as organizational charts.
+    Chart,
+    /// Subtrees of the children of the root flowed into multiple columns, as `ls -C` does.
+    Columns,
+}
+
+/// Expand/collapse indicators put before labels.
+///
+/// See [`RenderConfigBuilder::fold_markers()`].
+///
+/// [`RenderConfigBuilder::fold_markers()`]: struct.RenderConfigBuilder.html#method.fold_markers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldMarkers {
+    /// Marker for nodes with hidden children.
+    collapsed: String,
+    /// Marker for nodes with visible children.
+    expanded: String,
+    /// Marker for nodes without children.
+    leaf: String,
+}
+
+impl FoldMarkers {
+    /// Creates a new `FoldMarkers`.
+    ///
+    /// An empty marker means no indicator for the nodes.
+    pub fn new(
+        collapsed: impl Into<String>,
+        expanded: impl Into<String>,
+        leaf: impl Into<String>,
+    ) -> Self {
+        Self {
+            collapsed: collapsed.into(),
+            expanded: expanded.into(),
+            leaf: leaf.into(),
+        }
+    }
+
+    /// Returns the marker for nodes with hidden children.
+    pub(crate) fn collapsed(&self) -> &str {
+        &self.collapsed
+    }
+
+    /// Returns the marker for nodes with visible children.
+    pub(crate) fn expanded(&self) -> &str {
+        &self.expanded
+    }
+
+    /// Returns the marker for nodes without children.
+    pub(crate) fn leaf(&self) -> &str {
+        &self.leaf
+    }
+}
+
+impl Default for FoldMarkers {
+    /// Returns `[+]` for collapsed nodes, `[-]` for expanded nodes, and no markers for leaves.
+    fn default() -> Self {
+        Self::new("[+]", "[-]", "")
+    }
+}
+
+/// Alignment of cells in a metadata column.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAlign {
+    /// Left-aligned.
+    #[default]
+    Left,
+    /// Right-aligned.
+    Right,
+}
+
+/// Layout of a metadata column.
+///
+/// See [`RenderConfigBuilder::metadata_columns()`].
+///
+/// [`RenderConfigBuilder::metadata_columns()`]:
+/// struct.RenderConfigBuilder.html#method.metadata_columns
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataColumn {
+    /// Alignment of the cells.
+    align: ColumnAlign,
+    /// Minimum width of the column.
+    min_width: usize,
+}
+
+impl MetadataColumn {
+    /// Creates a new `MetadataColumn` with the given alignment.
+    pub fn new(align: ColumnAlign) -> Self {
+        Self {
+            align,
+            min_width: 0,
+        }
+    }
+
+    /// Sets the minimum width of the column.
+    ///
+    /// The column is widened to fit the widest cell.
+    pub fn with_min_width(mut self, width: usize) -> Self {
+        self.min_width = width;
+        self
+    }
+
+    /// Returns the alignment of the cells.
+    pub(crate) fn align(&self) -> ColumnAlign {
+        self.align
+    }
+
+    /// Returns the minimum width of the column.
+    pub(crate) fn min_width(&self) -> usize {
+        self.min_width
+    }
+}
+
+/// `RenderConfig` builder.
+#[derive(Default, Debug, Clone)]
+pub struct RenderConfigBuilder {
+    /// Current config.
+    config: RenderConfig,
+    /// Leader overriding the one of the tree config.
+    annotation_leader: Option<char>,
+}
+
+impl RenderConfigBuilder {
+    /// Creates a new `RenderConfigBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the edge config used for all nodes.
+    ///
+    /// If not set, the edge configs for each depth in the tree config are used (see
+    /// [`TreeConfigBuilder::depth_edges()`]), which are `EdgeConfig::Ascii` by default.
+    ///
+    /// [`TreeConfigBuilder::depth_edges()`]: struct.TreeConfigBuilder.html#method.depth_edges
+    pub fn edge(&mut self, edge: EdgeConfig) -> &mut Self {
+        self.config.edge = Some(edge);
+        self
+    }
+
+    /// Sets the config for the underlying tree printer.
+    pub fn tree_config(&mut self, tree: TreeConfig) -> &mut Self {
+        self.config.tree = tree;
+        self
+    }
+
+    /// Collapses chains of single-child nodes into one line, joining the labels with the given
+    /// joiner.
+    ///
+    /// Nodes with multiline labels are never collapsed.
+    ///
+    /// The value is `None` (no compression) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut com = TreeNode::new("com");
+    /// com.push_child(TreeNode::new("example"));
+    /// com.children_mut()[0]
+    ///     .push_child(TreeNode::new("Foo.java"))
+    ///     .push_child(TreeNode::new("Bar.java"));
+    /// let mut root = TreeNode::new("src");
+    /// root.push_child(com);
+    ///
+    /// let config = RenderConfigBuilder::new().compress_chains(Some("/")).build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "src\n\
+    ///                 `-- com/example\n    \
+    ///                     |-- Foo.java\n    \
+    ///                     `-- Bar.java\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn compress_chains(&mut self, joiner: Option<&str>) -> &mut Self {
+        self.config.chain_joiner = joiner.map(ToOwned::to_owned);
+        self
+    }
+
+    /// Sets the order of nodes.
+    ///
+    /// With `TraversalOrder::BreadthFirst`, nodes are listed level by level, each level under a
+    /// `depth N` heading.
+    /// Single-child chains are not compressed in that mode.
+    ///
+    /// The value is `TraversalOrder::DepthFirst` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TraversalOrder, TreeNode};
+    ///
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(TreeNode::new("foo"));
+    /// root.children_mut()[0].push_child(TreeNode::new("baz"));
+    /// root.push_child(TreeNode::new("bar"));
     ///
-    /// let mut writer = TreePrinter::new(String::new(), opts);
-    /// writer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
-    /// let buf = writer.finalize()?;
-    ///
-    /// // Note that there are no newline characters at the end of the output.
-    /// assert_eq!(buf, "`-- foo");
+    /// let config = RenderConfigBuilder::new()
+    ///     .traversal_order(TraversalOrder::BreadthFirst)
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 |-- depth 1\n\
+    ///                 |   |-- foo\n\
+    ///                 |   `-- bar\n\
+    ///                 `-- depth 2\n    \
+    ///                     `-- baz\n";
+    /// assert_eq!(got, expected);
     /// # plaintextree::Result::Ok(())
     /// ```
-    pub fn emit_trailing_newline(&mut self, v: bool) -> &mut Self {
-        self.config.emit_trailing_newline = v;
+    pub fn traversal_order(&mut self, order: TraversalOrder) -> &mut Self {
+        self.config.traversal_order = order;
         self
     }
 
-    /// Builds a `TreeConfig`.
-    pub fn build(self) -> TreeConfig {
-        self.config
+    /// Sets the layout of the tree.
+    ///
+    /// With `TreeLayout::Chart`, labels are drawn in boxes, and each node is put above its
+    /// children with lines connecting them.
+    /// The lines follow the box drawing characters of the edge config (see
+    /// [`ItemStyle::boxed()`]), the fixed edge config if set, or the edge config for the top
+    /// level otherwise.
+    /// Line drawing characters are assumed to be single width in that mode, and the traversal
+    /// order, single-child chain compression, and the layout options of the tree config such as
+    /// gutters are not applied.
+    ///
+    /// With `TreeLayout::Columns`, the subtree of each child of the root is rendered as a tree
+    /// on its own, and the subtrees are put from top to bottom and then left to right in as few
+    /// rows as possible within the [maximum width][max_width] (80 columns if not set).
+    /// This is compact for shallow trees with many small subtrees.
+    ///
+    /// The value is `TreeLayout::Indented` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeLayout, TreeNode};
+    ///
+    /// let mut root = TreeNode::new("CEO");
+    /// root.push_child(TreeNode::new("CTO"));
+    /// root.children_mut()[0].push_child(TreeNode::new("Dev"));
+    /// root.push_child(TreeNode::new("CFO"));
+    ///
+    /// let config = RenderConfigBuilder::new().layout(TreeLayout::Chart).build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "\x20   +-----+\n\
+    ///                 \x20   | CEO |\n\
+    ///                 \x20   +--+--+\n\
+    ///                 \x20  +---+----+\n\
+    ///                 +--+--+  +--+--+\n\
+    ///                 | CTO |  | CFO |\n\
+    ///                 +--+--+  +-----+\n\
+    ///                 \x20  |\n\
+    ///                 +--+--+\n\
+    ///                 | Dev |\n\
+    ///                 +-----+\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// Shallow trees can be flowed into columns.
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeConfigBuilder, TreeLayout, TreeNode};
+    ///
+    /// let mut root = TreeNode::new("src");
+    /// for name in ["main.rs", "lib.rs", "config.rs", "util.rs", "io.rs"] {
+    ///     root.push_child(TreeNode::new(name));
+    /// }
+    /// root.children_mut()[1].push_child(TreeNode::new("mod.rs"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .layout(TreeLayout::Columns)
+    ///     .tree_config(TreeConfigBuilder::new().max_width(Some(30)).build())
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "src\n\
+    ///                 main.rs     config.rs  io.rs\n\
+    ///                 lib.rs      util.rs\n\
+    ///                 `-- mod.rs\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`ItemStyle::boxed()`]: struct.ItemStyle.html#method.boxed
+    /// [max_width]: struct.TreeConfigBuilder.html#method.max_width
+    pub fn layout(&mut self, layout: TreeLayout) -> &mut Self {
+        self.config.layout = layout;
+        self
+    }
+
+    /// Reverses the order of children of every node.
+    ///
+    /// Each node can override this with [`TreeNode::set_reverse_children()`].
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut root = TreeNode::new("log");
+    /// root.push_child(TreeNode::new("2020-01-01"))
+    ///     .push_child(TreeNode::new("2020-01-02"));
+    ///
+    /// let config = RenderConfigBuilder::new().reverse_children(true).build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// assert_eq!(got, "log\n|-- 2020-01-02\n`-- 2020-01-01\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreeNode::set_reverse_children()`]: struct.TreeNode.html#method.set_reverse_children
+    pub fn reverse_children(&mut self, v: bool) -> &mut Self {
+        self.config.reverse_children = v;
+        self
+    }
+
+    /// Sets the suffix appended to the label of collapsed nodes.
+    ///
+    /// `{}` in the suffix is replaced with the number of hidden descendants.
+    /// The suffix is not appended to collapsed nodes without children.
+    ///
+    /// The value is `" \u{2026} ({} hidden)"` (`" … ({} hidden)"`) by default.
+    pub fn collapsed_suffix(&mut self, suffix: impl Into<String>) -> &mut Self {
+        self.config.collapsed_suffix = suffix.into();
+        self
+    }
+
+    /// Renders descendants of collapsed nodes as if they were not collapsed.
+    ///
+    /// This is useful to render both the overview and the expanded view of the same tree.
+    ///
+    /// The value is `false` by default.
+    pub fn expand_all(&mut self, v: bool) -> &mut Self {
+        self.config.expand_all = v;
+        self
+    }
+
+    /// Puts expand/collapse indicators before labels.
+    ///
+    /// Markers are separated from the labels by a space.
+    ///
+    /// The value is `None` (no markers) by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{FoldMarkers, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.push_child(TreeNode::new("lib.rs"));
+    /// let mut target = TreeNode::new("target");
+    /// target.push_child(TreeNode::new("debug"));
+    /// target.set_collapsed(true);
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(target);
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .fold_markers(Some(FoldMarkers::default()))
+    ///     .collapsed_suffix("")
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "[-] .\n\
+    ///                 |-- [-] src\n\
+    ///                 |   `-- lib.rs\n\
+    ///                 `-- [+] target\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn fold_markers(&mut self, markers: Option<FoldMarkers>) -> &mut Self {
+        self.config.fold_markers = markers;
+        self
+    }
+
+    /// Pads labels so that the first lines of all labels start at the same column.
+    ///
+    /// The column is computed from the widths of the line prefixes of the deepest visible
+    /// nodes, which produces table-like alignment for trees with mixed depths.
+    /// Continuation lines of labels are padded as well.
+    ///
+    /// This is ignored in breadth-first order, and when labels are quoted.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.push_child(TreeNode::new("lib.rs"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(TreeNode::new("Cargo.toml"));
+    ///
+    /// let config = RenderConfigBuilder::new().align_labels(true).build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 |--     src\n\
+    ///                 |   `-- lib.rs\n\
+    ///                 `--     Cargo.toml\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn align_labels(&mut self, v: bool) -> &mut Self {
+        self.config.align_labels = v;
+        self
+    }
+
+    /// Sets the color of the connectors on the path to marked nodes.
+    ///
+    /// See [`TreeNode::set_marked()`].
+    ///
+    /// The value is `None` (no colors) by default.
+    ///
+    /// [`TreeNode::set_marked()`]: struct.TreeNode.html#method.set_marked
+    #[cfg(feature = "ansi")]
+    pub fn marked_path_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.config.marked_path_color = color;
+        self
+    }
+
+    /// Sets the text style of the targets of alias nodes.
+    ///
+    /// See [`TreeNode::set_alias_target()`].
+    /// The style is not applied if the color mode of the tree config disables colors.
+    ///
+    /// The value is the plain style by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Color, RenderConfigBuilder, TextStyle, TreeNode};
+    ///
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(TreeNode::alias("latest", "v1.2.0"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .alias_target_style(*TextStyle::new().fg(Some(Color::Cyan)))
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// assert_eq!(got, ".\n`-- latest -> \x1b[36mv1.2.0\x1b[39m\n");
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreeNode::set_alias_target()`]: struct.TreeNode.html#method.set_alias_target
+    #[cfg(feature = "ansi")]
+    pub fn alias_target_style(&mut self, style: TextStyle) -> &mut Self {
+        self.config.alias_target_style = style;
+        self
+    }
+
+    /// Sets the character to fill the space between labels and annotations, overriding
+    /// [`TreeConfigBuilder::annotation_leader()`].
+    ///
+    /// The value of the tree config is used by default.
+    ///
+    /// [`TreeConfigBuilder::annotation_leader()`]:
+    /// struct.TreeConfigBuilder.html#method.annotation_leader
+    pub fn annotation_leader(&mut self, leader: char) -> &mut Self {
+        self.annotation_leader = Some(leader);
+        self
+    }
+
+    /// Sets the layouts of the metadata columns.
+    ///
+    /// Metadata cells are set by [`TreeNode::set_metadata()`], and laid out as a table after
+    /// the labels and annotations.
+    /// Columns without layouts are left-aligned.
+    ///
+    /// The value is empty by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{ColumnAlign, MetadataColumn, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.set_metadata(vec!["drwxr-xr-x", "4096"]);
+    /// let mut lib = TreeNode::new("lib.rs");
+    /// lib.set_metadata(vec!["-rw-r--r--", "120"]);
+    /// src.push_child(lib);
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src);
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .metadata_columns(vec![
+    ///         MetadataColumn::default(),
+    ///         MetadataColumn::new(ColumnAlign::Right).with_min_width(6),
+    ///     ])
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 `-- src         drwxr-xr-x    4096\n\
+    ///                 \x20   `-- lib.rs  -rw-r--r--     120\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`TreeNode::set_metadata()`]: struct.TreeNode.html#method.set_metadata
+    pub fn metadata_columns(&mut self, columns: Vec<MetadataColumn>) -> &mut Self {
+        self.config.metadata_columns = columns;
+        self
+    }
+
+    /// Sets the callback to classify nodes.
+    ///
+    /// The suffix of the returned kind is put at the end of the first line of the label,
+    /// before the collapsed suffix and the annotation.
+    ///
+    /// The value is `None` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{NodeKind, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.push_child(TreeNode::new("main.rs"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(TreeNode::new("empty"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .classify(Some(|node| match node.label() {
+    ///         "." | "empty" => Some(NodeKind::Directory),
+    ///         _ if !node.is_leaf() => Some(NodeKind::Directory),
+    ///         _ => None,
+    ///     }))
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "./\n\
+    ///                 |-- src/\n\
+    ///                 |   `-- main.rs\n\
+    ///                 `-- empty/\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn classify(&mut self, classify: Option<fn(&TreeNode) -> Option<NodeKind>>) -> &mut Self {
+        self.config.classify = classify;
+        self
+    }
+
+    /// Lets the renderer put [Nerd Font] icons before labels, as modern `ls` replacements do.
+    ///
+    /// The icons are chosen by [`nerd_font_icon()`] from the first line of the label and the
+    /// kind returned by the [`classify()`][classify] callback.
+    /// A space is put between the icon and the label, and the following lines of the label are
+    /// indented to the text of the first line.
+    ///
+    /// The value is `false` by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{NodeKind, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.push_child(TreeNode::new("main.rs"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(TreeNode::new("Cargo.toml"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .classify(Some(|node| {
+    ///         if node.is_leaf() {
+    ///             None
+    ///         } else {
+    ///             Some(NodeKind::Directory)
+    ///         }
+    ///     }))
+    ///     .nerd_font_icons(true)
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = "\u{F07B} ./\n\
+    ///                 |-- \u{F07B} src/\n\
+    ///                 |   `-- \u{E7A8} main.rs\n\
+    ///                 `-- \u{E615} Cargo.toml\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [Nerd Font]: https://www.nerdfonts.com/
+    /// [`nerd_font_icon()`]: fn.nerd_font_icon.html
+    /// [classify]: #method.classify
+    #[cfg(feature = "nerd-fonts")]
+    pub fn nerd_font_icons(&mut self, v: bool) -> &mut Self {
+        self.config.nerd_font_icons = v;
+        self
+    }
+
+    /// Builds a `RenderConfig`.
+    pub fn build(&self) -> RenderConfig {
+        let mut config = self.config.clone();
+        if let Some(leader) = self.annotation_leader {
+            config.tree.annotation_leader = leader;
+        }
+        config
     }
 }
 
-/// Options common for a tree.
-#[derive(Debug, Clone, Copy)]
-pub struct TreeConfig {
-    /// Whether to emit trailing whitespace.
+/// Options for rendering a retained tree.
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    /// Edge config.
+    ///
+    /// Default is `None`, which means the edge configs are chosen by depth.
+    edge: Option<EdgeConfig>,
+    /// Config for the tree printer.
+    tree: TreeConfig,
+    /// Joiner of labels of single-child chains.
+    ///
+    /// Default is `None`, which means chains are not compressed.
+    chain_joiner: Option<String>,
+    /// Order of nodes.
+    traversal_order: TraversalOrder,
+    /// Layout of the tree.
+    layout: TreeLayout,
+    /// Whether to reverse the order of children.
     ///
     /// Default is `false`.
-    emit_trailing_whitespace: bool,
-    /// Whether to emit a newline automatically at the tail of the tree.
+    reverse_children: bool,
+    /// Suffix of collapsed nodes.
     ///
-    /// Default is `true`.
-    emit_trailing_newline: bool,
+    /// Default is `" \u{2026} ({} hidden)"`.
+    collapsed_suffix: String,
+    /// Whether to ignore the collapsed state of nodes.
+    ///
+    /// Default is `false`.
+    expand_all: bool,
+    /// Expand/collapse indicators.
+    ///
+    /// Default is `None`.
+    fold_markers: Option<FoldMarkers>,
+    /// Whether to align the first lines of labels to the same column.
+    ///
+    /// Default is `false`.
+    align_labels: bool,
+    /// Layouts of the metadata columns.
+    ///
+    /// Default is empty.
+    metadata_columns: Vec<MetadataColumn>,
+    /// Callback to classify nodes.
+    ///
+    /// Default is `None`.
+    classify: Option<fn(&TreeNode) -> Option<NodeKind>>,
+    /// Whether to put Nerd Font icons before labels.
+    ///
+    /// Default is `false`.
+    #[cfg(feature = "nerd-fonts")]
+    nerd_font_icons: bool,
+    /// Color of the connectors on the path to marked nodes.
+    ///
+    /// Default is `None`.
+    #[cfg(feature = "ansi")]
+    marked_path_color: Option<Color>,
+    /// Text style of the targets of alias nodes.
+    ///
+    /// Default is the plain style.
+    #[cfg(feature = "ansi")]
+    alias_target_style: TextStyle,
 }
 
-impl Default for TreeConfig {
+impl Default for RenderConfig {
     fn default() -> Self {
         Self {
-            emit_trailing_whitespace: false,
-            emit_trailing_newline: true,
+            edge: None,
+            tree: TreeConfig::default(),
+            chain_joiner: None,
+            traversal_order: TraversalOrder::default(),
+            layout: TreeLayout::default(),
+            reverse_children: false,
+            collapsed_suffix: " \u{2026} ({} hidden)".to_owned(),
+            expand_all: false,
+            fold_markers: None,
+            align_labels: false,
+            metadata_columns: Vec::new(),
+            classify: None,
+            #[cfg(feature = "nerd-fonts")]
+            nerd_font_icons: false,
+            #[cfg(feature = "ansi")]
+            marked_path_color: None,
+            #[cfg(feature = "ansi")]
+            alias_target_style: TextStyle::new(),
         }
     }
 }
 
-impl TreeConfig {
-    /// Creates a new default `TreeConfig`.
+impl RenderConfig {
+    /// Creates a new default `RenderConfig`.
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Returns whether the writer should emit trailing whitespace if the line has no content.
-    pub(crate) fn emit_trailing_whitespace(self) -> bool {
-        self.emit_trailing_whitespace
+    /// Returns the item style for a node.
+    ///
+    /// `on_marked_path` is whether the node is on the path to a marked node.
+    pub(crate) fn item_style(&self, is_last_child: bool, on_marked_path: bool) -> ItemStyle {
+        let style = match &self.edge {
+            Some(edge) => ItemStyle::new(is_last_child, edge.clone()),
+            None => ItemStyle::by_depth(is_last_child),
+        };
+        if !on_marked_path {
+            return style;
+        }
+        #[cfg(feature = "ansi")]
+        let style = match self.marked_path_color {
+            Some(color) => style.with_edge_color(color),
+            None => style,
+        };
+        style.emphasized()
     }
 
-    /// Returns whether the writer should emit trailing newline at the tail of the tree.
-    pub(crate) fn emit_trailing_newline(self) -> bool {
-        self.emit_trailing_newline
+    /// Returns the display width of line prefixes and paddings before the first line of labels
+    /// at the given depth.
+    ///
+    /// Top-level items have depth 1.
+    pub(crate) fn gutter_width(&self, depth: usize) -> usize {
+        (0..depth)
+            .map(|level| {
+                let edge = match &self.edge {
+                    Some(edge) => edge.clone(),
+                    None => self.tree.edge_for_depth(level),
+                };
+                edge.prefix_and_padding_width(false, true, &self.tree.width_oracle())
+            })
+            .sum()
     }
 
-    /// Creates a new `ItemWriter`.
-    pub(crate) fn writer<'a, W: fmt::Write>(
-        self,
-        writer: &'a mut W,
-        states: &'a mut [ItemState],
-    ) -> ItemWriter<'a, W> {
-        ItemWriter::new(writer, states, self)
+    /// Returns the config for the tree printer.
+    pub(crate) fn tree_config(&self) -> &TreeConfig {
+        &self.tree
+    }
+
+    /// Returns the mutable config for the tree printer.
+    pub(crate) fn tree_config_mut(&mut self) -> &mut TreeConfig {
+        &mut self.tree
+    }
+
+    /// Returns the config with `ColorMode::Auto` resolved, treating the destination as not a
+    /// terminal.
+    pub(crate) fn resolve_color_mode(&self) -> Cow<'_, Self> {
+        #[cfg(feature = "ansi")]
+        if self.tree.color_mode == ColorMode::Auto {
+            let mut config = self.clone();
+            config.tree.resolve_color_mode(false);
+            return Cow::Owned(config);
+        }
+        Cow::Borrowed(self)
+    }
+
+    /// Returns the display width of the given label text.
+    pub(crate) fn str_width(&self, s: &str) -> usize {
+        self.tree.str_width(s)
+    }
+
+    /// Returns the joiner of labels of single-child chains.
+    pub(crate) fn chain_joiner(&self) -> Option<&str> {
+        self.chain_joiner.as_deref()
+    }
+
+    /// Returns the order of nodes.
+    pub(crate) fn traversal_order(&self) -> TraversalOrder {
+        self.traversal_order
+    }
+
+    /// Returns the layout of the tree.
+    pub(crate) fn layout(&self) -> TreeLayout {
+        self.layout
+    }
+
+    /// Returns the box drawing characters for the chart layout.
+    pub(crate) fn chart_box_chars(&self) -> BoxChars {
+        match &self.edge {
+            Some(edge) => edge.box_chars(),
+            None => self.tree.edge_for_depth(0).box_chars(),
+        }
+    }
+
+    /// Returns whether to reverse the order of children.
+    pub(crate) fn reverse_children(&self) -> bool {
+        self.reverse_children
+    }
+
+    /// Returns the suffix for a collapsed node with the given number of hidden descendants.
+    pub(crate) fn collapsed_suffix(&self, hidden: usize) -> String {
+        self.collapsed_suffix.replace("{}", &hidden.to_string())
+    }
+
+    /// Returns whether to ignore the collapsed state of nodes.
+    pub(crate) fn expand_all(&self) -> bool {
+        self.expand_all
+    }
+
+    /// Returns the layout of the metadata column at the given index.
+    pub(crate) fn metadata_column(&self, index: usize) -> MetadataColumn {
+        self.metadata_columns
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Writes the target of an alias node.
+    pub(crate) fn write_alias_target(&self, buf: &mut String, target: &str) {
+        #[cfg(feature = "ansi")]
+        {
+            if self.tree.color_mode != ColorMode::Never {
+                self.alias_target_style
+                    .downgrade(self.tree.color_level)
+                    .write_styled(buf, target)
+                    .expect("Should never fail: writing to `String`");
+                return;
+            }
+        }
+        buf.push_str(target);
+    }
+
+    /// Returns the kind of the node.
+    pub(crate) fn classify(&self, node: &TreeNode) -> Option<NodeKind> {
+        self.classify.and_then(|classify| classify(node))
+    }
+
+    /// Returns the Nerd Font icon for the node, if enabled.
+    #[cfg(feature = "nerd-fonts")]
+    pub(crate) fn nerd_font_icon(
+        &self,
+        node: &TreeNode,
+        kind: Option<&NodeKind>,
+    ) -> Option<&'static str> {
+        if !self.nerd_font_icons {
+            return None;
+        }
+        let name = node.label().lines().next().unwrap_or("");
+        Some(crate::icons::nerd_font_icon(name, kind))
+    }
+
+    /// Returns whether to align the first lines of labels to the same column.
+    pub(crate) fn align_labels(&self) -> bool {
+        self.align_labels
+    }
+
+    /// Returns the expand/collapse indicators.
+    pub(crate) fn fold_markers(&self) -> Option<&FoldMarkers> {
+        self.fold_markers.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_edges() {
+        let edge = |locale: Option<&str>, term: Option<&str>| {
+            edge_for_environment(locale.map(OsStr::new), term.map(OsStr::new))
+        };
+        let ascii = [
+            (None, None),
+            (Some("C"), None),
+            (Some("en_US.ISO-8859-1"), None),
+            (Some("ja_JP.eucJP"), None),
+            (Some("en_US.UTF-8"), Some("dumb")),
+        ];
+        for (locale, term) in ascii {
+            assert!(matches!(edge(locale, term), EdgeConfig::Ascii));
+        }
+        let single = [
+            (Some("en_US.UTF-8"), Some("xterm")),
+            (Some("C.utf8"), None),
+            (Some("de_DE.UTF-8@euro"), None),
+        ];
+        for (locale, term) in single {
+            assert!(matches!(edge(locale, term), EdgeConfig::UnicodeSingleWidth));
+        }
+        let double = [(Some("ja_JP.UTF-8"), None), (Some("zh_CN.utf8"), None)];
+        for (locale, term) in double {
+            assert!(matches!(edge(locale, term), EdgeConfig::UnicodeDoubleWidth));
+        }
+    }
+
+    #[test]
+    fn ascii_fallback_keeps_widths() {
+        let edges = [
+            EdgeConfig::UnicodeSingleWidth,
+            EdgeConfig::UnicodeDoubleWidth,
+            EdgeConfig::Indent("\u{3000}".to_owned()),
+            EdgeConfig::bullet(),
+            EdgeConfig::rounded(),
+            EdgeConfig::bold(),
+            EdgeConfig::double(),
+            EdgeConfig::dotted(),
+            EdgeConfig::compact(),
+        ];
+        let config = TreeConfig::new();
+        let width = config.width_oracle();
+        for edge in &edges {
+            let fallback = edge.to_ascii_fallback();
+            for &(last_child, first_line) in
+                &[(false, false), (false, true), (true, false), (true, true)]
+            {
+                for &fragment in &[PrefixPart::Prefix, PrefixPart::Padding] {
+                    let mut buf = String::new();
+                    fallback
+                        .write_edge(&mut buf, last_child, first_line, fragment)
+                        .expect("Should never fail: writing to `String`");
+                    assert!(buf.is_ascii(), "{:?} has non-ASCII characters", fallback);
+                }
+                assert_eq!(
+                    fallback.prefix_and_padding_width(last_child, first_line, &width),
+                    edge.prefix_and_padding_width(last_child, first_line, &width),
+                    "{:?}",
+                    edge
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_fallback_shapes() {
+        let fallback = |edge: EdgeConfig| {
+            let mut buf = String::new();
+            for &(last_child, first_line) in &[(false, true), (false, false), (true, true)] {
+                for &fragment in &[PrefixPart::Prefix, PrefixPart::Padding] {
+                    edge.to_ascii_fallback()
+                        .write_edge(&mut buf, last_child, first_line, fragment)
+                        .expect("Should never fail: writing to `String`");
+                }
+                buf.push('/');
+            }
+            buf
+        };
+        assert_eq!(fallback(EdgeConfig::bold()), "|-- /|   /`-- /");
+        assert_eq!(fallback(EdgeConfig::dotted()), "|-- /|   /`-- /");
+        assert_eq!(fallback(EdgeConfig::compact()), "| /| /` /");
+        assert_eq!(
+            fallback(EdgeConfig::UnicodeDoubleWidth),
+            "|--- /|    /`--- /"
+        );
     }
 }
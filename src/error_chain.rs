@@ -0,0 +1,236 @@
+//! Trees of error source chains.
+
+use std::error::Error;
+
+use crate::tree_node::TreeNode;
+
+/// Options for rendering error source chains as trees.
+#[derive(Debug, Default, Clone)]
+pub struct ErrorChainOptions {
+    /// Whether to show the `Debug` output of each error.
+    debug: bool,
+    /// Whether to use the alternate (pretty) `Debug` format.
+    alternate_debug: bool,
+}
+
+impl ErrorChainOptions {
+    /// Creates a new default `ErrorChainOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to show the `Debug` output of each error.
+    ///
+    /// The `Debug` output is shown at the lines following the `Display` output of the error.
+    /// [`RenderConfigBuilder::dim_continuation_lines()`] helps to distinguish them.
+    ///
+    /// Default is `false`.
+    ///
+    /// [`RenderConfigBuilder::dim_continuation_lines()`]:
+    /// struct.RenderConfigBuilder.html#method.dim_continuation_lines
+    pub fn debug(&mut self, v: bool) -> &mut Self {
+        self.debug = v;
+        self
+    }
+
+    /// Sets whether to use the alternate (pretty) `Debug` format, that is, `{:#?}`.
+    ///
+    /// This has no effect unless [`debug()`](#method.debug) is enabled.
+    ///
+    /// Default is `false`.
+    pub fn alternate_debug(&mut self, v: bool) -> &mut Self {
+        self.alternate_debug = v;
+        self
+    }
+
+    /// Returns the label of the error.
+    fn label(&self, err: &dyn Error) -> String {
+        let mut label = err.to_string().trim_end_matches(['\r', '\n']).to_owned();
+        if self.debug {
+            let debug = if self.alternate_debug {
+                format!("{:#?}", err)
+            } else {
+                format!("{:?}", err)
+            };
+            label.push('\n');
+            label.push_str(debug.trim_end_matches(['\r', '\n']));
+        }
+        label
+    }
+}
+
+/// Creates a tree of the error and its sources.
+///
+/// The error is the root, and each source is the only child of the error it causes.
+/// Each node is labeled with the `Display` output of the error, which can be multiline.
+/// Trailing newlines of the outputs are removed.
+///
+/// # Examples
+///
+/// ```
+/// use std::{error::Error, fmt, io};
+///
+/// use plaintextree::{error_chain_tree, ErrorChainOptions};
+///
+/// #[derive(Debug)]
+/// struct ConfigError(io::Error);
+///
+/// impl fmt::Display for ConfigError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("failed to load the config\nfrom `app.toml`")
+///     }
+/// }
+///
+/// impl Error for ConfigError {
+///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// let err = ConfigError(io::Error::new(io::ErrorKind::Other, "disk is on fire"));
+/// let tree = error_chain_tree(&err, &ErrorChainOptions::new());
+///
+/// let expected = "\
+/// failed to load the config
+/// from `app.toml`
+/// `-- disk is on fire
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// ```
+pub fn error_chain_tree(err: &(dyn Error + 'static), opts: &ErrorChainOptions) -> TreeNode {
+    let mut chain = Vec::new();
+    let mut next = Some(err);
+    while let Some(err) = next {
+        chain.push(TreeNode::new(opts.label(err)));
+        next = err.source();
+    }
+    let mut tree = chain
+        .pop()
+        .expect("Should never fail: the chain has at least the given error");
+    while let Some(mut parent) = chain.pop() {
+        parent.push_child(tree);
+        tree = parent;
+    }
+    tree
+}
+
+/// Creates a tree of the `anyhow::Error` and its sources.
+///
+/// Each context attached to the error is a node, followed by the error which it wraps.
+/// See [`error_chain_tree()`] for the tree.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use plaintextree::{anyhow_error_tree, ErrorChainOptions};
+///
+/// let err = "0x2a"
+///     .parse::<u32>()
+///     .context("invalid port `0x2a`")
+///     .context("failed to parse `server.toml`")
+///     .expect_err("not a decimal");
+/// let tree = anyhow_error_tree(&err, &ErrorChainOptions::new());
+///
+/// let expected = "\
+/// failed to parse `server.toml`
+/// `-- invalid port `0x2a`
+///     `-- invalid digit found in string
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// ```
+///
+/// [`error_chain_tree()`]: fn.error_chain_tree.html
+#[cfg(feature = "anyhow")]
+pub fn anyhow_error_tree(err: &anyhow::Error, opts: &ErrorChainOptions) -> TreeNode {
+    error_chain_tree(err.as_ref(), opts)
+}
+
+/// Creates a tree of the `eyre::Report` and its sources.
+///
+/// Each context attached to the report is a node, followed by the error which it wraps.
+/// Sections added by the report handler are not shown.
+/// See [`error_chain_tree()`] for the tree.
+///
+/// # Examples
+///
+/// ```
+/// use eyre::WrapErr;
+/// use plaintextree::{eyre_report_tree, ErrorChainOptions};
+///
+/// let report = "0x2a"
+///     .parse::<u32>()
+///     .wrap_err("invalid port `0x2a`")
+///     .wrap_err("failed to parse `server.toml`")
+///     .expect_err("not a decimal");
+/// let tree = eyre_report_tree(&report, &ErrorChainOptions::new());
+///
+/// let expected = "\
+/// failed to parse `server.toml`
+/// `-- invalid port `0x2a`
+///     `-- invalid digit found in string
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// ```
+///
+/// [`error_chain_tree()`]: fn.error_chain_tree.html
+#[cfg(feature = "eyre")]
+pub fn eyre_report_tree(report: &eyre::Report, opts: &ErrorChainOptions) -> TreeNode {
+    error_chain_tree(report.as_ref(), opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fmt;
+
+    /// Error with an optional source.
+    #[derive(Debug)]
+    struct Layer {
+        /// Message.
+        msg: &'static str,
+        /// Source.
+        source: Option<Box<Layer>>,
+    }
+
+    impl fmt::Display for Layer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.msg)
+        }
+    }
+
+    impl Error for Layer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref().map(|source| source as _)
+        }
+    }
+
+    #[test]
+    fn nested_debug() {
+        let err = Layer {
+            msg: "request failed\n",
+            source: Some(Box::new(Layer {
+                msg: "connection reset",
+                source: None,
+            })),
+        };
+        let mut opts = ErrorChainOptions::new();
+        opts.debug(true);
+        let got = error_chain_tree(&err, &opts).to_string();
+        let expected = "request failed\n\
+                        Layer { msg: \"request failed\\n\", source: Some(Layer { msg: \"connection reset\", source: None }) }\n\
+                        `-- connection reset\n    \
+                        Layer { msg: \"connection reset\", source: None }\n";
+        assert_eq!(got, expected);
+
+        opts.alternate_debug(true);
+        let got = error_chain_tree(err.source.as_deref().expect("has a source"), &opts).to_string();
+        let expected = "connection reset\n\
+                        Layer {\n    \
+                        msg: \"connection reset\",\n    \
+                        source: None,\n\
+                        }\n";
+        assert_eq!(got, expected);
+    }
+}
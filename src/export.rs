@@ -0,0 +1,11 @@
+//! Exporters of retained trees to other formats.
+
+pub(crate) mod asciidoc;
+pub(crate) mod html;
+pub(crate) mod latex;
+pub(crate) mod markdown;
+pub(crate) mod org;
+pub(crate) mod plantuml;
+pub(crate) mod rst;
+#[cfg(feature = "svg")]
+pub(crate) mod svg;
@@ -0,0 +1,115 @@
+//! AsciiDoc exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// AsciiDoc nested list renderer.
+///
+/// Each node is rendered as a list item, and the depth of the node is represented by the number
+/// of `*` markers.
+/// Characters which would be interpreted as inline markups are escaped as character references,
+/// so labels are displayed as is.
+/// Lines of multiline labels are separated by hard line breaks (` +`), and empty lines in labels
+/// start new paragraphs attached to the item by list continuations (`+`).
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{AsciiDocRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("src");
+/// root.push_child(TreeNode::new("lib.rs\ncrate root"));
+/// root.children_mut()[0].push_child(TreeNode::new("*_internal_*"));
+///
+/// let got = AsciiDocRenderer::new().render(&root, String::new())?;
+///
+/// let expected = "\
+/// * src
+/// ** lib.rs +
+/// crate root
+/// *** &#42;&#95;internal&#95;&#42;
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct AsciiDocRenderer {}
+
+impl AsciiDocRenderer {
+    /// Creates a new `AsciiDocRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the top-level list item.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        render_node(&mut writer, tree, 1)?;
+        Ok(writer)
+    }
+}
+
+/// Renders the node and its descendants.
+fn render_node<W: fmt::Write>(writer: &mut W, node: &TreeNode, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        writer.write_char('*')?;
+    }
+    writer.write_char(' ')?;
+
+    let mut lines = node.label().lines();
+    write_escaped(writer, lines.next().unwrap_or(""), false)?;
+    let mut paragraph_break = false;
+    for line in lines {
+        if line.is_empty() {
+            // Consecutive empty lines are squashed since paragraphs cannot be empty.
+            paragraph_break = true;
+            continue;
+        }
+        writer.write_str(if paragraph_break { "\n+\n" } else { " +\n" })?;
+        paragraph_break = false;
+        write_escaped(writer, line, true)?;
+    }
+    writer.write_char('\n')?;
+
+    node.children()
+        .iter()
+        .try_for_each(|child| render_node(writer, child, depth + 1))
+}
+
+/// Writes the line with inline markup characters escaped.
+///
+/// If `line_head` is true, characters which would be interpreted as block markups at the
+/// beginning of a line are also escaped.
+fn write_escaped<W: fmt::Write>(writer: &mut W, line: &str, line_head: bool) -> fmt::Result {
+    let mut at_head = line_head;
+    for c in line.chars() {
+        let escape = match c {
+            '&' | '*' | '_' | '`' | '#' | '^' | '~' | '+' | '{' | '[' | '<' | '\\' => true,
+            '.' | '-' | '=' | '/' | '|' | ' ' | '\t' => at_head,
+            _ => false,
+        };
+        if escape {
+            let c = if c == ' ' || c == '\t' { '\u{A0}' } else { c };
+            write!(writer, "&#{};", u32::from(c))?;
+        } else {
+            writer.write_char(c)?;
+        }
+        at_head = at_head && (c == ' ' || c == '\t');
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraphs_and_line_heads() -> Result<()> {
+        let tree = TreeNode::new("a\n  b\n\n\n.c\n\n");
+        let got = AsciiDocRenderer::new().render(&tree, String::new())?;
+        assert_eq!(got, "* a +\n&#160;&#160;b\n+\n&#46;c\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! HTML exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// HTML nested list renderer.
+///
+/// Each node is rendered as a `<li>` element, and children are wrapped in a `<ul>` element.
+/// Special characters in labels are escaped, and line breaks in labels are rendered as `<br>`.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{HtmlRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("<root>");
+/// root.push_child(TreeNode::new("foo & bar"));
+/// root.push_child(TreeNode::new("baz\nqux"));
+///
+/// let got = HtmlRenderer::new().render(&root, String::new())?;
+///
+/// let expected = r#"<ul>
+///   <li>&lt;root&gt;
+///     <ul>
+///       <li>foo &amp; bar</li>
+///       <li>baz<br>qux</li>
+///     </ul>
+///   </li>
+/// </ul>
+/// "#;
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+///
+/// With [`collapsible`][`Self::collapsible`] mode, every branch node is rendered as a
+/// `<details>` element with its label in `<summary>`, so that the tree can be folded without any
+/// script.
+///
+/// ```
+/// use plaintextree::{HtmlRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("root");
+/// root.push_child(TreeNode::new("leaf"));
+///
+/// let got = HtmlRenderer::new()
+///     .collapsible(true)
+///     .open(true)
+///     .render(&root, String::new())?;
+///
+/// let expected = r#"<ul>
+///   <li><details open><summary>root</summary>
+///     <ul>
+///       <li>leaf</li>
+///     </ul>
+///   </details></li>
+/// </ul>
+/// "#;
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct HtmlRenderer {
+    /// Whether to render branch nodes as `<details>` elements.
+    collapsible: bool,
+    /// Whether the `<details>` elements are initially expanded.
+    open: bool,
+}
+
+impl HtmlRenderer {
+    /// Creates a new `HtmlRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to render branch nodes as `<details>` elements.
+    ///
+    /// Default is `false`.
+    pub fn collapsible(&mut self, v: bool) -> &mut Self {
+        self.collapsible = v;
+        self
+    }
+
+    /// Sets whether the `<details>` elements are initially expanded.
+    ///
+    /// This has no effect unless [`collapsible`][`Self::collapsible`] mode is enabled.
+    ///
+    /// Default is `false`.
+    pub fn open(&mut self, v: bool) -> &mut Self {
+        self.open = v;
+        self
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the only item of the outermost list.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        writer.write_str("<ul>\n")?;
+        self.render_node(&mut writer, tree, 1)?;
+        writer.write_str("</ul>\n")?;
+        Ok(writer)
+    }
+
+    /// Renders the node and its descendants.
+    fn render_node<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        node: &TreeNode,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = depth * 4 - 2;
+        write!(writer, "{:indent$}<li>", "", indent = indent)?;
+        if node.is_leaf() {
+            write_label(writer, node.label())?;
+            return writer.write_str("</li>\n");
+        }
+
+        if self.collapsible {
+            writer.write_str(if self.open {
+                "<details open><summary>"
+            } else {
+                "<details><summary>"
+            })?;
+            write_label(writer, node.label())?;
+            writer.write_str("</summary>")?;
+        } else {
+            write_label(writer, node.label())?;
+        }
+        writeln!(writer, "\n{:indent$}<ul>", "", indent = indent + 2)?;
+        node.children()
+            .iter()
+            .try_for_each(|child| self.render_node(writer, child, depth + 1))?;
+        writeln!(writer, "{:indent$}</ul>", "", indent = indent + 2)?;
+        let close = if self.collapsible {
+            "</details></li>"
+        } else {
+            "</li>"
+        };
+        writeln!(writer, "{:indent$}{}", "", close, indent = indent)
+    }
+}
+
+/// Writes the escaped label, with line breaks replaced by `<br>`.
+pub(crate) fn write_label<W: fmt::Write>(writer: &mut W, label: &str) -> fmt::Result {
+    for (i, line) in label.lines().enumerate() {
+        if i != 0 {
+            writer.write_str("<br>")?;
+        }
+        write_escaped(writer, line)?;
+    }
+    Ok(())
+}
+
+/// Writes the string with HTML special characters escaped.
+pub(crate) fn write_escaped<W: fmt::Write>(writer: &mut W, s: &str) -> fmt::Result {
+    let mut rest = s;
+    while let Some(pos) = rest.find(&['&', '<', '>', '"', '\''][..]) {
+        writer.write_str(&rest[..pos])?;
+        writer.write_str(match rest.as_bytes()[pos] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'"' => "&quot;",
+            _ => "&#39;",
+        })?;
+        rest = &rest[(pos + 1)..];
+    }
+    writer.write_str(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() -> fmt::Result {
+        let mut buf = String::new();
+        write_escaped(&mut buf, r#"<a href="x">'&'</a>"#)?;
+        assert_eq!(buf, "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;");
+        Ok(())
+    }
+
+    #[test]
+    fn leaf_root() -> Result<()> {
+        let got = HtmlRenderer::new().render(&TreeNode::new("root"), String::new())?;
+        assert_eq!(got, "<ul>\n  <li>root</li>\n</ul>\n");
+        Ok(())
+    }
+
+    #[test]
+    fn collapsible_leaves_stay_plain() -> Result<()> {
+        let mut root = TreeNode::new("a");
+        root.push_child(TreeNode::new("b"))
+            .push_child(TreeNode::new("<c>"));
+        root.children_mut()[0].push_child(TreeNode::new("d"));
+
+        let got = HtmlRenderer::new()
+            .collapsible(true)
+            .render(&root, String::new())?;
+        let expected = "<ul>\n\
+                        \x20 <li><details><summary>a</summary>\n\
+                        \x20   <ul>\n\
+                        \x20     <li><details><summary>b</summary>\n\
+                        \x20       <ul>\n\
+                        \x20         <li>d</li>\n\
+                        \x20       </ul>\n\
+                        \x20     </details></li>\n\
+                        \x20     <li>&lt;c&gt;</li>\n\
+                        \x20   </ul>\n\
+                        \x20 </details></li>\n\
+                        </ul>\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! LaTeX exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// LaTeX syntax to render trees with.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatexStyle {
+    /// `\dirtree` command of the `dirtree` package.
+    #[default]
+    Dirtree,
+    /// `forest` environment of the `forest` package.
+    Forest,
+}
+
+/// LaTeX renderer.
+///
+/// Trees are rendered as `\dirtree{...}` command by default, or as `forest` environment.
+/// Special characters in labels are escaped, and each label is put into a group so that
+/// delimiters of the tree syntax in labels are not interpreted.
+/// Multiline labels are rendered as left-aligned stacked lines.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{LatexRenderer, LatexStyle, TreeNode};
+///
+/// let mut root = TreeNode::new("src");
+/// root.push_child(TreeNode::new("main.rs"));
+/// root.push_child(TreeNode::new("100%_done"));
+///
+/// let got = LatexRenderer::new().render(&root, String::new())?;
+/// let expected = r"\dirtree{%
+/// .1 {src}.
+/// .2 {main.rs}.
+/// .2 {100\%\_done}.
+/// }
+/// ";
+/// assert_eq!(got, expected);
+///
+/// let got = LatexRenderer::new()
+///     .style(LatexStyle::Forest)
+///     .render(&root, String::new())?;
+/// let expected = r"\begin{forest}
+///   [{src}
+///     [{main.rs}]
+///     [{100\%\_done}]
+///   ]
+/// \end{forest}
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct LatexRenderer {
+    /// Syntax to render trees with.
+    style: LatexStyle,
+}
+
+impl LatexRenderer {
+    /// Creates a new `LatexRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the syntax to render trees with.
+    ///
+    /// Default is `LatexStyle::Dirtree`.
+    pub fn style(&mut self, style: LatexStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        match self.style {
+            LatexStyle::Dirtree => {
+                writer.write_str("\\dirtree{%\n")?;
+                render_dirtree_node(&mut writer, tree, 1)?;
+                writer.write_str("}\n")?;
+            }
+            LatexStyle::Forest => {
+                writer.write_str("\\begin{forest}\n")?;
+                render_forest_node(&mut writer, tree, 1)?;
+                writer.write_str("\\end{forest}\n")?;
+            }
+        }
+        Ok(writer)
+    }
+}
+
+/// Renders the node and its descendants as `\dirtree` entries.
+fn render_dirtree_node<W: fmt::Write>(
+    writer: &mut W,
+    node: &TreeNode,
+    depth: usize,
+) -> fmt::Result {
+    write!(writer, ".{} {{", depth)?;
+    if node.label().contains('\n') {
+        writer.write_str("\\shortstack[l]{")?;
+        write_label(writer, node.label())?;
+        writer.write_char('}')?;
+    } else {
+        write_label(writer, node.label())?;
+    }
+    writer.write_str("}.\n")?;
+
+    node.children()
+        .iter()
+        .try_for_each(|child| render_dirtree_node(writer, child, depth + 1))
+}
+
+/// Renders the node and its descendants in the bracket syntax of `forest`.
+fn render_forest_node<W: fmt::Write>(writer: &mut W, node: &TreeNode, depth: usize) -> fmt::Result {
+    let indent = depth * 2;
+    write!(writer, "{:indent$}[{{", "", indent = indent)?;
+    write_label(writer, node.label())?;
+    writer.write_char('}')?;
+    if node.label().contains('\n') {
+        writer.write_str(", align=left")?;
+    }
+    if node.is_leaf() {
+        return writer.write_str("]\n");
+    }
+
+    writer.write_char('\n')?;
+    node.children()
+        .iter()
+        .try_for_each(|child| render_forest_node(writer, child, depth + 1))?;
+    writeln!(writer, "{:indent$}]", "", indent = indent)
+}
+
+/// Writes the escaped label, with line breaks replaced by `\\`.
+fn write_label<W: fmt::Write>(writer: &mut W, label: &str) -> fmt::Result {
+    for (i, line) in label.lines().enumerate() {
+        if i != 0 {
+            writer.write_str("\\\\")?;
+        }
+        write_escaped(writer, line)?;
+    }
+    Ok(())
+}
+
+/// Writes the string with LaTeX special characters escaped.
+fn write_escaped<W: fmt::Write>(writer: &mut W, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                writer.write_char('\\')?;
+                writer.write_char(c)?;
+            }
+            '\\' => writer.write_str("\\textbackslash{}")?,
+            '~' => writer.write_str("\\textasciitilde{}")?,
+            '^' => writer.write_str("\\textasciicircum{}")?,
+            c => writer.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() -> fmt::Result {
+        let mut buf = String::new();
+        write_escaped(&mut buf, r"a\b{c}~^$")?;
+        assert_eq!(
+            buf,
+            r"a\textbackslash{}b\{c\}\textasciitilde{}\textasciicircum{}\$"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multiline_labels() -> Result<()> {
+        let tree = TreeNode::new("a\nb");
+        let got = LatexRenderer::new().render(&tree, String::new())?;
+        assert_eq!(got, "\\dirtree{%\n.1 {\\shortstack[l]{a\\\\b}}.\n}\n");
+
+        let got = LatexRenderer::new()
+            .style(LatexStyle::Forest)
+            .render(&tree, String::new())?;
+        assert_eq!(
+            got,
+            "\\begin{forest}\n  [{a\\\\b}, align=left]\n\\end{forest}\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Markdown exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// Markdown nested bullet list renderer.
+///
+/// Each node is rendered as a list item, and each nest level is indented by 2 spaces.
+/// Characters which would be interpreted as inline markups or block markers are escaped with
+/// backslashes.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{MarkdownRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("src");
+/// root.push_child(TreeNode::new("main.rs"));
+/// root.push_child(TreeNode::new("*bin*"));
+/// root.children_mut()[1].push_child(TreeNode::new("tool.rs\nentry point"));
+///
+/// let got = MarkdownRenderer::new().render(&root, String::new())?;
+///
+/// let expected = r"- src
+///   - main.rs
+///   - \*bin\*
+///     - tool.rs
+///       entry point
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkdownRenderer {
+    /// Bullet character.
+    bullet: char,
+}
+
+impl Default for MarkdownRenderer {
+    fn default() -> Self {
+        Self { bullet: '-' }
+    }
+}
+
+impl MarkdownRenderer {
+    /// Creates a new `MarkdownRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the bullet character.
+    ///
+    /// The value is `'-'` by default.
+    pub fn bullet(&mut self, bullet: char) -> &mut Self {
+        self.bullet = bullet;
+        self
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the top-level list item.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        self.render_node(&mut writer, tree, 0)?;
+        Ok(writer)
+    }
+
+    /// Renders the node and its descendants.
+    fn render_node<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        node: &TreeNode,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = depth * 2;
+        let mut lines = node.label().lines();
+        write!(writer, "{:indent$}{} ", "", self.bullet, indent = indent)?;
+        write_escaped(writer, lines.next().unwrap_or(""))?;
+        writer.write_char('\n')?;
+        for line in lines {
+            if !line.is_empty() {
+                write!(writer, "{:indent$}", "", indent = indent + 2)?;
+                write_escaped(writer, line)?;
+            }
+            writer.write_char('\n')?;
+        }
+
+        node.children()
+            .iter()
+            .try_for_each(|child| self.render_node(writer, child, depth + 1))
+    }
+}
+
+/// Writes the line with markup characters escaped.
+///
+/// Both inline markups and markers which would start other blocks at the beginning of the line,
+/// such as headings, bullets, ordered list markers, and block quotes, are escaped.
+/// Leading whitespace is removed, since it would start an indented code block or change the
+/// nesting of the item.
+fn write_escaped<W: fmt::Write>(writer: &mut W, line: &str) -> fmt::Result {
+    let line = line.trim_start();
+    let marker_end = ordered_list_marker_end(line);
+    for (i, c) in line.char_indices() {
+        let escape = match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '|' => true,
+            '#' | '-' | '+' | '=' if i == 0 => true,
+            _ => Some(i) == marker_end,
+        };
+        if escape {
+            writer.write_char('\\')?;
+        }
+        writer.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Returns the byte position of the `.` or `)` ending the ordered list marker at the start of
+/// the line, if any.
+///
+/// Ordered list markers are up to 9 arabic numerals followed by `.` or `)`.
+fn ordered_list_marker_end(line: &str) -> Option<usize> {
+    let len = line.bytes().take_while(u8::is_ascii_digit).count();
+    match line.as_bytes().get(len) {
+        Some(b'.') | Some(b')') if (1..=9).contains(&len) => Some(len),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_bullet_and_blank_lines() -> Result<()> {
+        let mut root = TreeNode::new("root");
+        root.push_child(TreeNode::new("foo\n\nbar"));
+
+        let got = MarkdownRenderer::new()
+            .bullet('*')
+            .render(&root, String::new())?;
+        assert_eq!(got, "* root\n  * foo\n\n    bar\n");
+        Ok(())
+    }
+
+    #[test]
+    fn escape_block_markers() -> Result<()> {
+        let mut root = TreeNode::new("# heading");
+        root.push_child(TreeNode::new(
+            "- item\n1. first\n2) second\n+ plus\n> quote",
+        ));
+        root.push_child(TreeNode::new("version 1. 2"));
+
+        let got = MarkdownRenderer::new().render(&root, String::new())?;
+        let expected = r"- \# heading
+  - \- item
+    1\. first
+    2\) second
+    \+ plus
+    \> quote
+  - version 1. 2
+";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn escape_inline_markups() -> Result<()> {
+        let root = TreeNode::new(r"*a* _b_ `c` [d](e) <f> g|h \i");
+
+        let got = MarkdownRenderer::new().render(&root, String::new())?;
+        assert_eq!(
+            got,
+            "- \\*a\\* \\_b\\_ \\`c\\` \\[d\\](e) \\<f\\> g\\|h \\\\i\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Org-mode exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// Org-mode heading renderer.
+///
+/// Each node is rendered as a heading, and the depth of the node is represented by the number
+/// of `*` markers.
+/// The first line of a label is used as the heading title, and the rest lines are used as the
+/// body text.
+/// Body lines are indented to the title column, so that they are never parsed as headings.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{OrgRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("build log");
+/// root.push_child(TreeNode::new("compile\n2 warnings"));
+/// root.children_mut()[0].push_child(TreeNode::new("link"));
+///
+/// let got = OrgRenderer::new().render(&root, String::new())?;
+///
+/// let expected = "\
+/// * build log
+/// ** compile
+///    2 warnings
+/// *** link
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct OrgRenderer {}
+
+impl OrgRenderer {
+    /// Creates a new `OrgRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the top-level heading.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        render_node(&mut writer, tree, 1)?;
+        Ok(writer)
+    }
+}
+
+/// Renders the node and its descendants.
+fn render_node<W: fmt::Write>(writer: &mut W, node: &TreeNode, depth: usize) -> fmt::Result {
+    let mut lines = node.label().lines();
+    for _ in 0..depth {
+        writer.write_char('*')?;
+    }
+    writeln!(writer, " {}", lines.next().unwrap_or(""))?;
+    for line in lines {
+        if line.is_empty() {
+            writeln!(writer)?;
+        } else {
+            writeln!(writer, "{:indent$}{}", "", line, indent = depth + 1)?;
+        }
+    }
+
+    node.children()
+        .iter()
+        .try_for_each(|child| render_node(writer, child, depth + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_looking_like_heading() -> Result<()> {
+        let got =
+            OrgRenderer::new().render(&TreeNode::new("title\n* item\n\nend"), String::new())?;
+        assert_eq!(got, "* title\n  * item\n\n  end\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+//! PlantUML exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// PlantUML mind map renderer.
+///
+/// The tree is rendered as a `@startmindmap` diagram, and the depth of each node is represented
+/// by the number of `*` markers.
+/// Multiline labels are rendered using the `:...;` block syntax.
+/// Semicolons at the ends of label lines are written as `<U+003B>`, since they would end the
+/// block.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{PlantUmlRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("service");
+/// root.push_child(TreeNode::new("api"));
+/// root.children_mut()[0].push_child(TreeNode::new("handlers\n(generated)"));
+/// root.push_child(TreeNode::new("db"));
+///
+/// let got = PlantUmlRenderer::new().render(&root, String::new())?;
+///
+/// let expected = "\
+/// @startmindmap
+/// * service
+/// ** api
+/// ***:handlers
+/// (generated);
+/// ** db
+/// @endmindmap
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct PlantUmlRenderer {}
+
+impl PlantUmlRenderer {
+    /// Creates a new `PlantUmlRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the center of the mind map.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        writer.write_str("@startmindmap\n")?;
+        render_node(&mut writer, tree, 1)?;
+        writer.write_str("@endmindmap\n")?;
+        Ok(writer)
+    }
+}
+
+/// Renders the node and its descendants.
+fn render_node<W: fmt::Write>(writer: &mut W, node: &TreeNode, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        writer.write_char('*')?;
+    }
+    let label = node.label();
+    if label.contains('\n') {
+        writer.write_char(':')?;
+        for (i, line) in label.trim_end_matches('\n').split('\n').enumerate() {
+            if i != 0 {
+                writer.write_char('\n')?;
+            }
+            match line.strip_suffix(';') {
+                Some(line) => write!(writer, "{}<U+003B>", line)?,
+                None => writer.write_str(line)?,
+            }
+        }
+        writer.write_str(";\n")?;
+    } else {
+        writeln!(writer, " {}", label)?;
+    }
+
+    node.children()
+        .iter()
+        .try_for_each(|child| render_node(writer, child, depth + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_node() -> Result<()> {
+        let got = PlantUmlRenderer::new().render(&TreeNode::new("root"), String::new())?;
+        assert_eq!(got, "@startmindmap\n* root\n@endmindmap\n");
+        Ok(())
+    }
+
+    #[test]
+    fn semicolons_in_multiline_labels() -> Result<()> {
+        let mut root = TreeNode::new("a;");
+        root.push_child(TreeNode::new("let x = 1;\nlet y = 2;"));
+        let got = PlantUmlRenderer::new().render(&root, String::new())?;
+        let expected = "@startmindmap\n\
+                        * a;\n\
+                        **:let x = 1<U+003B>\n\
+                        let y = 2<U+003B>;\n\
+                        @endmindmap\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! reStructuredText exporter.
+
+use std::fmt;
+
+use crate::{tree_node::TreeNode, tree_printer::Result};
+
+/// reStructuredText nested bullet list renderer.
+///
+/// Each node is rendered as a `-` list item, and the item body (continuation lines and nested
+/// lists) is indented by 3 spaces.
+/// Nested lists are separated from the surrounding text by blank lines as reST requires.
+/// Characters which would be interpreted as inline markups are escaped with backslashes.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{RstRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("docs");
+/// root.push_child(TreeNode::new("index.rst\ntop page"));
+/// root.children_mut()[0].push_child(TreeNode::new("*draft*"));
+/// root.push_child(TreeNode::new("conf.py"));
+///
+/// let got = RstRenderer::new().render(&root, String::new())?;
+///
+/// let expected = r"-  docs
+///
+///    -  index.rst
+///       top page
+///
+///       -  \*draft\*
+///
+///    -  conf.py
+///
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct RstRenderer {}
+
+impl RstRenderer {
+    /// Creates a new `RstRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The root node is rendered as the only item of the top-level list.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        render_node(&mut writer, tree, 0)?;
+        Ok(writer)
+    }
+}
+
+/// Width of the list item marker and the item body indent.
+const INDENT: usize = 3;
+
+/// Renders the node and its descendants.
+fn render_node<W: fmt::Write>(writer: &mut W, node: &TreeNode, depth: usize) -> fmt::Result {
+    let indent = depth * INDENT;
+    let mut lines = node.label().lines();
+    write!(writer, "{:indent$}-  ", "", indent = indent)?;
+    write_escaped(writer, lines.next().unwrap_or(""))?;
+    writer.write_char('\n')?;
+    for line in lines {
+        if !line.is_empty() {
+            write!(writer, "{:indent$}", "", indent = indent + INDENT)?;
+            write_escaped(writer, line)?;
+        }
+        writer.write_char('\n')?;
+    }
+
+    if !node.is_leaf() {
+        writer.write_char('\n')?;
+        node.children()
+            .iter()
+            .try_for_each(|child| render_node(writer, child, depth + 1))?;
+        writer.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Writes the line with markup characters escaped.
+///
+/// Both inline markups and markers which would start other blocks at the beginning of the line,
+/// such as bullets, enumerators, and directives, are escaped.
+/// Leading whitespace is removed, since it would change the indentation of the item body.
+fn write_escaped<W: fmt::Write>(writer: &mut W, line: &str) -> fmt::Result {
+    let line = line.trim_start();
+    let enumerator_end = enumerator_end(line);
+    for (i, c) in line.char_indices() {
+        let escape = match c {
+            '\\' | '*' | '`' | '|' | '_' => true,
+            '-' | '+' | '#' | '.' | ':' | '>' | '=' if i == 0 => true,
+            _ => Some(i) == enumerator_end,
+        };
+        if escape {
+            writer.write_char('\\')?;
+        }
+        writer.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Returns the byte position of the `.` or `)` ending the list enumerator at the start of the
+/// line, if any.
+///
+/// Enumerators are arabic numerals, single letters, roman numerals, and `#`, followed by `.`
+/// or `)`, or enclosed by `(` and `)`.
+fn enumerator_end(line: &str) -> Option<usize> {
+    let start = if line.starts_with('(') { 1 } else { 0 };
+    let len = line[start..]
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+        .unwrap_or(line.len() - start);
+    let body = &line[start..start + len];
+    let is_enumerator = body == "#"
+        || (!body.is_empty() && body.bytes().all(|b| b.is_ascii_digit()))
+        || (body.len() == 1 && body.bytes().all(|b| b.is_ascii_alphabetic()))
+        || (!body.is_empty() && body.chars().all(|c| "ivxlcdm".contains(c)))
+        || (!body.is_empty() && body.chars().all(|c| "IVXLCDM".contains(c)));
+    let end = start + len;
+    let terminator_ok = match line[end..].chars().next() {
+        Some('.') => start == 0,
+        Some(')') => true,
+        _ => false,
+    };
+    if !is_enumerator || !terminator_ok {
+        return None;
+    }
+    let followed_by_space = line[end + 1..]
+        .chars()
+        .next()
+        .map_or(true, char::is_whitespace);
+    followed_by_space.then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_paragraphs() -> Result<()> {
+        let got = RstRenderer::new().render(&TreeNode::new("a\n\n  - b_"), String::new())?;
+        assert_eq!(got, "-  a\n\n   \\- b\\_\n");
+        Ok(())
+    }
+
+    #[test]
+    fn block_markers_on_first_lines() -> Result<()> {
+        let cases = [
+            ("- foo", r"-  \- foo"),
+            ("* foo", r"-  \* foo"),
+            ("1. foo", r"-  1\. foo"),
+            ("#) foo", r"-  \#\) foo"),
+            ("(iv) foo", r"-  (iv\) foo"),
+            (".. note:: foo", r"-  \.. note:: foo"),
+            ("1.5 m", "-  1.5 m"),
+            ("ab. cd", "-  ab. cd"),
+        ];
+        for (label, expected) in cases {
+            let got = RstRenderer::new().render(&TreeNode::new(label), String::new())?;
+            assert_eq!(got, format!("{}\n", expected), "{:?}", label);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn enumerators_on_continuation_lines() -> Result<()> {
+        let got = RstRenderer::new().render(&TreeNode::new("a\n2) b\nA. c"), String::new())?;
+        assert_eq!(got, "-  a\n   2\\) b\n   A\\. c\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+//! SVG exporter.
+
+use std::fmt;
+
+use crate::{
+    config::{EdgeConfig, TreeConfig},
+    export::html::write_escaped,
+    tree_node::TreeNode,
+    tree_printer::Result,
+};
+
+/// SVG renderer.
+///
+/// The tree is drawn with `<text>` elements for labels and `<line>` elements for edges, using
+/// the same geometry as the plain text rendering: a label is placed at the column where it
+/// would be printed after the prefixes, and an edge is drawn at the column of the vertical line
+/// of the prefix.
+/// The image is sized for a monospace font whose advance width is 0.6 times the font size.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{SvgRenderer, TreeNode};
+///
+/// let mut root = TreeNode::new("root");
+/// root.push_child(TreeNode::new("a & b"));
+///
+/// let got = SvgRenderer::new().font_size(10.0).render(&root, String::new())?;
+///
+/// let expected = r#"<svg xmlns="http://www.w3.org/2000/svg" width="54" height="24" font-family="monospace" font-size="10">
+/// <g stroke="currentColor" fill="none">
+/// <line x1="3" y1="12" x2="3" y2="18"/>
+/// <line x1="3" y1="18" x2="21" y2="18"/>
+/// </g>
+/// <g fill="currentColor">
+/// <text x="0" y="10">root</text>
+/// <text x="24" y="22">a &amp; b</text>
+/// </g>
+/// </svg>
+/// "#;
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SvgRenderer {
+    /// Edge config to compute the layout with.
+    edge: EdgeConfig,
+    /// Tree config to measure the widths with.
+    tree_config: TreeConfig,
+    /// Font size in pixels.
+    font_size: f64,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self {
+            edge: EdgeConfig::default(),
+            tree_config: TreeConfig::new(),
+            font_size: 14.0,
+        }
+    }
+}
+
+impl SvgRenderer {
+    /// Creates a new `SvgRenderer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the edge config to compute the layout with.
+    ///
+    /// Only the widths of the prefixes are used, and edges are always drawn as lines.
+    ///
+    /// Default is `EdgeConfig::Ascii`.
+    pub fn edge(&mut self, edge: EdgeConfig) -> &mut Self {
+        self.edge = edge;
+        self
+    }
+
+    /// Sets the tree config to measure the widths of labels and prefixes with.
+    ///
+    /// Only the width oracle and the width of ambiguous width characters are used.
+    ///
+    /// Default is `TreeConfig::new()`.
+    pub fn tree_config(&mut self, tree_config: TreeConfig) -> &mut Self {
+        self.tree_config = tree_config;
+        self
+    }
+
+    /// Sets the font size in pixels.
+    ///
+    /// Default is `14.0`.
+    pub fn font_size(&mut self, size: f64) -> &mut Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    pub fn render<W: fmt::Write>(&self, tree: &TreeNode, mut writer: W) -> Result<W> {
+        let mut layout = Layout::default();
+        self.layout_node(&mut layout, tree, 0);
+
+        let char_width = self.font_size * 0.6;
+        let line_height = self.font_size * 1.2;
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="{}">"#,
+            Px(layout.columns as f64 * char_width),
+            Px(layout.rows as f64 * line_height),
+            Px(self.font_size),
+        )?;
+
+        writer.write_str("<g stroke=\"currentColor\" fill=\"none\">\n")?;
+        for edge in &layout.edges {
+            let x = (edge.column as f64 + 0.5) * char_width;
+            let y_top = edge.top * line_height;
+            let y = (edge.row as f64 + 0.5) * line_height;
+            let x_end = (edge.end_column as f64 - 0.5) * char_width;
+            writeln!(
+                writer,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}"/>"#,
+                Px(y_top),
+                Px(y),
+                x = Px(x),
+            )?;
+            writeln!(
+                writer,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}"/>"#,
+                Px(x),
+                Px(x_end),
+                y = Px(y),
+            )?;
+        }
+        writer.write_str("</g>\n")?;
+
+        writer.write_str("<g fill=\"currentColor\">\n")?;
+        for label in &layout.labels {
+            let x = Px(label.column as f64 * char_width);
+            for (i, line) in label.text.lines().enumerate() {
+                let baseline = (label.row + i) as f64 * line_height + self.font_size;
+                write!(writer, r#"<text x="{}" y="{}">"#, x, Px(baseline))?;
+                write_escaped(&mut writer, line)?;
+                writer.write_str("</text>\n")?;
+            }
+        }
+        writer.write_str("</g>\n</svg>\n")?;
+
+        Ok(writer)
+    }
+
+    /// Computes the layout of the node and its descendants.
+    fn layout_node<'a>(&self, layout: &mut Layout<'a>, node: &'a TreeNode, column: usize) {
+        let row = layout.rows;
+        let num_lines = node.label().lines().count().max(1);
+        let label_width = node
+            .label()
+            .lines()
+            .map(|line| self.tree_config.str_width(line))
+            .max()
+            .unwrap_or(0);
+        layout.labels.push(PlacedLabel {
+            text: node.label(),
+            column,
+            row,
+        });
+        layout.rows += num_lines;
+        layout.columns = layout.columns.max(column + label_width);
+
+        let mut top = (row + num_lines) as f64;
+        let num_children = node.children().len();
+        for (i, child) in node.children().iter().enumerate() {
+            let indent = self.edge.prefix_and_padding_width(
+                i + 1 == num_children,
+                true,
+                &self.tree_config.width_oracle(),
+            );
+            let child_row = layout.rows;
+            layout.edges.push(PlacedEdge {
+                column,
+                top,
+                row: child_row,
+                end_column: column + indent,
+            });
+            top = child_row as f64 + 0.5;
+            self.layout_node(layout, child, column + indent);
+        }
+    }
+}
+
+/// Layout of a tree, in character cells.
+#[derive(Default)]
+struct Layout<'a> {
+    /// Labels.
+    labels: Vec<PlacedLabel<'a>>,
+    /// Edges.
+    edges: Vec<PlacedEdge>,
+    /// Number of rows.
+    rows: usize,
+    /// Number of columns.
+    columns: usize,
+}
+
+/// Label placed on the grid.
+struct PlacedLabel<'a> {
+    /// Label text.
+    text: &'a str,
+    /// Column of the first character.
+    column: usize,
+    /// Row of the first line.
+    row: usize,
+}
+
+/// Edge from the parent's vertical line to a child, placed on the grid.
+struct PlacedEdge {
+    /// Column of the vertical line.
+    column: usize,
+    /// Position where the vertical line starts, in rows.
+    ///
+    /// This is the bottom of the parent label for the first child, and the middle of the
+    /// previous sibling's first line for the others.
+    top: f64,
+    /// Row of the child.
+    row: usize,
+    /// Column of the child label.
+    end_column: usize,
+}
+
+/// Length in pixels, rounded to 2 decimal places.
+struct Px(f64);
+
+impl fmt::Display for Px {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&((self.0 * 100.0).round() / 100.0), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{config::TreeConfigBuilder, width::AmbiWidth};
+
+    #[test]
+    fn vertical_line_spans_multiline_sibling() -> Result<()> {
+        let mut root = TreeNode::new("r");
+        root.push_child(TreeNode::new("a\nb"));
+        root.push_child(TreeNode::new("c"));
+
+        let got = SvgRenderer::new()
+            .font_size(10.0)
+            .render(&root, String::new())?;
+        let edges = got
+            .lines()
+            .filter(|line| line.starts_with("<line"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            [
+                r#"<line x1="3" y1="12" x2="3" y2="18"/>"#,
+                r#"<line x1="3" y1="18" x2="21" y2="18"/>"#,
+                r#"<line x1="3" y1="18" x2="3" y2="42"/>"#,
+                r#"<line x1="3" y1="42" x2="21" y2="42"/>"#,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ambiguous_width() -> Result<()> {
+        let mut root = TreeNode::new("r");
+        root.push_child(TreeNode::new("a"));
+
+        let got = SvgRenderer::new()
+            .edge(EdgeConfig::UnicodeSingleWidth)
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .ambiguous_width(AmbiWidth::Wide)
+                    .build(),
+            )
+            .font_size(10.0)
+            .render(&root, String::new())?;
+        // `└── ` is 7 columns wide.
+        assert!(got.contains(r#"<text x="42" y="22">a</text>"#), "{}", got);
+        Ok(())
+    }
+}
@@ -0,0 +1,585 @@
+//! Trees of directories in the filesystem.
+
+use std::{
+    fs::{self, DirEntry, FileType},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::NodeKind, tree_node::TreeNode};
+
+/// Options for rendering directories as trees.
+#[derive(Debug, Default, Clone)]
+pub struct DirTreeOptions {
+    /// Maximum depth of the entries to show.
+    max_depth: Option<usize>,
+    /// Whether to show entries whose names start with `.`.
+    show_hidden: bool,
+    /// Whether to put directories before other entries.
+    dirs_first: bool,
+    /// Whether to descend into symbolic links to directories.
+    follow_symlinks: bool,
+    /// Whether to put kind suffixes after names.
+    classify: bool,
+    /// Whether to put Nerd Font icons before names.
+    #[cfg(feature = "nerd-fonts")]
+    icons: bool,
+    /// Pattern of the names of non-directory entries to show.
+    include: Option<String>,
+    /// Pattern of the names of entries to hide.
+    exclude: Option<String>,
+}
+
+impl DirTreeOptions {
+    /// Creates a new default `DirTreeOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum depth of the entries to show.
+    ///
+    /// The entries directly in the root directory have depth 1, as `tree -L` does.
+    ///
+    /// Default is `None`, that is, the whole directory is shown.
+    pub fn max_depth(&mut self, max: Option<usize>) -> &mut Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Sets whether to show entries whose names start with `.`.
+    ///
+    /// Default is `false`.
+    pub fn show_hidden(&mut self, v: bool) -> &mut Self {
+        self.show_hidden = v;
+        self
+    }
+
+    /// Sets whether to put directories before other entries.
+    ///
+    /// Otherwise the entries are sorted only by their names.
+    ///
+    /// Default is `false`.
+    pub fn dirs_first(&mut self, v: bool) -> &mut Self {
+        self.dirs_first = v;
+        self
+    }
+
+    /// Sets whether to descend into symbolic links to directories.
+    ///
+    /// Links to their ancestor directories are never descended into, and are annotated with
+    /// `recursive, not followed`.
+    ///
+    /// Default is `false`.
+    pub fn follow_symlinks(&mut self, v: bool) -> &mut Self {
+        self.follow_symlinks = v;
+        self
+    }
+
+    /// Sets whether to put kind suffixes after names, as `tree -F` does.
+    ///
+    /// The suffixes are the ones of [`NodeKind`], and are parts of the labels.
+    /// Executables are detected only on Unix.
+    ///
+    /// Default is `false`.
+    ///
+    /// [`NodeKind`]: enum.NodeKind.html
+    pub fn classify(&mut self, v: bool) -> &mut Self {
+        self.classify = v;
+        self
+    }
+
+    /// Sets whether to put [Nerd Font] icons before names.
+    ///
+    /// The icons are the ones of [`nerd_font_icon()`] for the names and the kinds of the entries,
+    /// and are parts of the labels.
+    ///
+    /// Default is `false`.
+    ///
+    /// [Nerd Font]: https://www.nerdfonts.com/
+    /// [`nerd_font_icon()`]: fn.nerd_font_icon.html
+    #[cfg(feature = "nerd-fonts")]
+    pub fn icons(&mut self, v: bool) -> &mut Self {
+        self.icons = v;
+        self
+    }
+
+    /// Sets the pattern of the names of non-directory entries to show, as `tree -P` does.
+    ///
+    /// Non-directory entries whose names do not match the pattern are hidden, and directories
+    /// are shown regardless of the pattern.
+    /// The pattern is a wildcard: `*` matches any string, `?` matches any character, `[...]`
+    /// matches any character in the brackets (or not in the brackets if started with `!` or
+    /// `^`, and `a-z` matches a range), and `\` escapes the next character.
+    /// Alternatives are separated by `|`, such as `*.rs|*.toml`.
+    ///
+    /// Default is `None`, that is, all entries are shown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use plaintextree::{dir_tree, DirTreeOptions};
+    ///
+    /// # let tmp = std::env::temp_dir().join(format!("plaintextree-doc-glob-{}", std::process::id()));
+    /// # let _ = fs::remove_dir_all(&tmp);
+    /// let project = tmp.join("project");
+    /// fs::create_dir_all(project.join("src"))?;
+    /// fs::create_dir_all(project.join("target/debug"))?;
+    /// fs::write(project.join("Cargo.toml"), "")?;
+    /// fs::write(project.join("README.md"), "")?;
+    /// fs::write(project.join("src/lib.rs"), "")?;
+    ///
+    /// let tree = dir_tree(
+    ///     &project,
+    ///     DirTreeOptions::new()
+    ///         .include(Some("*.rs|*.toml"))
+    ///         .exclude(Some("target")),
+    /// )?;
+    ///
+    /// let expected = format!(
+    ///     "\
+    /// {}
+    /// |-- Cargo.toml
+    /// `-- src
+    ///     `-- lib.rs
+    /// ",
+    ///     project.display()
+    /// );
+    /// assert_eq!(tree.to_string(), expected);
+    /// # fs::remove_dir_all(&tmp)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn include(&mut self, pattern: Option<&str>) -> &mut Self {
+        self.include = pattern.map(Into::into);
+        self
+    }
+
+    /// Sets the pattern of the names of entries to hide, as `tree -I` does.
+    ///
+    /// Both directories and other entries whose names match the pattern are hidden.
+    /// See [`include()`](#method.include) for the syntax of the pattern.
+    ///
+    /// Default is `None`, that is, no entries are hidden by patterns.
+    pub fn exclude(&mut self, pattern: Option<&str>) -> &mut Self {
+        self.exclude = pattern.map(Into::into);
+        self
+    }
+
+    /// Returns whether to show the entry with the given name.
+    fn is_shown(&self, name: &str, is_dir: bool) -> bool {
+        if !self.show_hidden && name.starts_with('.') {
+            return false;
+        }
+        if self
+            .exclude
+            .as_deref()
+            .is_some_and(|pattern| glob_match(pattern, name))
+        {
+            return false;
+        }
+        is_dir
+            || self
+                .include
+                .as_deref()
+                .map_or(true, |pattern| glob_match(pattern, name))
+    }
+}
+
+/// Returns whether the name matches the wildcard pattern with `|`-separated alternatives.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let name = name.chars().collect::<Vec<_>>();
+    pattern
+        .split('|')
+        .any(|alt| wildcard_match(&alt.chars().collect::<Vec<_>>(), &name))
+}
+
+/// Returns whether the name matches the wildcard pattern.
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Position in the pattern after the last `*`, and the position in the name matched by it.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p + 1, n));
+            p += 1;
+            continue;
+        }
+        if p < pattern.len() {
+            let (len, matched) = match_token(&pattern[p..], name[n]);
+            if matched {
+                p += len;
+                n += 1;
+                continue;
+            }
+        }
+        // Let the last `*` consume one more character.
+        match star {
+            Some((star_p, star_n)) => {
+                p = star_p;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns the length of the first token of the non-empty pattern and whether it matches the
+/// character.
+fn match_token(pattern: &[char], c: char) -> (usize, bool) {
+    match pattern[0] {
+        '?' => (1, true),
+        '\\' if pattern.len() > 1 => (2, pattern[1] == c),
+        '[' => match_class(pattern, c).unwrap_or((1, c == '[')),
+        token => (1, token == c),
+    }
+}
+
+/// Returns the length of the bracket expression at the start of the pattern and whether it
+/// matches the character, or `None` if the bracket is not closed.
+fn match_class(pattern: &[char], c: char) -> Option<(usize, bool)> {
+    let negated = matches!(pattern.get(1), Some('!') | Some('^'));
+    let mut i = if negated { 2 } else { 1 };
+    let first = i;
+    let mut matched = false;
+    while let Some(&start) = pattern.get(i) {
+        // `]` right after the opening bracket is a literal.
+        if start == ']' && i > first {
+            return Some((i + 1, matched != negated));
+        }
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                matched |= (start..=end).contains(&c);
+                i += 3;
+            }
+            _ => {
+                matched |= start == c;
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+/// Walker of directories.
+struct DirWalker<'a> {
+    /// Options.
+    opts: &'a DirTreeOptions,
+    /// Canonical paths of the directories being walked, if symbolic links are followed.
+    ancestors: Vec<PathBuf>,
+}
+
+impl DirWalker<'_> {
+    /// Returns the nodes of the entries in the directory at the given depth.
+    fn children(&mut self, dir: &Path, depth: usize) -> io::Result<Vec<TreeNode>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let file_type = entry.file_type()?;
+            let is_dir = file_type.is_dir()
+                || (self.opts.follow_symlinks && file_type.is_symlink() && entry.path().is_dir());
+            if !self.opts.is_shown(&name, is_dir) {
+                continue;
+            }
+            entries.push((
+                self.opts.dirs_first && !is_dir,
+                name,
+                entry,
+                file_type,
+                is_dir,
+            ));
+        }
+        entries.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        Ok(entries
+            .into_iter()
+            .map(|(_, name, entry, file_type, is_dir)| {
+                self.entry(name, &entry, file_type, is_dir, depth)
+            })
+            .collect())
+    }
+
+    /// Returns the node of the entry at the given depth.
+    fn entry(
+        &mut self,
+        mut name: String,
+        entry: &DirEntry,
+        file_type: FileType,
+        is_dir: bool,
+        depth: usize,
+    ) -> TreeNode {
+        let path = entry.path();
+        let target = if file_type.is_symlink() {
+            fs::read_link(&path).ok()
+        } else {
+            None
+        };
+        let kind = classify(entry, file_type, is_dir);
+        #[cfg(feature = "nerd-fonts")]
+        let icon = crate::icons::nerd_font_icon(&name, kind.as_ref());
+        if self.opts.classify {
+            if let Some(kind) = kind {
+                name.push_str(kind.suffix());
+            }
+        }
+        #[cfg(feature = "nerd-fonts")]
+        if self.opts.icons {
+            name = format!("{} {}", icon, name);
+        }
+        let mut node = TreeNode::new(name);
+        node.set_alias_target(target.map(|target| target.display().to_string()));
+        if is_dir && self.opts.max_depth.map_or(true, |max| depth < max) {
+            if let Err(e) = self.descend(&mut node, &path, depth) {
+                node.set_annotation(Some(format!("error: {}", e)));
+            }
+        }
+        node
+    }
+
+    /// Adds the entries in the directory to the node.
+    fn descend(&mut self, node: &mut TreeNode, dir: &Path, depth: usize) -> io::Result<()> {
+        if !self.opts.follow_symlinks {
+            *node.children_mut() = self.children(dir, depth + 1)?;
+            return Ok(());
+        }
+        let canonical = fs::canonicalize(dir)?;
+        if self.ancestors.contains(&canonical) {
+            node.set_annotation(Some("recursive, not followed"));
+            return Ok(());
+        }
+        self.ancestors.push(canonical);
+        let children = self.children(dir, depth + 1);
+        self.ancestors.pop();
+        *node.children_mut() = children?;
+        Ok(())
+    }
+}
+
+/// Returns the kind of the entry.
+fn classify(entry: &DirEntry, file_type: FileType, is_dir: bool) -> Option<NodeKind> {
+    if file_type.is_symlink() {
+        return Some(if is_dir {
+            NodeKind::Directory
+        } else {
+            NodeKind::Symlink
+        });
+    }
+    if is_dir {
+        return Some(NodeKind::Directory);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+        if file_type.is_fifo() {
+            return Some(NodeKind::Fifo);
+        }
+        if file_type.is_socket() {
+            return Some(NodeKind::Socket);
+        }
+        let executable = entry
+            .metadata()
+            .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0);
+        if file_type.is_file() && executable {
+            return Some(NodeKind::Executable);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = entry;
+    None
+}
+
+/// Creates a tree of the directory.
+///
+/// The root is labeled with the given path, and the other nodes are labeled with the names of
+/// the entries, sorted by the names.
+/// Symbolic links are aliases to their targets.
+/// Directories which cannot be read are annotated with the errors, but errors reading the root
+/// directory are returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs;
+///
+/// use plaintextree::{dir_tree, DirTreeOptions};
+///
+/// # let tmp = std::env::temp_dir().join(format!("plaintextree-doc-{}", std::process::id()));
+/// # let _ = fs::remove_dir_all(&tmp);
+/// let project = tmp.join("project");
+/// fs::create_dir_all(project.join("src/bin"))?;
+/// fs::create_dir_all(project.join(".git"))?;
+/// fs::write(project.join("Cargo.toml"), "")?;
+/// fs::write(project.join("src/lib.rs"), "")?;
+///
+/// let tree = dir_tree(&project, DirTreeOptions::new().dirs_first(true))?;
+///
+/// let expected = format!(
+///     "\
+/// {}
+/// |-- src
+/// |   |-- bin
+/// |   `-- lib.rs
+/// `-- Cargo.toml
+/// ",
+///     project.display()
+/// );
+/// assert_eq!(tree.to_string(), expected);
+/// # fs::remove_dir_all(&tmp)?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn dir_tree(path: impl AsRef<Path>, opts: &DirTreeOptions) -> io::Result<TreeNode> {
+    let path = path.as_ref();
+    let mut walker = DirWalker {
+        opts,
+        ancestors: Vec::new(),
+    };
+    if opts.follow_symlinks {
+        walker.ancestors.push(fs::canonicalize(path)?);
+    }
+    let mut label = path.display().to_string();
+    if opts.classify {
+        label.push_str(NodeKind::Directory.suffix());
+    }
+    #[cfg(feature = "nerd-fonts")]
+    if opts.icons {
+        label = format!(
+            "{} {}",
+            crate::icons::nerd_font_icon(&label, Some(&NodeKind::Directory)),
+            label
+        );
+    }
+    let mut root = TreeNode::new(label);
+    if opts.max_depth != Some(0) {
+        *root.children_mut() = walker.children(path, 1)?;
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary directory removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        /// Creates a new empty temporary directory.
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("plaintextree-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).expect("temporary directory should be created");
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn hidden_and_depth() -> io::Result<()> {
+        let tmp = TempDir::new("hidden-and-depth");
+        fs::create_dir_all(tmp.0.join("a/b/c"))?;
+        fs::create_dir_all(tmp.0.join(".cache"))?;
+        fs::write(tmp.0.join("a/.env"), "")?;
+        fs::write(tmp.0.join("z"), "")?;
+
+        let mut opts = DirTreeOptions::new();
+        opts.show_hidden(true).max_depth(Some(2)).classify(true);
+        let got = dir_tree(&tmp.0, &opts)?;
+        let expected = "|-- .cache/\n\
+                        |-- a/\n\
+                        |   |-- .env\n\
+                        |   `-- b/\n\
+                        `-- z\n";
+        let got = got.to_string();
+        let (_, body) = got.split_once('\n').expect("root line exists");
+        assert_eq!(body, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "nerd-fonts")]
+    #[test]
+    fn icons() -> io::Result<()> {
+        let tmp = TempDir::new("icons");
+        fs::create_dir_all(tmp.0.join("src"))?;
+        fs::write(tmp.0.join("src/main.rs"), "")?;
+        fs::write(tmp.0.join("Cargo.lock"), "")?;
+
+        let mut opts = DirTreeOptions::new();
+        opts.icons(true).classify(true);
+        let got = dir_tree(&tmp.0, &opts)?.to_string();
+        let (root, body) = got.split_once('\n').expect("root line exists");
+        assert!(root.starts_with("\u{F07B} "), "{:?}", root);
+        let expected = "|-- \u{F023} Cargo.lock\n\
+                        `-- \u{F07B} src/\n    \
+                        `-- \u{E7A8} main.rs\n";
+        assert_eq!(body, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn wildcards() {
+        let cases = [
+            ("*.rs", "main.rs", true),
+            ("*.rs", "main.rsx", false),
+            ("a*b*c", "abbbc", true),
+            ("a*b*c", "acb", false),
+            ("?", "\u{3042}", true),
+            ("??", "a", false),
+            ("[a-c]x", "bx", true),
+            ("[!a-c]x", "bx", false),
+            ("[^a-c]x", "dx", true),
+            ("[]]", "]", true),
+            ("[a-]", "-", true),
+            ("[ab", "[ab", true),
+            ("\\*", "*", true),
+            ("\\*", "a", false),
+            ("target|*.lock", "Cargo.lock", true),
+            ("", "", true),
+            ("**", "", true),
+        ];
+        for (pattern, name, expected) in cases {
+            assert_eq!(
+                glob_match(pattern, name),
+                expected,
+                "{:?} {:?}",
+                pattern,
+                name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks() -> io::Result<()> {
+        let tmp = TempDir::new("symlinks");
+        fs::create_dir_all(tmp.0.join("dir/sub"))?;
+        std::os::unix::fs::symlink("..", tmp.0.join("dir/sub/up"))?;
+        std::os::unix::fs::symlink("sub", tmp.0.join("dir/link"))?;
+
+        let got = dir_tree(tmp.0.join("dir"), &DirTreeOptions::new())?.to_string();
+        let (_, body) = got.split_once('\n').expect("root line exists");
+        let expected = "|-- link -> sub\n\
+                        `-- sub\n    \
+                        `-- up -> ..\n";
+        assert_eq!(body, expected);
+
+        let mut opts = DirTreeOptions::new();
+        opts.follow_symlinks(true);
+        let got = dir_tree(tmp.0.join("dir"), &opts)?.to_string();
+        let (_, body) = got.split_once('\n').expect("root line exists");
+        let expected = "|-- link -> sub\n\
+                        |   `-- up -> ..  recursive, not followed\n\
+                        `-- sub\n    \
+                        `-- up -> ..  recursive, not followed\n";
+        assert_eq!(body, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Nerd Font icons.
+
+use crate::config::NodeKind;
+
+/// Icon for directories (`nf-fa-folder`).
+const DIRECTORY: &str = "\u{F07B}";
+/// Icon for regular files without specific icons (`nf-fa-file`).
+const FILE: &str = "\u{F15B}";
+/// Icon for symbolic links (`nf-fa-link`).
+const SYMLINK: &str = "\u{F0C1}";
+/// Icon for executables and shell scripts (`nf-oct-terminal`).
+const TERMINAL: &str = "\u{F489}";
+/// Icon for named pipes (`nf-fa-exchange`).
+const FIFO: &str = "\u{F0EC}";
+/// Icon for sockets (`nf-fa-plug`).
+const SOCKET: &str = "\u{F1E6}";
+/// Icon for configuration files (`nf-seti-config`).
+const CONFIG: &str = "\u{E615}";
+/// Icon for images (`nf-fa-file_image_o`).
+const IMAGE: &str = "\u{F1C5}";
+/// Icon for archives (`nf-fa-file_archive_o`).
+const ARCHIVE: &str = "\u{F1C6}";
+/// Icon for lock files (`nf-fa-lock`).
+const LOCK: &str = "\u{F023}";
+
+/// Icons for file names.
+///
+/// File names are compared case-insensitively.
+const FILE_NAMES: &[(&str, &str)] = &[
+    (".gitignore", "\u{E702}"),
+    (".gitmodules", "\u{E702}"),
+    ("cargo.lock", LOCK),
+    ("dockerfile", "\u{E7B0}"),
+    ("package-lock.json", LOCK),
+];
+
+/// Icons for file extensions.
+///
+/// Extensions are compared case-insensitively.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("7z", ARCHIVE),
+    ("bash", TERMINAL),
+    ("c", "\u{E61E}"),
+    ("cpp", "\u{E61D}"),
+    ("css", "\u{E749}"),
+    ("gif", IMAGE),
+    ("go", "\u{E627}"),
+    ("gz", ARCHIVE),
+    ("h", "\u{E61E}"),
+    ("hpp", "\u{E61D}"),
+    ("html", "\u{E736}"),
+    ("ini", CONFIG),
+    ("java", "\u{E738}"),
+    ("jpeg", IMAGE),
+    ("jpg", IMAGE),
+    ("js", "\u{E74E}"),
+    ("json", "\u{E60B}"),
+    ("lock", LOCK),
+    ("md", "\u{E73E}"),
+    ("pdf", "\u{F1C1}"),
+    ("png", IMAGE),
+    ("py", "\u{E73C}"),
+    ("rs", "\u{E7A8}"),
+    ("sh", TERMINAL),
+    ("svg", IMAGE),
+    ("tar", ARCHIVE),
+    ("toml", CONFIG),
+    ("ts", "\u{E628}"),
+    ("txt", "\u{F15C}"),
+    ("xz", ARCHIVE),
+    ("yaml", CONFIG),
+    ("yml", CONFIG),
+    ("zip", ARCHIVE),
+    ("zsh", TERMINAL),
+];
+
+/// Returns the [Nerd Font] icon for the file with the given name and kind.
+///
+/// Directories, symbolic links, named pipes, and sockets have icons for the kinds.
+/// Icons for other files are chosen from the file names and extensions, and executables without
+/// specific icons have a terminal icon.
+///
+/// The icons are in the private use area, so they are shown correctly only with Nerd Fonts.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{nerd_font_icon, NodeKind};
+///
+/// assert_eq!(nerd_font_icon("src", Some(&NodeKind::Directory)), "\u{F07B}");
+/// assert_eq!(nerd_font_icon("main.rs", None), "\u{E7A8}");
+/// assert_eq!(nerd_font_icon("Cargo.lock", None), "\u{F023}");
+/// assert_eq!(nerd_font_icon("run", Some(&NodeKind::Executable)), "\u{F489}");
+/// ```
+///
+/// [Nerd Font]: https://www.nerdfonts.com/
+pub fn nerd_font_icon(name: &str, kind: Option<&NodeKind>) -> &'static str {
+    match kind {
+        Some(NodeKind::Directory) => return DIRECTORY,
+        Some(NodeKind::Symlink) => return SYMLINK,
+        Some(NodeKind::Fifo) => return FIFO,
+        Some(NodeKind::Socket) => return SOCKET,
+        _ => {}
+    }
+
+    let lookup = |table: &[(&str, &'static str)], key: &str| {
+        table
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|&(_, icon)| icon)
+    };
+    let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+    lookup(FILE_NAMES, name)
+        .or_else(|| extension.and_then(|extension| lookup(EXTENSIONS, extension)))
+        .unwrap_or(match kind {
+            Some(NodeKind::Executable) => TERMINAL,
+            _ => FILE,
+        })
+}
@@ -1,67 +1,117 @@
 //! Tree node writer.
 
 use std::{
+    borrow::Cow,
     fmt::{self, Write},
     mem,
 };
 
-use crate::config::{EdgeConfig, ItemStyle, PrefixPart, TreeConfig};
+#[cfg(feature = "ansi")]
+use crate::ansi::{SGR_DEFAULT_FG, SGR_DIM, SGR_NORMAL_INTENSITY};
+use crate::{
+    config::{emphasized_char, EdgeConfig, ItemStyle, PrefixPart, TreeConfig},
+    mirror::flip_char,
+    width::str_width_ambi,
+};
+
+/// First strong isolate (FSI), which starts a bidirectional isolate.
+pub(crate) const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+/// Pop directional isolate (PDI), which ends a bidirectional isolate.
+pub(crate) const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Writer of the tree output which records where the contents of lines start.
+pub(crate) trait LineWrite: fmt::Write {
+    /// Records that the content of the current line starts at the current position.
+    ///
+    /// This is called only for mirrored trees.
+    fn mark_content_start(&mut self) {}
+}
+
+impl LineWrite for String {}
+
+impl<W: LineWrite + ?Sized> LineWrite for &mut W {
+    fn mark_content_start(&mut self) {
+        (**self).mark_content_start()
+    }
+}
 
 /// A sink to write single item.
 pub(crate) struct ItemWriter<'a, W> {
     /// Writer.
     writer: &'a mut W,
     /// Writer options.
-    opts: TreeConfig,
+    opts: &'a TreeConfig,
     /// Item writer state.
     states: &'a mut [ItemState],
+    /// Index of the current output line.
+    line_index: &'a mut usize,
 }
 
-impl<'a, W: fmt::Write> ItemWriter<'a, W> {
+impl<'a, W: LineWrite> ItemWriter<'a, W> {
     /// Creates a new `ItemWriter`.
-    pub(crate) fn new(writer: &'a mut W, states: &'a mut [ItemState], opts: TreeConfig) -> Self {
+    pub(crate) fn new(
+        writer: &'a mut W,
+        states: &'a mut [ItemState],
+        opts: &'a TreeConfig,
+        line_index: &'a mut usize,
+    ) -> Self {
         Self {
             writer,
-            states,
             opts,
+            states,
+            line_index,
         }
     }
 
     /// Writes line prefixes and paddings if necessary.
     fn write_prefix_and_padding(&mut self, line_is_empty: bool) -> fmt::Result {
-        if self.states.is_empty() {
+        let Self {
+            writer,
+            states,
+            opts,
+            ..
+        } = self;
+        let writer: &mut W = writer;
+        let (last, ancestors) = match states.split_last_mut() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        // Continuation lines of flush-left items have no prefixes and paddings of the item.
+        let flush_left = last.style.is_flush_left() && !last.at_first_line;
+        let states = if flush_left {
+            if !line_is_empty {
+                last.edge_status = LineEdgeStatus::PaddingEmitted;
+            }
+            ancestors
+        } else {
+            &mut **states
+        };
+        if states.is_empty() {
             return Ok(());
         }
 
         // Delay the emission of the prefixes and paddings in some cases.
-        let emit_last_padding = self.opts.emit_trailing_whitespace() || !line_is_empty;
+        let emit_last_padding = opts.emit_trailing_whitespace() || !line_is_empty;
         let last_non_omissible_prefix_index = if emit_last_padding {
-            assert!(!self.states.is_empty(), "Decrement should never overflow");
-            Some(self.states.len() - 1)
+            assert!(!states.is_empty(), "Decrement should never overflow");
+            Some(states.len() - 1)
         } else {
-            self.states.iter().rposition(|state| {
+            states.iter().rposition(|state| {
                 !state
                     .edge()
-                    .is_prefix_whitespace(state.is_last_child(), state.at_first_line)
+                    .is_prefix_whitespace(state.draws_as_last_child(opts), state.at_first_line)
             })
         };
         if let Some(last_non_omissible_prefix_index) = last_non_omissible_prefix_index {
-            let Self {
-                writer,
-                states,
-                opts,
-                ..
-            } = self;
-            let writer: &mut W = *writer;
             states
                 .iter_mut()
                 .take(last_non_omissible_prefix_index)
                 .try_for_each(|state| {
                     if state.edge_status == LineEdgeStatus::LineStart {
-                        state.write_prefix(writer, opts.emit_trailing_whitespace())?;
+                        state.write_prefix(writer, opts)?;
                     }
                     if state.edge_status == LineEdgeStatus::PrefixEmitted {
-                        state.write_padding(writer)?;
+                        state.write_padding(writer, opts)?;
                     }
                     debug_assert_eq!(state.edge_status, LineEdgeStatus::PaddingEmitted);
                     Ok(())
@@ -69,10 +119,10 @@ impl<'a, W: fmt::Write> ItemWriter<'a, W> {
 
             let last_state = &mut states[last_non_omissible_prefix_index];
             if last_state.edge_status == LineEdgeStatus::LineStart {
-                last_state.write_prefix(writer, opts.emit_trailing_whitespace())?;
+                last_state.write_prefix(writer, opts)?;
             }
             if last_state.edge_status == LineEdgeStatus::PrefixEmitted && emit_last_padding {
-                last_state.write_padding(writer)?;
+                last_state.write_padding(writer, opts)?;
             }
         }
 
@@ -86,15 +136,73 @@ impl<'a, W: fmt::Write> ItemWriter<'a, W> {
             .for_each(|state| state.reset_line_state());
     }
 
+    /// Writes the content of a line, without newline.
+    fn write_content(&mut self, line: &str) -> fmt::Result {
+        let line = if self.opts.strips_carriage_returns() && line.contains('\r') {
+            Cow::Owned(line.replace('\r', ""))
+        } else {
+            Cow::Borrowed(line)
+        };
+        if self.opts.is_mirrored() && !line.is_empty() {
+            // Let the printer know where the line prefix ends.
+            self.writer.mark_content_start();
+        }
+        let isolate = self.opts.isolates_bidi() && !line.is_empty();
+        if isolate {
+            self.writer.write_char(FIRST_STRONG_ISOLATE)?;
+        }
+        self.write_styled_content(&line)?;
+        if isolate {
+            self.writer.write_char(POP_DIRECTIONAL_ISOLATE)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the content of a line with the label style.
+    fn write_styled_content(&mut self, line: &str) -> fmt::Result {
+        #[cfg(feature = "ansi")]
+        {
+            if let Some(state) = self.states.last() {
+                let dim =
+                    self.opts.dim_continuation_lines() && !state.at_first_line && !line.is_empty();
+                if dim {
+                    self.writer.write_str(SGR_DIM)?;
+                }
+                state.style.label_style().write_styled(self.writer, line)?;
+                if dim {
+                    self.writer.write_str(SGR_NORMAL_INTENSITY)?;
+                }
+                return Ok(());
+            }
+        }
+
+        self.writer.write_str(line)
+    }
+
+    /// Writes the carriage return held at the end of the last chunk as a part of the content.
+    ///
+    /// This should be called when no more chunks are written to the current line of the item.
+    pub(crate) fn flush_carriage_return(&mut self) -> fmt::Result {
+        let pending = self
+            .states
+            .last_mut()
+            .is_some_and(|state| mem::replace(&mut state.pending_carriage_return, false));
+        if pending {
+            self.write_lines("\r")?;
+        }
+        Ok(())
+    }
+
     /// Writes a newline character if necessary, and moves the cursor to the head of the next line.
     pub(crate) fn go_to_next_line(&mut self) -> fmt::Result {
+        self.flush_carriage_return()?;
         let last_state = self
             .states
             .last()
             .expect("Should never fail: `states` must not be empty");
         if !last_state.is_at_line_head() {
             self.opts
-                .writer(&mut self.writer, &mut self.states)
+                .writer(&mut self.writer, self.states, self.line_index)
                 .write_str("\n")?;
         }
         debug_assert!(self
@@ -105,25 +213,34 @@ impl<'a, W: fmt::Write> ItemWriter<'a, W> {
 
         Ok(())
     }
-}
 
-impl<'a, W: fmt::Write> fmt::Write for ItemWriter<'a, W> {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
+    /// Writes the lines of the string.
+    fn write_lines(&mut self, s: &str) -> fmt::Result {
         for (line, at_last_line) in lines_with_last_line_flag(s) {
             // Delay the emission of the prefix (and padding) until the line content is given.
             if at_last_line && line.is_empty() {
                 break;
             }
 
+            // Write the gutter if nothing is written on the line yet.
+            if let (Some(gutter), Some(first)) = (self.opts.line_gutter(), self.states.first()) {
+                let reordered = self.opts.is_mirrored() || self.opts.is_bottom_up();
+                if first.edge_status == LineEdgeStatus::LineStart && !reordered {
+                    let depth = self.states.len() - 1;
+                    self.writer.write_str(&gutter(*self.line_index, depth))?;
+                }
+            }
+
             // Write line prefixes and paddings if necessary.
             self.write_prefix_and_padding(line.is_empty())?;
 
             // Write the line content.
-            self.writer.write_str(line)?;
+            self.write_content(line)?;
 
             // Write the newline if there are next lines to be written.
             if !at_last_line {
-                self.writer.write_char('\n')?;
+                self.writer.write_str(self.opts.newline())?;
+                *self.line_index += 1;
                 self.reset_line_state();
             }
         }
@@ -132,6 +249,32 @@ impl<'a, W: fmt::Write> fmt::Write for ItemWriter<'a, W> {
     }
 }
 
+impl<'a, W: LineWrite> fmt::Write for ItemWriter<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        let state = match self.states.last_mut() {
+            Some(state) => state,
+            None => return self.write_lines(s),
+        };
+        // CRLF can be split into the different chunks, so the carriage return at the end of a
+        // chunk is held until the next chunk is given.
+        let pending = mem::replace(&mut state.pending_carriage_return, false);
+        let s = match s.strip_suffix('\r') {
+            Some(s) => {
+                state.pending_carriage_return = true;
+                s
+            }
+            None => s,
+        };
+        if pending && !s.starts_with('\n') {
+            self.write_lines("\r")?;
+        }
+        self.write_lines(s)
+    }
+}
+
 /// Item writer state for single nest level.
 #[derive(Debug, Clone)]
 pub(crate) struct ItemState {
@@ -141,30 +284,84 @@ pub(crate) struct ItemState {
     at_first_line: bool,
     /// Edge emission status.
     edge_status: LineEdgeStatus,
+    /// Whether the item has any child items.
+    has_children: bool,
+    /// Whether the last chunk of the content ended with a carriage return not written yet.
+    pending_carriage_return: bool,
 }
 
 impl ItemState {
+    /// Returns whether the item has any child items.
+    pub(crate) fn has_children(&self) -> bool {
+        self.has_children
+    }
+
+    /// Marks the item as having child items.
+    pub(crate) fn set_has_children(&mut self) {
+        self.has_children = true;
+    }
+
     /// Returns whether the cursor is at the beginning of the line.
     pub(crate) fn is_at_line_head(&self) -> bool {
         self.edge_status == LineEdgeStatus::LineStart
     }
 
     /// Returns whether the item is the last child.
-    fn is_last_child(&self) -> bool {
+    pub(crate) fn is_last_child(&self) -> bool {
         self.style.is_last_child()
     }
 
+    /// Returns whether the edge for the current line is drawn as the last child.
+    ///
+    /// In bottom-up trees, continuation lines of the last child are followed by the siblings
+    /// and the parent, so they are drawn as non-last children.
+    fn draws_as_last_child(&self, opts: &TreeConfig) -> bool {
+        let own_continuation = !self.at_first_line && !self.has_children;
+        self.is_last_child() && !(opts.is_bottom_up() && own_continuation)
+    }
+
+    /// Returns the display width of the prefix and padding for the current line.
+    pub(crate) fn prefix_width(&self, opts: &TreeConfig) -> usize {
+        self.edge().prefix_and_padding_width(
+            self.draws_as_last_child(opts),
+            self.at_first_line,
+            &opts.width_oracle(),
+        )
+    }
+
+    /// Returns the display width of the prefix and padding put before lines of descendants.
+    pub(crate) fn guide_width(&self, opts: &TreeConfig) -> usize {
+        // Hanging indents align the lines to the text of the first line.
+        self.edge().prefix_and_padding_width(
+            self.is_last_child(),
+            opts.aligns_continuation_lines(),
+            &opts.width_oracle(),
+        )
+    }
+
+    /// Returns the display width of the prefix and padding put before continuation lines of
+    /// the item.
+    pub(crate) fn continuation_width(&self, opts: &TreeConfig) -> usize {
+        if self.style.is_flush_left() {
+            0
+        } else if opts.is_bottom_up() {
+            self.edge().prefix_and_padding_width(
+                false,
+                opts.aligns_continuation_lines(),
+                &opts.width_oracle(),
+            )
+        } else {
+            self.guide_width(opts)
+        }
+    }
+
     /// Returns the edge config.
-    fn edge(&self) -> &EdgeConfig {
+    pub(crate) fn edge(&self) -> &EdgeConfig {
         self.style.edge()
     }
 
     /// Writes a line prefix (and padding if possible) for the current line.
-    fn write_prefix<W: fmt::Write>(
-        &mut self,
-        writer: &mut W,
-        emit_trailing_whitespace: bool,
-    ) -> fmt::Result {
+    fn write_prefix<W: fmt::Write>(&mut self, writer: &mut W, opts: &TreeConfig) -> fmt::Result {
         assert_eq!(
             self.edge_status,
             LineEdgeStatus::LineStart,
@@ -172,23 +369,18 @@ impl ItemState {
         );
         self.edge_status = LineEdgeStatus::PrefixEmitted;
 
-        self.edge().write_edge(
-            writer,
-            self.is_last_child(),
-            self.at_first_line,
-            PrefixPart::Prefix,
-        )?;
+        self.write_edge(writer, opts, PrefixPart::Prefix)?;
 
-        if emit_trailing_whitespace {
+        if opts.emit_trailing_whitespace() {
             // Padding is always necessary.
-            self.write_padding(writer)?;
+            self.write_padding(writer, opts)?;
         }
 
         Ok(())
     }
 
     /// Writes a padding after the line prefix.
-    fn write_padding<W: fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+    fn write_padding<W: fmt::Write>(&mut self, writer: &mut W, opts: &TreeConfig) -> fmt::Result {
         assert_eq!(
             self.edge_status,
             LineEdgeStatus::PrefixEmitted,
@@ -196,12 +388,72 @@ impl ItemState {
         );
         self.edge_status = LineEdgeStatus::PaddingEmitted;
 
-        self.edge().write_edge(
-            writer,
-            self.is_last_child(),
-            self.at_first_line,
-            PrefixPart::Padding,
-        )
+        if opts.aligns_continuation_lines() && !self.at_first_line {
+            // Align the line to the text of the first line.
+            let last_child = self.draws_as_last_child(opts);
+            let mut first_line = String::new();
+            let mut prefix = String::new();
+            self.edge()
+                .write_edge(&mut first_line, last_child, true, PrefixPart::Prefix)?;
+            self.edge()
+                .write_edge(&mut first_line, last_child, true, PrefixPart::Padding)?;
+            self.edge()
+                .write_edge(&mut prefix, last_child, false, PrefixPart::Prefix)?;
+            let ambiguous = opts.ambiguous_width();
+            let width = str_width_ambi(&first_line, ambiguous)
+                .saturating_sub(str_width_ambi(&prefix, ambiguous));
+            return (0..width).try_for_each(|_| writer.write_char(' '));
+        }
+
+        self.write_edge(writer, opts, PrefixPart::Padding)
+    }
+
+    /// Writes the given part of the edge for the current line.
+    fn write_edge<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        opts: &TreeConfig,
+        fragment: PrefixPart,
+    ) -> fmt::Result {
+        let first_line_prefix = self.at_first_line && fragment == PrefixPart::Prefix;
+        let emphasize = self.style.is_emphasized() && first_line_prefix;
+        let flip = opts.is_bottom_up() && first_line_prefix;
+        #[cfg(feature = "ansi")]
+        let colored = self.style.edge_color().is_some();
+        #[cfg(not(feature = "ansi"))]
+        let colored = false;
+        let last_child = self.draws_as_last_child(opts);
+        if !emphasize && !flip && !colored {
+            return self
+                .edge()
+                .write_edge(writer, last_child, self.at_first_line, fragment);
+        }
+
+        let mut buf = String::new();
+        self.edge()
+            .write_edge(&mut buf, last_child, self.at_first_line, fragment)?;
+        if emphasize {
+            buf = buf.chars().map(emphasized_char).collect();
+        }
+        if flip {
+            buf = buf.chars().map(flip_char).collect();
+        }
+        #[cfg(feature = "ansi")]
+        {
+            if let Some(color) = self.style.edge_color() {
+                // Whitespaces need not be colored.
+                let body = buf.trim_end();
+                if body.trim_start().is_empty() {
+                    return writer.write_str(&buf);
+                }
+                color.write_fg(writer)?;
+                writer.write_str(body)?;
+                writer.write_str(SGR_DEFAULT_FG)?;
+                return writer.write_str(&buf[body.len()..]);
+            }
+        }
+
+        writer.write_str(&buf)
     }
 
     /// Resets the writer status for the next new line.
@@ -217,6 +469,8 @@ impl From<ItemStyle> for ItemState {
             style,
             at_first_line: true,
             edge_status: LineEdgeStatus::LineStart,
+            has_children: false,
+            pending_carriage_return: false,
         }
     }
 }
@@ -233,7 +487,10 @@ enum LineEdgeStatus {
 }
 
 /// Returns an iterator of lines with "last line" flag.
+///
+/// Both LF and CRLF are treated as line breaks.
 fn lines_with_last_line_flag(s: &str) -> impl Iterator<Item = (&str, bool)> {
+    // `<str>::lines()` strips "\r" before "\n" from the lines.
     let mut lines_raw = s.lines();
     let mut current = lines_raw.next();
     // `<str>::lines()` treats the trailing "\n" as a line ending, but does not consider it as a
@@ -275,7 +532,8 @@ mod tests {
         let _writer = ItemWriter::new(
             &mut buf,
             &mut [ItemStyle::non_last(EdgeConfig::Ascii).into()],
-            TreeConfig::new(),
+            &TreeConfig::new(),
+            &mut 0,
         );
         assert!(
             buf.is_empty(),
@@ -300,17 +558,20 @@ mod tests {
     fn emit_test_tree(edge: EdgeConfig, opts: TreeConfig) -> Result<String, fmt::Error> {
         let mut buf = String::new();
         let mut states = Vec::new();
+        let mut line_index = 0;
         buf.write_str(".\n")?;
 
         {
             states.push(ItemStyle::non_last(edge.clone()).into());
-            opts.writer(&mut buf, &mut states).write_str("foo\n")?;
+            opts.writer(&mut buf, &mut states, &mut line_index)
+                .write_str("foo\n")?;
             {
                 states.push(ItemStyle::non_last(edge.clone()).into());
-                opts.writer(&mut buf, &mut states).write_str("bar\n")?;
+                opts.writer(&mut buf, &mut states, &mut line_index)
+                    .write_str("bar\n")?;
                 {
                     states.push(ItemStyle::last(edge.clone()).into());
-                    opts.writer(&mut buf, &mut states)
+                    opts.writer(&mut buf, &mut states, &mut line_index)
                         .write_str("baz\n\nbaz2\n")?;
                     states.pop();
                 }
@@ -318,10 +579,12 @@ mod tests {
             }
             {
                 states.push(ItemStyle::last(edge.clone()).into());
-                opts.writer(&mut buf, &mut states).write_str("qux\n")?;
+                opts.writer(&mut buf, &mut states, &mut line_index)
+                    .write_str("qux\n")?;
                 {
                     states.push(ItemStyle::last(edge.clone()).into());
-                    opts.writer(&mut buf, &mut states).write_str("quux\n")?;
+                    opts.writer(&mut buf, &mut states, &mut line_index)
+                        .write_str("quux\n")?;
                     states.pop();
                 }
                 states.pop();
@@ -330,12 +593,14 @@ mod tests {
         }
         {
             states.push(ItemStyle::non_last(edge.clone()).into());
-            opts.writer(&mut buf, &mut states).write_str("corge\n")?;
+            opts.writer(&mut buf, &mut states, &mut line_index)
+                .write_str("corge\n")?;
             states.pop();
         }
         {
             states.push(ItemStyle::last(edge.clone()).into());
-            opts.writer(&mut buf, &mut states).write_str("grault\n")?;
+            opts.writer(&mut buf, &mut states, &mut line_index)
+                .write_str("grault\n")?;
             states.pop();
         }
 
@@ -400,7 +665,9 @@ mod tests {
     fn non_last_item_single_line() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::non_last(EdgeConfig::Ascii).into()];
-        let mut writer = ItemWriter::new(&mut buf, states, TreeConfig::new());
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = ItemWriter::new(&mut buf, states, &opts, &mut line_index);
         writer.write_str("foo")?;
 
         assert_eq!(buf, "|-- foo");
@@ -411,7 +678,9 @@ mod tests {
     fn last_item_single_line() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::last(EdgeConfig::Ascii).into()];
-        let mut writer = ItemWriter::new(&mut buf, states, TreeConfig::new());
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = ItemWriter::new(&mut buf, states, &opts, &mut line_index);
         writer.write_str("foo")?;
 
         assert_eq!(buf, "`-- foo");
@@ -422,7 +691,9 @@ mod tests {
     fn non_last_item_multi_line() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::non_last(EdgeConfig::Ascii).into()];
-        let mut writer = ItemWriter::new(&mut buf, states, TreeConfig::new());
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = ItemWriter::new(&mut buf, states, &opts, &mut line_index);
         writer.write_str("foo\n\nbar")?;
 
         assert_eq!(buf, "|-- foo\n|\n|   bar");
@@ -433,7 +704,9 @@ mod tests {
     fn last_item_multi_line() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::last(EdgeConfig::Ascii).into()];
-        let mut writer = ItemWriter::new(&mut buf, states, TreeConfig::new());
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = ItemWriter::new(&mut buf, states, &opts, &mut line_index);
         writer.write_str("foo\n\nbar")?;
 
         assert_eq!(buf, "`-- foo\n\n    bar");
@@ -444,11 +717,11 @@ mod tests {
     fn non_last_item_multi_line_with_trailing_spaces() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::non_last(EdgeConfig::Ascii).into()];
-        let mut writer = {
-            let mut opts = TreeConfigBuilder::new();
-            opts.emit_trailing_whitespace(true);
-            opts.build().writer(&mut buf, states)
-        };
+        let opts = TreeConfigBuilder::new()
+            .emit_trailing_whitespace(true)
+            .build();
+        let mut line_index = 0;
+        let mut writer = opts.writer(&mut buf, states, &mut line_index);
         writer.write_str("foo\n\nbar")?;
 
         assert_eq!(buf, "|-- foo\n|   \n|   bar");
@@ -459,14 +732,60 @@ mod tests {
     fn last_item_multi_line_with_trailing_spaces() -> fmt::Result {
         let mut buf = String::new();
         let states = &mut [ItemStyle::last(EdgeConfig::Ascii).into()];
-        let mut writer = {
-            let mut opts = TreeConfigBuilder::new();
-            opts.emit_trailing_whitespace(true);
-            opts.build().writer(&mut buf, states)
-        };
+        let opts = TreeConfigBuilder::new()
+            .emit_trailing_whitespace(true)
+            .build();
+        let mut line_index = 0;
+        let mut writer = opts.writer(&mut buf, states, &mut line_index);
         writer.write_str("foo\n\nbar")?;
 
         assert_eq!(buf, "`-- foo\n    \n    bar");
         Ok(())
     }
+
+    #[test]
+    fn crlf_line_breaks() -> fmt::Result {
+        let mut buf = String::new();
+        let states = &mut [ItemStyle::non_last(EdgeConfig::Ascii).into()];
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = ItemWriter::new(&mut buf, states, &opts, &mut line_index);
+        writer.write_str("foo\r\n\r\nbar\r\n")?;
+
+        assert_eq!(buf, "|-- foo\n|\n|   bar\n");
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_split_across_chunks() -> fmt::Result {
+        let mut buf = String::new();
+        let states = &mut [ItemStyle::last(EdgeConfig::Ascii).into()];
+        let opts = TreeConfig::new();
+        let mut line_index = 0;
+        let mut writer = opts.writer(&mut buf, states, &mut line_index);
+        writer.write_str("a\r")?;
+        writer.write_str("")?;
+        writer.write_str("\nb\r")?;
+        writer.write_str("c\r")?;
+        writer.flush_carriage_return()?;
+
+        assert_eq!(buf, "`-- a\n    b\rc\r");
+        Ok(())
+    }
+
+    #[test]
+    fn strip_carriage_returns() -> fmt::Result {
+        let mut buf = String::new();
+        let states = &mut [ItemStyle::last(EdgeConfig::Ascii).into()];
+        let opts = TreeConfigBuilder::new()
+            .strip_carriage_returns(true)
+            .build();
+        let mut line_index = 0;
+        let mut writer = opts.writer(&mut buf, states, &mut line_index);
+        writer.write_str("foo\rbar\r")?;
+        writer.write_str("\nbaz")?;
+
+        assert_eq!(buf, "`-- foobar\n    baz");
+        Ok(())
+    }
 }
@@ -0,0 +1,170 @@
+//! Trees of JSON values.
+
+use serde_json::Value;
+
+use crate::tree_node::TreeNode;
+
+/// Options for rendering JSON values as trees.
+#[derive(Debug, Clone)]
+pub struct JsonTreeOptions {
+    /// Label of the root node.
+    root_label: String,
+    /// Whether to sort the keys of objects.
+    sort_keys: bool,
+    /// Maximum number of characters of strings to show.
+    max_string_chars: Option<usize>,
+}
+
+impl Default for JsonTreeOptions {
+    fn default() -> Self {
+        Self {
+            root_label: ".".to_owned(),
+            sort_keys: false,
+            max_string_chars: None,
+        }
+    }
+}
+
+impl JsonTreeOptions {
+    /// Creates a new default `JsonTreeOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the label of the root node.
+    ///
+    /// Default is `"."`.
+    pub fn root_label(&mut self, label: impl Into<String>) -> &mut Self {
+        self.root_label = label.into();
+        self
+    }
+
+    /// Sets whether to sort the keys of objects.
+    ///
+    /// Otherwise the keys are in the order of the object, which is the insertion order only if
+    /// the `preserve_order` feature of `serde_json` is enabled.
+    ///
+    /// Default is `false`.
+    pub fn sort_keys(&mut self, v: bool) -> &mut Self {
+        self.sort_keys = v;
+        self
+    }
+
+    /// Sets the maximum number of characters of strings to show.
+    ///
+    /// Longer strings are elided with `…` (U+2026 HORIZONTAL ELLIPSIS) at the end.
+    ///
+    /// Default is `None`, that is, strings are shown entirely.
+    pub fn max_string_chars(&mut self, max: Option<usize>) -> &mut Self {
+        self.max_string_chars = max;
+        self
+    }
+
+    /// Returns the JSON representation of the scalar value.
+    fn scalar(&self, value: &Value) -> String {
+        match (value, self.max_string_chars) {
+            (Value::String(s), Some(max)) if s.chars().nth(max).is_some() => {
+                let elided = s.chars().take(max).collect::<String>();
+                let mut quoted = Value::String(elided).to_string();
+                quoted.insert(quoted.len() - 1, '\u{2026}');
+                quoted
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Returns the tree node for the value with the given name.
+    fn node(&self, name: String, value: &Value) -> TreeNode {
+        let children = match value {
+            Value::Array(items) if !items.is_empty() => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| self.node(format!("[{}]", i), item))
+                .collect(),
+            Value::Object(entries) if !entries.is_empty() => {
+                let mut entries = entries.iter().collect::<Vec<_>>();
+                if self.sort_keys {
+                    entries.sort_by_key(|(key, _)| *key);
+                }
+                entries
+                    .into_iter()
+                    .map(|(key, item)| self.node(Value::String(key.clone()).to_string(), item))
+                    .collect()
+            }
+            _ => return TreeNode::new(format!("{}: {}", name, self.scalar(value))),
+        };
+        let mut node = TreeNode::new(name);
+        *node.children_mut() = children;
+        node
+    }
+}
+
+/// Creates a tree of the JSON value.
+///
+/// Objects and arrays are branches, and scalars are leaves.
+/// Each node is labeled with the quoted key or the `[index]` of the value, and leaves also
+/// have the JSON representations of the values after `: `.
+/// Empty objects and arrays are leaves with `{}` and `[]`.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{json_value_tree, JsonTreeOptions, RenderConfig};
+///
+/// let value = serde_json::json!({
+///     "name": "plaintextree",
+///     "keywords": ["tree", "text"],
+///     "description": "Library to emit trees in plain text",
+///     "metadata": {},
+/// });
+/// let tree = json_value_tree(
+///     &value,
+///     JsonTreeOptions::new()
+///         .root_label("Cargo.json")
+///         .sort_keys(true)
+///         .max_string_chars(Some(12)),
+/// );
+/// let got = tree.render(String::new(), &RenderConfig::new())?;
+///
+/// let expected = "\
+/// Cargo.json
+/// |-- \"description\": \"Library to e\u{2026}\"
+/// |-- \"keywords\"
+/// |   |-- [0]: \"tree\"
+/// |   `-- [1]: \"text\"
+/// |-- \"metadata\": {}
+/// `-- \"name\": \"plaintextree\"
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+pub fn json_value_tree(value: &Value, opts: &JsonTreeOptions) -> TreeNode {
+    opts.node(opts.root_label.clone(), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn scalar_root() {
+        let tree = json_value_tree(&json!(42), &JsonTreeOptions::new());
+        assert_eq!(tree.to_string(), ".: 42\n");
+    }
+
+    #[test]
+    fn elided_strings() {
+        let mut opts = JsonTreeOptions::new();
+        opts.max_string_chars(Some(3));
+        let value = json!(["abc", "abcd", "a\"\u{3042}\u{3044}", null]);
+        let got = json_value_tree(&value, &opts).to_string();
+        let expected = ".\n\
+                        |-- [0]: \"abc\"\n\
+                        |-- [1]: \"abc\u{2026}\"\n\
+                        |-- [2]: \"a\\\"\u{3042}\u{2026}\"\n\
+                        `-- [3]: null\n";
+        assert_eq!(got, expected);
+    }
+}
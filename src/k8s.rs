@@ -0,0 +1,289 @@
+//! Kubernetes-style resource hierarchy.
+
+use std::{fmt, time::Duration};
+
+use crate::tree_node::TreeNode;
+
+/// Phase of a pod.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PodPhase {
+    /// Pending.
+    Pending,
+    /// Running.
+    Running,
+    /// Succeeded.
+    Succeeded,
+    /// Failed.
+    Failed,
+    /// Unknown.
+    Unknown,
+}
+
+impl fmt::Display for PodPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "Pending",
+            Self::Running => "Running",
+            Self::Succeeded => "Succeeded",
+            Self::Failed => "Failed",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
+
+/// A container in a pod.
+#[derive(Debug, Clone)]
+pub struct K8sContainer {
+    /// Name.
+    name: String,
+    /// Whether the container is ready.
+    ready: bool,
+    /// Restart count.
+    restarts: u32,
+}
+
+impl K8sContainer {
+    /// Creates a new `K8sContainer`.
+    pub fn new(name: impl Into<String>, ready: bool, restarts: u32) -> Self {
+        Self {
+            name: name.into(),
+            ready,
+            restarts,
+        }
+    }
+
+    /// Returns the tree node for the container.
+    fn to_tree_node(&self) -> TreeNode {
+        let mut label = format!("container/{}  [", self.name);
+        label.push_str(if self.ready { "ready" } else { "not ready" });
+        match self.restarts {
+            0 => {}
+            1 => label.push_str(", 1 restart"),
+            n => label.push_str(&format!(", {} restarts", n)),
+        }
+        label.push(']');
+        TreeNode::new(label)
+    }
+}
+
+/// A pod.
+#[derive(Debug, Clone)]
+pub struct K8sPod {
+    /// Name.
+    name: String,
+    /// Phase.
+    phase: PodPhase,
+    /// Age.
+    age: Duration,
+    /// Containers.
+    containers: Vec<K8sContainer>,
+}
+
+impl K8sPod {
+    /// Creates a new `K8sPod` without containers.
+    pub fn new(name: impl Into<String>, phase: PodPhase, age: Duration) -> Self {
+        Self {
+            name: name.into(),
+            phase,
+            age,
+            containers: Vec::new(),
+        }
+    }
+
+    /// Adds a container.
+    pub fn push_container(&mut self, container: K8sContainer) -> &mut Self {
+        self.containers.push(container);
+        self
+    }
+
+    /// Returns whether the pod is running and all of its containers are ready.
+    fn is_ready(&self) -> bool {
+        self.phase == PodPhase::Running && self.containers.iter().all(|c| c.ready)
+    }
+
+    /// Returns the tree node for the pod.
+    fn to_tree_node(&self) -> TreeNode {
+        let mut node = TreeNode::new(format!(
+            "pod/{}  [{}]  {}",
+            self.name,
+            self.phase,
+            HumanDuration(self.age)
+        ));
+        for container in &self.containers {
+            node.push_child(container.to_tree_node());
+        }
+        node
+    }
+}
+
+/// A workload (deployment, stateful set, compose service, etc.).
+#[derive(Debug, Clone)]
+pub struct K8sWorkload {
+    /// Kind (such as `deployment`).
+    kind: String,
+    /// Name.
+    name: String,
+    /// Pods.
+    pods: Vec<K8sPod>,
+}
+
+impl K8sWorkload {
+    /// Creates a new `K8sWorkload` without pods.
+    ///
+    /// `kind` is used as the resource type part of the label, such as `deployment` or `service`.
+    pub fn new(kind: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            name: name.into(),
+            pods: Vec::new(),
+        }
+    }
+
+    /// Adds a pod.
+    pub fn push_pod(&mut self, pod: K8sPod) -> &mut Self {
+        self.pods.push(pod);
+        self
+    }
+
+    /// Returns the tree node for the workload.
+    fn to_tree_node(&self) -> TreeNode {
+        let ready = self.pods.iter().filter(|pod| pod.is_ready()).count();
+        let mut node = TreeNode::new(format!(
+            "{}/{}  {}/{} ready",
+            self.kind,
+            self.name,
+            ready,
+            self.pods.len()
+        ));
+        for pod in &self.pods {
+            node.push_child(pod.to_tree_node());
+        }
+        node
+    }
+}
+
+/// A namespace (or a compose project).
+#[derive(Debug, Clone)]
+pub struct K8sNamespace {
+    /// Name.
+    name: String,
+    /// Workloads.
+    workloads: Vec<K8sWorkload>,
+}
+
+impl K8sNamespace {
+    /// Creates a new `K8sNamespace` without workloads.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            workloads: Vec::new(),
+        }
+    }
+
+    /// Adds a workload.
+    pub fn push_workload(&mut self, workload: K8sWorkload) -> &mut Self {
+        self.workloads.push(workload);
+        self
+    }
+
+    /// Returns the tree node for the namespace.
+    fn to_tree_node(&self) -> TreeNode {
+        let mut node = TreeNode::new(format!("namespace/{}", self.name));
+        for workload in &self.workloads {
+            node.push_child(workload.to_tree_node());
+        }
+        node
+    }
+}
+
+/// Creates a tree of namespaces, workloads, pods, and containers.
+///
+/// Pods are annotated with their phase and age, and containers are annotated with their
+/// readiness and restart counts.
+/// No API calls are made; the caller provides all the resources.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use plaintextree::{
+///     k8s_resource_tree, K8sContainer, K8sNamespace, K8sPod, K8sWorkload, PodPhase,
+///     RenderConfig,
+/// };
+///
+/// let mut pod = K8sPod::new("web-7d9c-x2x8q", PodPhase::Running, Duration::from_secs(3 * 86400));
+/// pod.push_container(K8sContainer::new("nginx", true, 0))
+///     .push_container(K8sContainer::new("sidecar", false, 2));
+/// let mut web = K8sWorkload::new("deployment", "web");
+/// web.push_pod(pod);
+/// let mut ns = K8sNamespace::new("default");
+/// ns.push_workload(web);
+///
+/// let tree = k8s_resource_tree("cluster", &[ns]);
+/// let got = tree.render(String::new(), &RenderConfig::new())?;
+///
+/// let expected = "\
+/// cluster
+/// `-- namespace/default
+///     `-- deployment/web  0/1 ready
+///         `-- pod/web-7d9c-x2x8q  [Running]  3d
+///             |-- container/nginx  [ready]
+///             `-- container/sidecar  [not ready, 2 restarts]
+/// ";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+pub fn k8s_resource_tree(root_label: impl Into<String>, namespaces: &[K8sNamespace]) -> TreeNode {
+    let mut root = TreeNode::new(root_label);
+    for namespace in namespaces {
+        root.push_child(namespace.to_tree_node());
+    }
+    root
+}
+
+/// Duration formatted in the `kubectl` style (such as `45s`, `12m`, `5h`, and `3d`).
+struct HumanDuration(Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        match secs {
+            0..=119 => write!(f, "{}s", secs),
+            120..=10_799 => write!(f, "{}m", secs / 60),
+            10_800..=172_799 => write!(f, "{}h", secs / 3600),
+            _ => write!(f, "{}d", secs / 86400),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_duration() {
+        let format = |secs| HumanDuration(Duration::from_secs(secs)).to_string();
+        assert_eq!(format(0), "0s");
+        assert_eq!(format(119), "119s");
+        assert_eq!(format(120), "2m");
+        assert_eq!(format(3 * 3600 - 1), "179m");
+        assert_eq!(format(3 * 3600), "3h");
+        assert_eq!(format(48 * 3600), "2d");
+    }
+
+    #[test]
+    fn workload_readiness() {
+        let mut ready = K8sPod::new("a", PodPhase::Running, Duration::from_secs(5));
+        ready.push_container(K8sContainer::new("c", true, 1));
+        let pending = K8sPod::new("b", PodPhase::Pending, Duration::from_secs(5));
+        let mut workload = K8sWorkload::new("statefulset", "db");
+        workload.push_pod(ready).push_pod(pending);
+
+        let got = workload.to_tree_node().to_string();
+        let expected = "statefulset/db  1/2 ready\n\
+                        |-- pod/a  [Running]  5s\n\
+                        |   `-- container/c  [ready, 1 restart]\n\
+                        `-- pod/b  [Pending]  5s\n";
+        assert_eq!(got, expected);
+    }
+}
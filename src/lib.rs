@@ -97,10 +97,109 @@
 #![warn(clippy::missing_docs_in_private_items)]
 
 pub use self::{
-    config::{EdgeConfig, ItemStyle, TreeConfig, TreeConfigBuilder},
-    tree_printer::{Error, Result, TreePrinter},
+    bench::{Bench, BenchComparison, BenchGroup},
+    canvas::{Canvas, VerticalAlign},
+    capture::{capture_tree, CaptureFrame},
+    config::{
+        ColumnAlign, CustomEdgeConfig, CustomEdgeConfigBuilder, EdgeConfig, EdgeWidthError,
+        FoldMarkers, ItemStyle, LabelOverflow, MetadataColumn, NodeKind, RenderConfig,
+        RenderConfigBuilder, TraversalOrder, TreeConfig, TreeConfigBuilder, TreeLayout,
+    },
+    error_chain::{error_chain_tree, ErrorChainOptions},
+    export::{
+        asciidoc::AsciiDocRenderer,
+        html::HtmlRenderer,
+        latex::{LatexRenderer, LatexStyle},
+        markdown::MarkdownRenderer,
+        org::OrgRenderer,
+        plantuml::PlantUmlRenderer,
+        rst::RstRenderer,
+    },
+    k8s::{k8s_resource_tree, K8sContainer, K8sNamespace, K8sPod, K8sWorkload, PodPhase},
+    parser::{
+        convert, diff_texts, parse_indented, IndentUnit, ParseError, ParseOptions, TreeEvent,
+        TreeParser,
+    },
+    theme::{EdgeCharset, Theme, ThemeError},
+    tree_node::{Highlight, MergeConflict, RenderedLines, RenderedPages, TreeBuilder, TreeNode},
+    tree_printer::{Error, IoTreePrinter, Result, TreePrinter},
+    width::{AmbiWidth, CharCountWidth, Width},
 };
 
+#[cfg(feature = "ansi")]
+pub use self::ansi::{ansi_to_html, Color, ColorLevel, ColorMode, TextStyle};
+#[cfg(feature = "tokio")]
+pub use self::async_printer::AsyncTreePrinter;
+#[cfg(feature = "futures-io")]
+pub use self::async_printer::FuturesTreePrinter;
+#[cfg(feature = "tracing-error")]
+pub use self::capture::span_trace_tree;
+#[cfg(feature = "cargo-metadata")]
+pub use self::cargo_tree::{cargo_dependency_tree, CargoTreeOptions};
+#[cfg(feature = "anyhow")]
+pub use self::error_chain::anyhow_error_tree;
+#[cfg(feature = "eyre")]
+pub use self::error_chain::eyre_report_tree;
+#[cfg(feature = "svg")]
+pub use self::export::svg::SvgRenderer;
+#[cfg(feature = "fs")]
+pub use self::fs::{dir_tree, DirTreeOptions};
+#[cfg(feature = "nerd-fonts")]
+pub use self::icons::nerd_font_icon;
+#[cfg(feature = "json")]
+pub use self::json::{json_value_tree, JsonTreeOptions};
+#[cfg(feature = "serde")]
+pub use self::serializer::{to_tree, TreeSerializeError, TreeSerializer};
+#[cfg(feature = "termcolor")]
+pub use self::termcolor_printer::WriteColorTreePrinter;
+#[cfg(feature = "terminal-size")]
+pub use self::terminal::terminal_width;
+#[cfg(feature = "tracing-subscriber")]
+pub use self::tracing_layer::SpanTreeLayer;
+#[cfg(feature = "unicode-width")]
+pub use self::width::UnicodeWidth;
+#[cfg(feature = "xml")]
+pub use self::xml::{xml_document_tree, xml_element_tree, XmlTreeOptions};
+#[cfg(feature = "yaml")]
+pub use self::yaml::{yaml_value_tree, YamlTreeOptions};
+
+#[cfg(feature = "ansi")]
+pub(crate) mod ansi;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) mod async_printer;
+pub(crate) mod bench;
+pub(crate) mod canvas;
+pub(crate) mod capture;
+#[cfg(feature = "cargo-metadata")]
+pub(crate) mod cargo_tree;
+pub(crate) mod chart;
 pub(crate) mod config;
+pub(crate) mod error_chain;
+pub(crate) mod export;
+#[cfg(feature = "fs")]
+pub(crate) mod fs;
+#[cfg(feature = "nerd-fonts")]
+pub(crate) mod icons;
 pub(crate) mod item_writer;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+pub(crate) mod k8s;
+pub(crate) mod mirror;
+pub(crate) mod parser;
+pub(crate) mod quote;
+#[cfg(feature = "serde")]
+pub(crate) mod serializer;
+#[cfg(feature = "termcolor")]
+pub(crate) mod termcolor_printer;
+#[cfg(feature = "terminal-size")]
+pub(crate) mod terminal;
+pub(crate) mod theme;
+#[cfg(feature = "tracing-subscriber")]
+pub(crate) mod tracing_layer;
+pub(crate) mod tree_node;
 pub(crate) mod tree_printer;
+pub(crate) mod width;
+#[cfg(feature = "xml")]
+pub(crate) mod xml;
+#[cfg(feature = "yaml")]
+pub(crate) mod yaml;
@@ -0,0 +1,307 @@
+//! Mirrored and bottom-up tree layouts.
+
+use std::{iter, ops::Range};
+
+use crate::{config::TreeConfig, width};
+
+/// SGR sequence to reset all styles.
+const SGR_RESET: &str = "\x1b[0m";
+
+/// Returns the mirrored layout of the buffered tree output.
+///
+/// `content_starts` are the byte offsets in the buffer where the contents of lines start, in
+/// ascending order.
+/// Everything before the content in the line is the line prefix.
+/// Line prefixes are mirrored and put after the content of each line, and the lines are
+/// aligned to the right.
+pub(crate) fn mirror_lines(buf: &str, content_starts: &[usize], opts: &TreeConfig) -> String {
+    let newline = opts.newline();
+    let (body, trailing_newline) = match buf.strip_suffix(newline) {
+        Some(body) => (body, true),
+        None => (buf, false),
+    };
+    let mut content_starts = content_starts.iter().copied().peekable();
+    let mut line_start = 0;
+    let lines = body
+        .split(newline)
+        .map(|line| {
+            let line_end = line_start + line.len();
+            let mut content_start = None;
+            while let Some(start) = content_starts.next_if(|&start| start <= line_end) {
+                content_start = content_start.or(Some(start - line_start));
+            }
+            line_start = line_end + newline.len();
+            match content_start {
+                Some(start) => {
+                    let (prefix, content) = line.split_at(start);
+                    let prefix_width = width::str_width_ambi(prefix, opts.ambiguous_width());
+                    let width = prefix_width + opts.str_width(content);
+                    (mirror_prefix(prefix), content, width)
+                }
+                None => {
+                    let prefix = line.trim_end();
+                    let width = width::str_width_ambi(prefix, opts.ambiguous_width());
+                    (mirror_prefix(prefix), "", width)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let max_width = lines.iter().map(|&(_, _, width)| width).max().unwrap_or(0);
+
+    let mut mirrored = String::with_capacity(buf.len() + lines.len() * max_width);
+    for (i, (prefix, content, width)) in lines.into_iter().enumerate() {
+        if i != 0 {
+            mirrored.push_str(newline);
+        }
+        let line_start = mirrored.len();
+        mirrored.extend(std::iter::repeat(' ').take(max_width - width));
+        mirrored.push_str(content);
+        mirrored.push_str(&prefix);
+        if !opts.emit_trailing_whitespace() {
+            let trimmed = mirrored[line_start..].trim_end().len();
+            mirrored.truncate(line_start + trimmed);
+        }
+    }
+    if trailing_newline {
+        mirrored.push_str(newline);
+    }
+    mirrored
+}
+
+/// Returns the byte ranges of the items in the output of the given length.
+///
+/// `item_starts` are the byte offsets where items start, in ascending order.
+pub(crate) fn item_ranges(
+    item_starts: &[usize],
+    len: usize,
+) -> impl DoubleEndedIterator<Item = Range<usize>> + '_ {
+    (0..item_starts.len()).map(move |i| {
+        let end = item_starts.get(i + 1).copied().unwrap_or(len);
+        item_starts[i]..end
+    })
+}
+
+/// Returns the bottom-up layout of the buffered tree output.
+///
+/// `item_starts` are the byte offsets in the buffer where items start, in ascending order.
+/// The items are put in the reversed order, and `content_starts` are updated to point to the
+/// same positions in the returned string.
+pub(crate) fn reverse_items(
+    buf: &str,
+    item_starts: &[usize],
+    content_starts: &mut Vec<usize>,
+    opts: &TreeConfig,
+) -> String {
+    let newline = opts.newline();
+    let preamble_end = item_starts.first().copied().unwrap_or(buf.len());
+    let trailing_newline = item_ranges(item_starts, buf.len())
+        .last()
+        .map_or(true, |range| buf[range].ends_with(newline));
+
+    let mut reversed = String::with_capacity(buf.len() + newline.len());
+    let mut moved_content_starts = Vec::with_capacity(content_starts.len());
+    let preamble = 0..preamble_end;
+    for range in iter::once(preamble).chain(item_ranges(item_starts, buf.len()).rev()) {
+        let item = &buf[range.clone()];
+        // Items with empty labels have no lines.
+        if item.is_empty() {
+            continue;
+        }
+        moved_content_starts.extend(
+            content_starts
+                .iter()
+                .filter(|start| range.contains(start))
+                .map(|start| start - range.start + reversed.len()),
+        );
+        reversed.push_str(item);
+        if !item.ends_with(newline) {
+            reversed.push_str(newline);
+        }
+    }
+    if !trailing_newline && reversed.ends_with(newline) {
+        reversed.truncate(reversed.len() - newline.len());
+    }
+    moved_content_starts.sort_unstable();
+    *content_starts = moved_content_starts;
+    reversed
+}
+
+/// Aligns the root label and the mirrored descendants of a retained tree to the right.
+///
+/// Returns the label and the descendants padded to the width of the wider one.
+/// The label is separated by `\n`, and the descendants are separated by the configured newline.
+pub(crate) fn align_root_label(label: &str, body: &str, opts: &TreeConfig) -> (String, String) {
+    let newline = opts.newline();
+    let body_width = body
+        .split(newline)
+        .map(|line| opts.str_width(line))
+        .max()
+        .unwrap_or(0);
+    let label_width = label
+        .split('\n')
+        .map(|line| opts.str_width(line))
+        .max()
+        .unwrap_or(0);
+    let width = label_width.max(body_width);
+
+    let pad_lines = |s: &str, sep: &str, padding: &dyn Fn(&str) -> usize| {
+        let mut padded = String::with_capacity(s.len());
+        for (i, line) in s.split(sep).enumerate() {
+            if i != 0 {
+                padded.push_str(sep);
+            }
+            if !line.is_empty() {
+                padded.extend(std::iter::repeat(' ').take(padding(line)));
+            }
+            padded.push_str(line);
+        }
+        padded
+    };
+    let label = pad_lines(label, "\n", &|line| width - opts.str_width(line));
+    let body = pad_lines(body, newline, &|_| width - body_width);
+    (label, body)
+}
+
+/// Returns the prefix with the order of characters reversed and each character mirrored.
+///
+/// Styles set by escape sequences are kept for each character.
+fn mirror_prefix(prefix: &str) -> String {
+    if !prefix.contains('\x1b') {
+        return prefix.chars().rev().map(mirror_char).collect();
+    }
+
+    // Remember the escape sequences emitted before each character, so that the styles can be
+    // replayed in the reversed order.
+    let mut cells = Vec::new();
+    let mut escapes = String::new();
+    for (unit, is_escape) in width::units(prefix) {
+        if is_escape {
+            escapes.push_str(unit);
+        } else {
+            cells.push((escapes.clone(), unit));
+        }
+    }
+    let mut mirrored = String::with_capacity(prefix.len() * 2);
+    let mut current = None;
+    for (escapes, unit) in cells.iter().rev() {
+        if current != Some(escapes) {
+            mirrored.push_str(SGR_RESET);
+            mirrored.push_str(escapes);
+            current = Some(escapes);
+        }
+        mirrored.extend(unit.chars().rev().map(mirror_char));
+    }
+    mirrored.push_str(SGR_RESET);
+    mirrored
+}
+
+/// Returns the vertically flipped character.
+pub(crate) fn flip_char(c: char) -> char {
+    match c {
+        '`' => ',',
+        ',' => '`',
+        '/' => '\\',
+        '\\' => '/',
+        '\u{250C}' => '\u{2514}',
+        '\u{2514}' => '\u{250C}',
+        '\u{250D}' => '\u{2515}',
+        '\u{2515}' => '\u{250D}',
+        '\u{250F}' => '\u{2517}',
+        '\u{2517}' => '\u{250F}',
+        '\u{2510}' => '\u{2518}',
+        '\u{2518}' => '\u{2510}',
+        '\u{2513}' => '\u{251B}',
+        '\u{251B}' => '\u{2513}',
+        '\u{252C}' => '\u{2534}',
+        '\u{2534}' => '\u{252C}',
+        '\u{2533}' => '\u{253B}',
+        '\u{253B}' => '\u{2533}',
+        '\u{2554}' => '\u{255A}',
+        '\u{255A}' => '\u{2554}',
+        '\u{2557}' => '\u{255D}',
+        '\u{255D}' => '\u{2557}',
+        '\u{2566}' => '\u{2569}',
+        '\u{2569}' => '\u{2566}',
+        '\u{256D}' => '\u{2570}',
+        '\u{2570}' => '\u{256D}',
+        '\u{256E}' => '\u{256F}',
+        '\u{256F}' => '\u{256E}',
+        c => c,
+    }
+}
+
+/// Returns the horizontally mirrored character.
+fn mirror_char(c: char) -> char {
+    match c {
+        '`' => '\'',
+        '\'' => '`',
+        '/' => '\\',
+        '\\' => '/',
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '\u{250C}' => '\u{2510}',
+        '\u{2510}' => '\u{250C}',
+        '\u{250F}' => '\u{2513}',
+        '\u{2513}' => '\u{250F}',
+        '\u{2514}' => '\u{2518}',
+        '\u{2518}' => '\u{2514}',
+        '\u{2517}' => '\u{251B}',
+        '\u{251B}' => '\u{2517}',
+        '\u{251C}' => '\u{2524}',
+        '\u{2524}' => '\u{251C}',
+        '\u{2523}' => '\u{252B}',
+        '\u{252B}' => '\u{2523}',
+        '\u{2554}' => '\u{2557}',
+        '\u{2557}' => '\u{2554}',
+        '\u{255A}' => '\u{255D}',
+        '\u{255D}' => '\u{255A}',
+        '\u{2560}' => '\u{2563}',
+        '\u{2563}' => '\u{2560}',
+        '\u{256D}' => '\u{256E}',
+        '\u{256E}' => '\u{256D}',
+        '\u{256F}' => '\u{2570}',
+        '\u{2570}' => '\u{256F}',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_item_blocks() {
+        let opts = TreeConfig::new();
+        let mut content_starts = vec![0, 4, 6, 11];
+        assert_eq!(
+            reverse_items("a\n|-b\nb2\n|-c\n", &[0, 2, 9], &mut content_starts, &opts),
+            "|-c\n|-b\nb2\na\n"
+        );
+        assert_eq!(content_starts, [2, 6, 8, 11]);
+        assert_eq!(
+            reverse_items("a\nb", &[0, 2], &mut Vec::new(), &opts),
+            "b\na"
+        );
+        assert_eq!(reverse_items("ab", &[0, 1], &mut Vec::new(), &opts), "b\na");
+        assert_eq!(reverse_items("", &[], &mut Vec::new(), &opts), "");
+    }
+
+    #[test]
+    fn mirror_prefixes() {
+        assert_eq!(mirror_prefix("|   `-- "), " --'   |");
+        assert_eq!(
+            mirror_prefix("\u{2502}   \u{251C}\u{2500}\u{2500} "),
+            " \u{2500}\u{2500}\u{2524}   \u{2502}"
+        );
+        assert_eq!(
+            mirror_prefix("\x1b[31m`--\x1b[39m "),
+            "\x1b[0m\x1b[31m\x1b[39m \x1b[0m\x1b[31m--'\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,848 @@
+//! Parser of rendered trees.
+
+use std::{collections::VecDeque, error, fmt, iter, str};
+
+use crate::{
+    config::{EdgeConfig, PrefixPart, RenderConfig},
+    quote::unquote_label,
+    tree_node::TreeNode,
+};
+
+/// Tree parse error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// A line has prefixes which are inconsistent with the preceding lines.
+    InvalidPrefix {
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// A line has indentation which is inconsistent with the indent unit or the preceding lines.
+    InvalidIndent {
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// A label starting with `"` is not correctly quoted.
+    InvalidQuote {
+        /// Line number (1-based).
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPrefix { line } => write!(f, "Invalid tree prefix at line {}", line),
+            Self::InvalidIndent { line } => write!(f, "Invalid indentation at line {}", line),
+            Self::InvalidQuote { line } => write!(f, "Invalid quoted label at line {}", line),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parse event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEvent {
+    /// Opens a node with the given label.
+    Open(String),
+    /// Closes the last open node.
+    Close,
+}
+
+/// Options for parsing rendered trees.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Edge configs to detect.
+    edges: Vec<EdgeConfig>,
+    /// Label of the root node to wrap the top-level nodes.
+    root_label: String,
+    /// Unit of indentation for indentation-based outlines.
+    indent_unit: IndentUnit,
+    /// Whether to unquote quoted labels.
+    unquote_labels: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            edges: vec![
+                EdgeConfig::Ascii,
+                EdgeConfig::UnicodeSingleWidth,
+                EdgeConfig::UnicodeDoubleWidth,
+            ],
+            root_label: ".".to_owned(),
+            indent_unit: IndentUnit::Auto,
+            unquote_labels: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Creates a new default `ParseOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the edge configs to detect.
+    ///
+    /// The edge config is detected at the first node with prefixes, and the detected config is
+    /// used for the rest of the input.
+    /// Edge configs without visible markers for items (such as `EdgeConfig::Indent`) are ignored.
+    ///
+    /// Default is `Ascii`, `UnicodeSingleWidth`, and `UnicodeDoubleWidth`.
+    pub fn edges(&mut self, edges: impl IntoIterator<Item = EdgeConfig>) -> &mut Self {
+        self.edges = edges.into_iter().collect();
+        self
+    }
+
+    /// Sets the label of the root node to wrap the top-level nodes.
+    ///
+    /// The root node is created by [`TreeParser::into_tree()`] and [`parse_indented()`] when the
+    /// input does not consist of a single tree.
+    ///
+    /// Default is `"."`.
+    ///
+    /// [`TreeParser::into_tree()`]: struct.TreeParser.html#method.into_tree
+    /// [`parse_indented()`]: fn.parse_indented.html
+    pub fn root_label(&mut self, label: impl Into<String>) -> &mut Self {
+        self.root_label = label.into();
+        self
+    }
+
+    /// Sets the unit of indentation for indentation-based outlines.
+    ///
+    /// This is used by [`parse_indented()`].
+    ///
+    /// Default is `IndentUnit::Auto`.
+    ///
+    /// [`parse_indented()`]: fn.parse_indented.html
+    pub fn indent_unit(&mut self, unit: IndentUnit) -> &mut Self {
+        self.indent_unit = unit;
+        self
+    }
+
+    /// Sets whether to unquote labels quoted by [`TreeConfigBuilder::quote_labels()`].
+    ///
+    /// With this option, rendering with quoting and parsing with unquoting is lossless.
+    /// Labels starting with `"` must be correctly quoted.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{
+    ///     ParseOptions, RenderConfigBuilder, TreeConfigBuilder, TreeNode, TreeParser,
+    /// };
+    ///
+    /// let mut root = TreeNode::new("root\n|-- not a child");
+    /// root.push_child(TreeNode::new(" \"child\" "));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .tree_config(TreeConfigBuilder::new().quote_labels(true).build())
+    ///     .build();
+    /// let rendered = root.render(String::new(), &config)?;
+    /// assert_eq!(rendered, "\"root\\n|-- not a child\"\n`-- \" \\\"child\\\" \"\n");
+    ///
+    /// let parsed = TreeParser::new(&rendered, ParseOptions::new().unquote_labels(true))
+    ///     .into_tree()?;
+    /// assert_eq!(parsed, root);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`TreeConfigBuilder::quote_labels()`]: struct.TreeConfigBuilder.html#method.quote_labels
+    pub fn unquote_labels(&mut self, v: bool) -> &mut Self {
+        self.unquote_labels = v;
+        self
+    }
+}
+
+/// Prefixes of an edge config.
+#[derive(Debug, Clone)]
+struct EdgeUnits {
+    /// Prefixes of the first line of items: non-last child and last child.
+    items: [String; 2],
+    /// Prefixes of the following lines of ancestors: non-last child and last child.
+    ancestors: [String; 2],
+}
+
+impl EdgeUnits {
+    /// Creates a new `EdgeUnits` for the given edge config.
+    ///
+    /// Returns `None` if the edge config has no visible markers for items.
+    fn new(edge: &EdgeConfig) -> Option<Self> {
+        let unit = |last_child, first_line| {
+            let mut buf = String::new();
+            edge.write_edge(&mut buf, last_child, first_line, PrefixPart::Prefix)
+                .and_then(|_| {
+                    edge.write_edge(&mut buf, last_child, first_line, PrefixPart::Padding)
+                })
+                .expect("Should never fail: writing to `String`");
+            buf
+        };
+        let items = [unit(false, true), unit(true, true)];
+        if items.iter().any(|item| item.trim().is_empty()) {
+            return None;
+        }
+        Some(Self {
+            items,
+            ancestors: [unit(false, false), unit(true, false)],
+        })
+    }
+
+    /// Strips the item prefix, and returns the label.
+    fn strip_item<'a>(&self, line: &'a str) -> Option<&'a str> {
+        strip_unit(&self.items, line)
+    }
+
+    /// Strips the ancestor prefix, and returns the rest.
+    fn strip_ancestor<'a>(&self, line: &'a str) -> Option<&'a str> {
+        strip_unit(&self.ancestors, line)
+    }
+}
+
+/// Strips one of the given prefixes, and returns the rest.
+///
+/// Prefixes without trailing whitespaces are also accepted at the end of the line.
+fn strip_unit<'a>(units: &[String; 2], line: &'a str) -> Option<&'a str> {
+    units.iter().find_map(|unit| {
+        if line.starts_with(unit.as_str()) {
+            Some(&line[unit.len()..])
+        } else if !unit.trim_end().is_empty() && line.trim_end() == unit.trim_end() {
+            Some("")
+        } else {
+            None
+        }
+    })
+}
+
+/// Node whose label might continue to the next lines.
+#[derive(Debug, Clone)]
+struct PendingNode {
+    /// Number of the ancestor prefixes.
+    depth: usize,
+    /// Line number of the first line (1-based).
+    line: usize,
+    /// Label.
+    label: String,
+}
+
+/// Line of rendered trees.
+enum Line<'a> {
+    /// The first line of a node.
+    Node {
+        /// Number of the ancestor prefixes (i.e. depth of the node).
+        depth: usize,
+        /// First line of the label.
+        label: &'a str,
+    },
+    /// Following line of the last node.
+    Continuation(&'a str),
+}
+
+/// Parser of rendered trees.
+///
+/// This parses texts in the formats emitted by this crate (and `tree` command), and produces
+/// [`TreeEvent`]s.
+/// Multiline labels are parsed as labels of single nodes.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{ParseOptions, TreeEvent, TreeParser};
+///
+/// let input = "\
+/// .
+/// |-- foo
+/// |   `-- bar
+/// |       bar2
+/// `-- baz
+/// ";
+///
+/// let events = TreeParser::new(input, &ParseOptions::new()).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(
+///     events,
+///     [
+///         TreeEvent::Open(".".to_owned()),
+///         TreeEvent::Open("foo".to_owned()),
+///         TreeEvent::Open("bar\nbar2".to_owned()),
+///         TreeEvent::Close,
+///         TreeEvent::Close,
+///         TreeEvent::Open("baz".to_owned()),
+///         TreeEvent::Close,
+///         TreeEvent::Close,
+///     ]
+/// );
+///
+/// let tree = TreeParser::new(input, &ParseOptions::new()).into_tree()?;
+/// assert_eq!(tree.to_string(), input);
+/// # Ok::<_, plaintextree::ParseError>(())
+/// ```
+///
+/// [`TreeEvent`]: enum.TreeEvent.html
+#[derive(Debug, Clone)]
+pub struct TreeParser<'a> {
+    /// Input lines.
+    lines: iter::Enumerate<str::Lines<'a>>,
+    /// Candidates of the edge config.
+    candidates: Vec<EdgeUnits>,
+    /// Detected edge config.
+    edge: Option<EdgeUnits>,
+    /// Depth of the top-level nodes.
+    base_depth: Option<usize>,
+    /// Number of the open nodes.
+    num_open: usize,
+    /// Node whose label might continue to the next lines.
+    pending: Option<PendingNode>,
+    /// Events ready to be emitted.
+    queue: VecDeque<TreeEvent>,
+    /// Whether the whole input is processed.
+    finished: bool,
+    /// Label of the root node to wrap the top-level nodes.
+    root_label: String,
+    /// Whether to unquote quoted labels.
+    unquote_labels: bool,
+}
+
+impl<'a> TreeParser<'a> {
+    /// Creates a new `TreeParser`.
+    pub fn new(input: &'a str, options: &ParseOptions) -> Self {
+        Self {
+            lines: input.lines().enumerate(),
+            candidates: options.edges.iter().filter_map(EdgeUnits::new).collect(),
+            edge: None,
+            base_depth: None,
+            num_open: 0,
+            pending: None,
+            queue: VecDeque::new(),
+            finished: false,
+            root_label: options.root_label.clone(),
+            unquote_labels: options.unquote_labels,
+        }
+    }
+
+    /// Parses the whole input and returns the tree.
+    ///
+    /// If the input is a single tree with the root line without prefix, the root node is
+    /// returned.
+    /// Otherwise, the top-level nodes are wrapped with a new root node with the label given by
+    /// [`ParseOptions::root_label()`].
+    ///
+    /// [`ParseOptions::root_label()`]: struct.ParseOptions.html#method.root_label
+    pub fn into_tree(mut self) -> Result<TreeNode, ParseError> {
+        let root_label = self.root_label.clone();
+        let root = build_tree(&mut self, &root_label)?;
+        Ok(unwrap_single_root(root, self.base_depth == Some(0)))
+    }
+
+    /// Classifies the line.
+    fn classify<'l>(&mut self, line: &'l str, line_num: usize) -> Result<Line<'l>, ParseError> {
+        let pending_depth = match &self.pending {
+            Some(pending) => pending.depth,
+            None => {
+                if let Some((depth, label)) = self.strip_item(line, 0) {
+                    return Ok(Line::Node { depth, label });
+                }
+                return Ok(Line::Node {
+                    depth: 0,
+                    label: line,
+                });
+            }
+        };
+
+        let mut rest = line;
+        let mut depth = 0;
+        loop {
+            if let Some((depth, label)) = self.strip_item(rest, depth) {
+                return Ok(Line::Node { depth, label });
+            }
+            if depth == pending_depth {
+                return Ok(Line::Continuation(rest));
+            }
+            match self
+                .edge
+                .as_ref()
+                .and_then(|edge| edge.strip_ancestor(rest))
+            {
+                Some(stripped) => {
+                    rest = stripped;
+                    depth += 1;
+                }
+                None => break,
+            }
+        }
+
+        if rest.trim_end().is_empty() {
+            // An empty line with trailing whitespaces trimmed.
+            Ok(Line::Continuation(""))
+        } else if depth == 0 && self.base_depth == Some(0) {
+            // A new tree.
+            Ok(Line::Node {
+                depth: 0,
+                label: line,
+            })
+        } else {
+            Err(ParseError::InvalidPrefix { line: line_num })
+        }
+    }
+
+    /// Strips the item prefix using the detected (or detects) edge config.
+    ///
+    /// Returns the depth and the label on success.
+    fn strip_item<'l>(&mut self, line: &'l str, num_ancestors: usize) -> Option<(usize, &'l str)> {
+        if let Some(edge) = &self.edge {
+            return edge
+                .strip_item(line)
+                .map(|label| (num_ancestors + 1, label));
+        }
+        let (index, label) = self
+            .candidates
+            .iter()
+            .enumerate()
+            .find_map(|(i, edge)| edge.strip_item(line).map(|label| (i, label)))?;
+        self.edge = Some(self.candidates.swap_remove(index));
+        Some((num_ancestors + 1, label))
+    }
+
+    /// Emits the pending node.
+    fn flush_pending(&mut self) -> Result<(), ParseError> {
+        if let Some(PendingNode {
+            depth,
+            line,
+            mut label,
+        }) = self.pending.take()
+        {
+            let len = label.trim_end_matches('\n').len();
+            label.truncate(len);
+            if self.unquote_labels && label.starts_with('"') {
+                label = unquote_label(&label).ok_or(ParseError::InvalidQuote { line })?;
+            }
+            self.queue.push_back(TreeEvent::Open(label));
+            self.num_open = depth
+                - self
+                    .base_depth
+                    .expect("Should never fail: base depth is set with the first node")
+                + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Starts a new node.
+    fn start_node(&mut self, depth: usize, label: &str, line_num: usize) -> Result<(), ParseError> {
+        let base_depth = *self.base_depth.get_or_insert(depth);
+        if depth < base_depth {
+            return Err(ParseError::InvalidPrefix { line: line_num });
+        }
+        self.flush_pending()?;
+        while self.num_open > depth - base_depth {
+            self.queue.push_back(TreeEvent::Close);
+            self.num_open -= 1;
+        }
+        self.pending = Some(PendingNode {
+            depth,
+            line: line_num,
+            label: label.to_owned(),
+        });
+
+        Ok(())
+    }
+
+    /// Processes the next line.
+    fn process_line(&mut self, line_num: usize, line: &str) -> Result<(), ParseError> {
+        if self.pending.is_none() && line.trim().is_empty() {
+            // Skip leading empty lines.
+            return Ok(());
+        }
+        match self.classify(line, line_num)? {
+            Line::Node { depth, label } => self.start_node(depth, label, line_num)?,
+            Line::Continuation(content) => {
+                let label = &mut self
+                    .pending
+                    .as_mut()
+                    .expect("Should never fail: continuation lines follow a node")
+                    .label;
+                label.push('\n');
+                label.push_str(content);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for TreeParser<'_> {
+    type Item = Result<TreeEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.finished {
+                return None;
+            }
+            match self.lines.next() {
+                Some((i, line)) => {
+                    if let Err(e) = self.process_line(i + 1, line) {
+                        self.finished = true;
+                        return Some(Err(e));
+                    }
+                }
+                None => {
+                    self.finished = true;
+                    if let Err(e) = self.flush_pending() {
+                        return Some(Err(e));
+                    }
+                    for _ in 0..self.num_open {
+                        self.queue.push_back(TreeEvent::Close);
+                    }
+                    self.num_open = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Re-renders the tree text in another style.
+///
+/// The input is parsed by [`TreeParser`] with the given options, and rendered with the given
+/// config.
+/// Top-level lines without prefixes are kept as root lines, and top-level items with prefixes
+/// are kept as top-level items.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{convert, EdgeConfig, ParseOptions, RenderConfigBuilder};
+///
+/// let input = "\
+/// .
+/// |-- foo
+/// |   `-- bar
+/// `-- baz
+/// ";
+/// let config = RenderConfigBuilder::new()
+///     .edge(EdgeConfig::UnicodeSingleWidth)
+///     .build();
+///
+/// let got = convert(input, &ParseOptions::new(), &config)?;
+///
+/// let expected = "\
+/// .
+/// ├── foo
+/// │   └── bar
+/// └── baz
+/// ";
+/// assert_eq!(got, expected);
+/// # Ok::<_, plaintextree::ParseError>(())
+/// ```
+///
+/// [`TreeParser`]: struct.TreeParser.html
+pub fn convert(input: &str, from: &ParseOptions, to: &RenderConfig) -> Result<String, ParseError> {
+    let mut parser = TreeParser::new(input, from);
+    let root = build_tree(&mut parser, "")?;
+
+    let mut output = String::new();
+    if parser.base_depth == Some(0) {
+        for tree in root.children() {
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output = tree
+                .render(output, to)
+                .expect("Should never fail: rendering to `String`");
+        }
+    } else {
+        output = root
+            .render_descendants(output, to)
+            .expect("Should never fail: rendering to `String`");
+    }
+
+    Ok(output)
+}
+
+/// Parses two tree texts and renders their structural diff.
+///
+/// See [`TreeNode::diff()`] for the format of the diff.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{diff_texts, ParseOptions, RenderConfig};
+///
+/// let old = ".\n|-- a\n`-- b\n";
+/// let new = ".\n|-- a\n`-- c\n";
+///
+/// let got = diff_texts(old, new, &ParseOptions::new(), &RenderConfig::new())?;
+///
+/// let expected = "\
+/// ~ .
+/// |--   a
+/// |-- - b
+/// `-- + c
+/// ";
+/// assert_eq!(got, expected);
+/// # Ok::<_, plaintextree::ParseError>(())
+/// ```
+///
+/// [`TreeNode::diff()`]: struct.TreeNode.html#method.diff
+pub fn diff_texts(
+    old: &str,
+    new: &str,
+    options: &ParseOptions,
+    config: &RenderConfig,
+) -> Result<String, ParseError> {
+    let old = TreeParser::new(old, options).into_tree()?;
+    let new = TreeParser::new(new, options).into_tree()?;
+    Ok(old
+        .diff(&new)
+        .render(String::new(), config)
+        .expect("Should never fail: rendering to `String`"))
+}
+
+/// Builds a tree from the events, with the top-level nodes wrapped by a new root node.
+fn build_tree(
+    events: impl Iterator<Item = Result<TreeEvent, ParseError>>,
+    root_label: &str,
+) -> Result<TreeNode, ParseError> {
+    let mut stack = vec![TreeNode::new(root_label)];
+    for event in events {
+        match event? {
+            TreeEvent::Open(label) => stack.push(TreeNode::new(label)),
+            TreeEvent::Close => {
+                let node = stack
+                    .pop()
+                    .expect("Should never fail: `Close` events are balanced");
+                stack
+                    .last_mut()
+                    .expect("Should never fail: `Close` events are balanced")
+                    .push_child(node);
+            }
+        }
+    }
+    Ok(stack
+        .pop()
+        .expect("Should never fail: the root is always in `stack`"))
+}
+
+/// Returns the only child of the wrapping root node if possible.
+fn unwrap_single_root(mut root: TreeNode, unwrap: bool) -> TreeNode {
+    if unwrap && root.children().len() == 1 {
+        return root.children_mut().remove(0);
+    }
+    root
+}
+
+/// Unit of indentation.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentUnit {
+    /// Detects from the first indented line.
+    ///
+    /// If the line is indented by tabs, a tab is used as the unit.
+    /// Otherwise, the number of the leading spaces of the line is used as the unit.
+    #[default]
+    Auto,
+    /// A tab.
+    Tab,
+    /// The given number of spaces.
+    Spaces(usize),
+}
+
+/// Parses an indentation-based outline.
+///
+/// Each non-empty line becomes a node, and its depth is determined by the indentation.
+/// Empty lines are ignored.
+/// The indentation unit is configured by [`ParseOptions::indent_unit()`].
+///
+/// If the input has a single top-level line, the node for the line is returned as the root.
+/// Otherwise, the top-level nodes are wrapped with a new root node with the label given by
+/// [`ParseOptions::root_label()`].
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{parse_indented, ParseOptions};
+///
+/// let input = "\
+/// groceries
+///   fruits
+///     apple
+///   milk
+/// ";
+///
+/// let tree = parse_indented(input, &ParseOptions::new())?;
+///
+/// let expected = "\
+/// groceries
+/// |-- fruits
+/// |   `-- apple
+/// `-- milk
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// # Ok::<_, plaintextree::ParseError>(())
+/// ```
+///
+/// [`ParseOptions::indent_unit()`]: struct.ParseOptions.html#method.indent_unit
+/// [`ParseOptions::root_label()`]: struct.ParseOptions.html#method.root_label
+pub fn parse_indented(input: &str, options: &ParseOptions) -> Result<TreeNode, ParseError> {
+    let mut unit = options.indent_unit;
+    let mut events = Vec::new();
+    let mut num_open = 0;
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..(line.len() - content.len())];
+        if unit == IndentUnit::Auto && !indent.is_empty() {
+            unit = if indent.starts_with('\t') {
+                IndentUnit::Tab
+            } else {
+                IndentUnit::Spaces(indent.len())
+            };
+        }
+        let depth = match unit {
+            _ if indent.is_empty() => Some(0),
+            IndentUnit::Tab if indent.bytes().all(|b| b == b'\t') => Some(indent.len()),
+            IndentUnit::Spaces(n) if n != 0 && indent.bytes().all(|b| b == b' ') => {
+                if indent.len() % n == 0 {
+                    Some(indent.len() / n)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        let depth = match depth {
+            Some(depth) if depth <= num_open => depth,
+            _ => return Err(ParseError::InvalidIndent { line: i + 1 }),
+        };
+
+        events.extend(iter::repeat(TreeEvent::Close).take(num_open - depth));
+        events.push(TreeEvent::Open(content.to_owned()));
+        num_open = depth + 1;
+    }
+    events.extend(iter::repeat(TreeEvent::Close).take(num_open));
+
+    let root = build_tree(events.into_iter().map(Ok), &options.root_label)?;
+    Ok(unwrap_single_root(root, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{RenderConfigBuilder, TreeConfigBuilder};
+
+    fn test_tree() -> TreeNode {
+        let mut foo = TreeNode::new("foo");
+        foo.push_child(TreeNode::new("bar"));
+        foo.children_mut()[0].push_child(TreeNode::new("baz\n\nbaz2"));
+        foo.push_child(TreeNode::new("qux"));
+        foo.children_mut()[1].push_child(TreeNode::new("quux"));
+        let mut root = TreeNode::new(".");
+        root.push_child(foo);
+        root.push_child(TreeNode::new("corge"));
+        root.push_child(TreeNode::new("grault"));
+        root
+    }
+
+    #[test]
+    fn roundtrip() -> Result<(), ParseError> {
+        let tree = test_tree();
+        for edge in [
+            EdgeConfig::Ascii,
+            EdgeConfig::UnicodeSingleWidth,
+            EdgeConfig::UnicodeDoubleWidth,
+        ]
+        .iter()
+        {
+            for &trailing_whitespace in &[false, true] {
+                let config = RenderConfigBuilder::new()
+                    .edge(edge.clone())
+                    .tree_config(
+                        TreeConfigBuilder::new()
+                            .emit_trailing_whitespace(trailing_whitespace)
+                            .build(),
+                    )
+                    .build();
+                let rendered = tree
+                    .render(String::new(), &config)
+                    .expect("Should never fail: rendering to `String`");
+                let parsed = TreeParser::new(&rendered, &ParseOptions::new()).into_tree()?;
+                assert_eq!(parsed, tree, "rendered:\n{}", rendered);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_items_with_prefixes() -> Result<(), ParseError> {
+        let input = "|-- foo\n|   `-- bar\n`-- baz\n";
+        let mut options = ParseOptions::new();
+        options.root_label("root");
+        let tree = TreeParser::new(input, &options).into_tree()?;
+        assert_eq!(tree.to_string(), format!("root\n{}", input));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_prefix() {
+        let input = ".\n|-- foo\n|   `-- bar\n    qux\n";
+        let got = TreeParser::new(input, &ParseOptions::new()).into_tree();
+        assert_eq!(got, Err(ParseError::InvalidPrefix { line: 4 }));
+    }
+
+    #[test]
+    fn indented_with_tabs_and_multiple_roots() -> Result<(), ParseError> {
+        let input = "a\n\tb\n\n\t\tc\nd\n";
+        let tree = parse_indented(input, &ParseOptions::new())?;
+        assert_eq!(
+            tree.to_string(),
+            ".\n|-- a\n|   `-- b\n|       `-- c\n`-- d\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_indent() {
+        let mut options = ParseOptions::new();
+        options.indent_unit(IndentUnit::Spaces(2));
+        assert_eq!(
+            parse_indented("a\n   b\n", &options),
+            Err(ParseError::InvalidIndent { line: 2 })
+        );
+        assert_eq!(
+            parse_indented("a\n    b\n", &options),
+            Err(ParseError::InvalidIndent { line: 2 })
+        );
+        assert_eq!(
+            parse_indented("a\n\tb\n", &options),
+            Err(ParseError::InvalidIndent { line: 2 })
+        );
+    }
+
+    #[test]
+    fn convert_top_level_items_and_multiple_roots() -> Result<(), ParseError> {
+        let config = RenderConfigBuilder::new()
+            .edge(EdgeConfig::UnicodeDoubleWidth)
+            .build();
+
+        let got = convert("|-- a\n`-- b\n", &ParseOptions::new(), &config)?;
+        assert_eq!(got, "\u{251C}\u{2500} a\n\u{2514}\u{2500} b\n");
+
+        let got = convert("x\n`-- y\nz\n", &ParseOptions::new(), &config)?;
+        assert_eq!(got, "x\n\u{2514}\u{2500} y\nz\n");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_quote() {
+        let mut options = ParseOptions::new();
+        options.unquote_labels(true);
+        let got = TreeParser::new(".\n`-- \"a\\q\"\n", &options).into_tree();
+        assert_eq!(got, Err(ParseError::InvalidQuote { line: 2 }));
+    }
+}
@@ -0,0 +1,112 @@
+//! Label quoting.
+//!
+//! Quoted labels are enclosed by `"`, and special characters in them are escaped by backslashes
+//! (`\\`, `\"`, `\n`, `\r`, `\t`, and `\u{XXXX}` for other control characters).
+
+use std::{borrow::Cow, fmt::Write};
+
+/// Returns whether the label needs to be quoted to be parsed back unambiguously.
+fn needs_quote(label: &str) -> bool {
+    let first = match label.chars().next() {
+        Some(c) => c,
+        None => return true,
+    };
+    let edge_like = matches!(first, '|' | '`' | '-' | '+' | '"' | '\u{2022}')
+        || ('\u{2500}'..='\u{257F}').contains(&first);
+    edge_like
+        || first.is_whitespace()
+        || label.ends_with(char::is_whitespace)
+        || label.chars().any(char::is_control)
+}
+
+/// Quotes the label if necessary.
+pub(crate) fn quote_label(label: &str) -> Cow<'_, str> {
+    if !needs_quote(label) {
+        return Cow::Borrowed(label);
+    }
+
+    let mut quoted = String::with_capacity(label.len() + 2);
+    quoted.push('"');
+    for c in label.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => write!(quoted, "\\u{{{:04X}}}", u32::from(c))
+                .expect("Should never fail: writing to `String`"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+/// Unquotes the quoted label.
+///
+/// Returns `None` if the label is not correctly quoted.
+pub(crate) fn unquote_label(quoted: &str) -> Option<String> {
+    let body = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut label = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return None,
+            '\\' => match chars.next()? {
+                '\\' => label.push('\\'),
+                '"' => label.push('"'),
+                'n' => label.push('\n'),
+                'r' => label.push('\r'),
+                't' => label.push('\t'),
+                'u' => {
+                    let rest = chars.as_str().strip_prefix('{')?;
+                    let end = rest.find('}')?;
+                    let code = u32::from_str_radix(&rest[..end], 16).ok()?;
+                    label.push(std::char::from_u32(code)?);
+                    chars = rest[(end + 1)..].chars();
+                }
+                _ => return None,
+            },
+            c => label.push(c),
+        }
+    }
+    Some(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for label in &[
+            "",
+            " a",
+            "a ",
+            "\"",
+            "|-- a",
+            "a\nb\\c",
+            "\u{1b}[1m",
+            "\u{2514}x",
+        ] {
+            let quoted = quote_label(label);
+            assert!(
+                quoted.starts_with('"'),
+                "label {:?} should be quoted",
+                label
+            );
+            assert!(!quoted.contains('\n'));
+            assert_eq!(unquote_label(&quoted).as_deref(), Some(*label));
+        }
+        assert_eq!(quote_label("plain label"), "plain label");
+    }
+
+    #[test]
+    fn invalid_quote() {
+        assert_eq!(unquote_label("\"a"), None);
+        assert_eq!(unquote_label("\"a\"b\""), None);
+        assert_eq!(unquote_label("\"\\x\""), None);
+        assert_eq!(unquote_label("\"\\u{110000}\""), None);
+    }
+}
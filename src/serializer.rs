@@ -0,0 +1,539 @@
+//! Serializer into trees.
+
+use std::{error, fmt};
+
+use serde::ser::{self, Serialize};
+
+use crate::tree_node::TreeNode;
+
+/// Error for serializing values into trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeSerializeError {
+    /// Message.
+    msg: String,
+}
+
+impl fmt::Display for TreeSerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl error::Error for TreeSerializeError {}
+
+impl ser::Error for TreeSerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+/// Serializes the value into a tree.
+///
+/// Structs, maps, sequences, tuples, and non-unit enum variants are branches, and other
+/// values are leaves.
+/// Each node is labeled with the field name, the map key, or the `[index]` of the value,
+/// and followed by `: ` and the value for leaves or the type name for branches of structs and
+/// enum variants.
+/// Strings and characters are quoted, and `Option` and newtype structs are transparent.
+/// The root node is labeled with the type name for structs and enum variants, or `.` for
+/// other branches.
+///
+/// Map keys should be serialized as leaves.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::to_tree;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Point,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Layer {
+///     name: String,
+///     visible: bool,
+///     opacity: Option<u8>,
+///     shapes: Vec<Shape>,
+/// }
+///
+/// let layer = Layer {
+///     name: "background".to_owned(),
+///     visible: true,
+///     opacity: None,
+///     shapes: vec![Shape::Circle { radius: 2.0 }, Shape::Point],
+/// };
+/// let tree = to_tree(&layer)?;
+///
+/// let expected = "\
+/// Layer
+/// |-- name: \"background\"
+/// |-- visible: true
+/// |-- opacity: None
+/// `-- shapes
+///     |-- [0]: Circle
+///     |   `-- radius: 2.0
+///     `-- [1]: Point
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// # Ok::<_, plaintextree::TreeSerializeError>(())
+/// ```
+pub fn to_tree<T: Serialize + ?Sized>(value: &T) -> Result<TreeNode, TreeSerializeError> {
+    let mut tree = value.serialize(TreeSerializer::new())?;
+    if tree.label().is_empty() {
+        tree.set_label(".");
+    }
+    Ok(tree)
+}
+
+/// Serializer into a tree.
+///
+/// The label of the serialized node is the value for leaves, the type name for structs and
+/// enum variants, or empty for other branches.
+/// Use [`to_tree()`] to serialize values into complete trees.
+///
+/// [`to_tree()`]: fn.to_tree.html
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TreeSerializer {
+    /// Whether the value is a map key, whose strings are not quoted.
+    key: bool,
+}
+
+impl TreeSerializer {
+    /// Creates a new `TreeSerializer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a leaf with the given label.
+    fn leaf(label: impl Into<String>) -> Result<TreeNode, TreeSerializeError> {
+        Ok(TreeNode::new(label))
+    }
+
+    /// Returns a compound serializer for the branch with the given label.
+    fn branch(label: &str, empty: &'static str) -> Compound {
+        Compound {
+            node: TreeNode::new(label),
+            empty,
+            key: None,
+        }
+    }
+}
+
+/// Returns the node with the label prefixed by the name.
+fn named(name: String, mut node: TreeNode) -> TreeNode {
+    let label = if node.label().is_empty() {
+        name
+    } else {
+        format!("{}: {}", name, node.label())
+    };
+    node.set_label(label);
+    node
+}
+
+impl ser::Serializer for TreeSerializer {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fn serialize_bool(self, v: bool) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(v.to_string())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(format!("{:?}", v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(format!("{:?}", v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<TreeNode, TreeSerializeError> {
+        if self.key {
+            Self::leaf(v.to_string())
+        } else {
+            Self::leaf(format!("{:?}", v))
+        }
+    }
+
+    fn serialize_str(self, v: &str) -> Result<TreeNode, TreeSerializeError> {
+        if self.key {
+            Self::leaf(v)
+        } else {
+            Self::leaf(format!("{:?}", v))
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(format!("{:?}", v))
+    }
+
+    fn serialize_none(self) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf("None")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<TreeNode, TreeSerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf("()")
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<TreeNode, TreeSerializeError> {
+        Self::leaf(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<TreeNode, TreeSerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<TreeNode, TreeSerializeError> {
+        let inner = value.serialize(self)?;
+        if inner.is_leaf() {
+            return Self::leaf(format!("{}({})", variant, inner.label()));
+        }
+        let mut node = TreeNode::new(variant);
+        node.push_child(named("[0]".to_owned(), inner));
+        Ok(node)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch("", "[]"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch("", "()"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch(name, ""))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch(variant, ""))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch("", "{}"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch(name, ""))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound, TreeSerializeError> {
+        Ok(Self::branch(variant, ""))
+    }
+}
+
+/// Serializer for the children of a branch.
+#[derive(Debug)]
+pub struct Compound {
+    /// Node of the branch.
+    node: TreeNode,
+    /// Label used if the branch has neither type names nor children.
+    empty: &'static str,
+    /// Key of the next map value.
+    key: Option<String>,
+}
+
+impl Compound {
+    /// Serializes the child with the given name.
+    fn push(
+        &mut self,
+        name: String,
+        value: &(impl Serialize + ?Sized),
+    ) -> Result<(), TreeSerializeError> {
+        let child = value.serialize(TreeSerializer::new())?;
+        self.node.push_child(named(name, child));
+        Ok(())
+    }
+
+    /// Serializes the child named with its index.
+    fn push_element(
+        &mut self,
+        value: &(impl Serialize + ?Sized),
+    ) -> Result<(), TreeSerializeError> {
+        self.push(format!("[{}]", self.node.children().len()), value)
+    }
+
+    /// Finishes the branch.
+    fn finish(mut self) -> Result<TreeNode, TreeSerializeError> {
+        if self.node.is_leaf() && self.node.label().is_empty() {
+            self.node.set_label(self.empty);
+        }
+        Ok(self.node)
+    }
+}
+
+impl ser::SerializeSeq for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), TreeSerializeError> {
+        let key = key.serialize(TreeSerializer { key: true })?;
+        if !key.is_leaf() {
+            return Err(ser::Error::custom(
+                "map keys should be serialized as leaves",
+            ));
+        }
+        self.key = Some(key.label().to_owned());
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("map value is serialized before the key"))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound {
+    type Ok = TreeNode;
+    type Error = TreeSerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TreeSerializeError> {
+        self.push(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<TreeNode, TreeSerializeError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    #[derive(serde::Serialize)]
+    struct Meters(f32);
+
+    #[derive(serde::Serialize)]
+    enum Item {
+        Wrapped(Vec<u8>),
+        Pair(char, Meters),
+        Empty {},
+    }
+
+    #[test]
+    fn containers() -> Result<(), TreeSerializeError> {
+        let mut map = BTreeMap::new();
+        map.insert("a b", vec![Item::Wrapped(vec![1]), Item::Wrapped(vec![])]);
+        map.insert("c", vec![Item::Pair('x', Meters(1.5)), Item::Empty {}]);
+        map.insert("d", vec![]);
+        let got = to_tree(&(map, ()))?.to_string();
+        let expected = ".\n\
+                        |-- [0]\n\
+                        |   |-- a b\n\
+                        |   |   |-- [0]: Wrapped\n\
+                        |   |   |   `-- [0]\n\
+                        |   |   |       `-- [0]: 1\n\
+                        |   |   `-- [1]: Wrapped([])\n\
+                        |   |-- c\n\
+                        |   |   |-- [0]: Pair\n\
+                        |   |   |   |-- [0]: 'x'\n\
+                        |   |   |   `-- [1]: 1.5\n\
+                        |   |   `-- [1]: Empty\n\
+                        |   `-- d: []\n\
+                        `-- [1]: ()\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn non_scalar_keys() {
+        let mut map = BTreeMap::new();
+        map.insert(vec![1], 2);
+        assert!(to_tree(&map).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Tree printer for `termcolor`.
+
+use std::fmt;
+
+use termcolor::{Color as TermColor, ColorSpec, WriteColor};
+
+use crate::{
+    ansi::{AnsiSegment, AnsiSegments, SgrColor, SgrState},
+    config::{ItemStyle, TreeConfig},
+    tree_printer::{Backend, BackendPrinter, Result},
+};
+
+/// Tree printer writing to a `termcolor::WriteColor`.
+///
+/// This is the same as [`IoTreePrinter`] except that edge colors and label styles are emitted
+/// through [`WriteColor::set_color()`] instead of raw SGR sequences, so they are also
+/// available on Windows consoles without virtual terminal support.
+/// If the writer does not support colors, the tree is written without colors, and
+/// `ColorMode::Auto` is resolved to `ColorMode::Never`.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{Color, EdgeConfig, ItemStyle, TreeConfig, WriteColorTreePrinter};
+/// use termcolor::Buffer;
+///
+/// let mut printer = WriteColorTreePrinter::new(Buffer::no_color(), TreeConfig::new());
+/// let edge = ItemStyle::non_last(EdgeConfig::Ascii).with_edge_color(Color::Blue);
+/// printer.open_node(edge, "foo")?;
+/// printer.close_node()?;
+/// printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+/// let buf = printer.finalize()?;
+///
+/// assert_eq!(buf.as_slice(), b"|-- foo\n`-- bar\n");
+/// # plaintextree::Result::Ok(())
+/// ```
+///
+/// [`IoTreePrinter`]: struct.IoTreePrinter.html
+/// [`WriteColor::set_color()`]: https://docs.rs/termcolor/1/termcolor/trait.WriteColor.html#tymethod.set_color
+pub struct WriteColorTreePrinter<W> {
+    /// Tree printer.
+    printer: BackendPrinter<ColorBackend<W>>,
+}
+
+impl<W: WriteColor> WriteColorTreePrinter<W> {
+    /// Creates a new `WriteColorTreePrinter`.
+    pub fn new(writer: W, opts: TreeConfig) -> Self {
+        let supports_color = writer.supports_color();
+        let backend = ColorBackend {
+            writer,
+            state: SgrState::default(),
+        };
+        Self {
+            printer: BackendPrinter::new(backend, opts, supports_color),
+        }
+    }
+
+    /// Opens a new node with the given content.
+    ///
+    /// See [`TreePrinter::open_node()`].
+    ///
+    /// [`TreePrinter::open_node()`]: struct.TreePrinter.html#method.open_node
+    pub fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content)
+    }
+
+    /// Closes a node.
+    ///
+    /// See [`TreePrinter::close_node()`].
+    ///
+    /// [`TreePrinter::close_node()`]: struct.TreePrinter.html#method.close_node
+    pub fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node()
+    }
+
+    /// Finishes writing the tree, resets the colors, flushes the inner writer, and returns it.
+    pub fn finalize(self) -> Result<W> {
+        Ok(self.printer.finalize()?.writer)
+    }
+}
+
+/// Backend replacing SGR sequences with color specs of a `termcolor::WriteColor`.
+struct ColorBackend<W> {
+    /// Writer.
+    writer: W,
+    /// Text attributes currently set to the writer.
+    state: SgrState,
+}
+
+impl<W: WriteColor> Backend for ColorBackend<W> {
+    fn write(&mut self, s: &str) -> Result<()> {
+        for segment in AnsiSegments::new(s) {
+            let params = match segment {
+                AnsiSegment::Text(text) => {
+                    self.writer.write_all(text.as_bytes())?;
+                    continue;
+                }
+                AnsiSegment::Sgr(_) if !self.writer.supports_color() => continue,
+                AnsiSegment::Sgr(params) => params,
+            };
+
+            let prev = self.state.clone();
+            self.state.apply(params);
+            if self.state == prev {
+                continue;
+            }
+            if self.state == SgrState::default() {
+                self.writer.reset()?;
+            } else {
+                self.writer.set_color(&color_spec(&self.state))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.state != SgrState::default() {
+            self.writer.reset()?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Returns the color spec for the text attributes.
+///
+/// Reverse video is emulated by swapping the colors, assuming white text on black background
+/// for the default colors.
+fn color_spec(state: &SgrState) -> ColorSpec {
+    let (fg, bg) = if state.reverse {
+        (
+            Some(state.bg.unwrap_or(SgrColor::Basic(0))),
+            Some(state.fg.unwrap_or(SgrColor::Basic(7))),
+        )
+    } else {
+        (state.fg, state.bg)
+    };
+    let fg = fg.map(term_color);
+    let bg = bg.map(term_color);
+
+    let mut spec = ColorSpec::new();
+    spec.set_bold(state.bold)
+        .set_dimmed(state.dim)
+        .set_italic(state.italic)
+        .set_underline(state.underline)
+        .set_fg(fg.map(|(color, _)| color))
+        .set_bg(bg.map(|(color, _)| color));
+    // `termcolor` has only one intensity flag, and the foreground is more noticeable.
+    if let Some((_, intense)) = fg.or(bg) {
+        spec.set_intense(intense);
+    }
+    spec
+}
+
+/// Returns the `termcolor` color and whether it is intense.
+fn term_color(color: SgrColor) -> (TermColor, bool) {
+    /// Basic colors in the order of SGR color codes.
+    const BASIC: [TermColor; 8] = [
+        TermColor::Black,
+        TermColor::Red,
+        TermColor::Green,
+        TermColor::Yellow,
+        TermColor::Blue,
+        TermColor::Magenta,
+        TermColor::Cyan,
+        TermColor::White,
+    ];
+
+    match color {
+        SgrColor::Basic(index) => (BASIC[usize::from(index % 8)], index >= 8),
+        SgrColor::Indexed(index) => (TermColor::Ansi256(index), false),
+        SgrColor::Rgb(r, g, b) => (TermColor::Rgb(r, g, b), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io;
+
+    use crate::{
+        ansi::{Color, ColorMode},
+        config::{EdgeConfig, TreeConfigBuilder},
+    };
+
+    /// Event emitted to the recorder.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Event {
+        /// Text.
+        Text(String),
+        /// Color spec.
+        Color(ColorSpec),
+        /// Reset.
+        Reset,
+    }
+
+    /// Writer recording the text and the colors.
+    #[derive(Default)]
+    struct Recorder {
+        /// Recorded events.
+        events: Vec<Event>,
+    }
+
+    impl io::Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let text = String::from_utf8_lossy(buf).into_owned();
+            match self.events.last_mut() {
+                Some(Event::Text(last)) => last.push_str(&text),
+                _ => self.events.push(Event::Text(text)),
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl WriteColor for Recorder {
+        fn supports_color(&self) -> bool {
+            true
+        }
+
+        fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+            self.events.push(Event::Color(spec.clone()));
+            Ok(())
+        }
+
+        fn reset(&mut self) -> io::Result<()> {
+            self.events.push(Event::Reset);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn edge_colors() -> Result<()> {
+        let mut printer = WriteColorTreePrinter::new(Recorder::default(), TreeConfig::new());
+        let style = ItemStyle::non_last(EdgeConfig::Ascii).with_edge_color(Color::BrightRed);
+        printer.open_node(style, "foo")?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+        let got = printer.finalize()?.events;
+
+        let mut red = ColorSpec::new();
+        red.set_fg(Some(TermColor::Red)).set_intense(true);
+        let expected = [
+            Event::Color(red),
+            Event::Text("|--".into()),
+            Event::Reset,
+            Event::Text(" foo\n`-- bar\n".into()),
+        ];
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn auto_color_mode_with_color_writer() -> Result<()> {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            // Colors are disabled by the environment.
+            return Ok(());
+        }
+        let opts = TreeConfigBuilder::new()
+            .edge_color(Some(Color::Red))
+            .color_mode(ColorMode::Auto)
+            .build();
+        let mut printer = WriteColorTreePrinter::new(Recorder::default(), opts);
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        let got = printer.finalize()?.events;
+
+        let mut red = ColorSpec::new();
+        red.set_fg(Some(TermColor::Red));
+        let expected = [
+            Event::Color(red),
+            Event::Text("`--".into()),
+            Event::Reset,
+            Event::Text(" foo\n".into()),
+        ];
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_video() {
+        let mut state = SgrState::default();
+        state.apply("7;31");
+        let mut expected = ColorSpec::new();
+        expected
+            .set_fg(Some(TermColor::Black))
+            .set_bg(Some(TermColor::Red));
+        assert_eq!(color_spec(&state), expected);
+    }
+}
@@ -0,0 +1,49 @@
+//! Terminal size detection.
+
+use std::{env, ffi::OsStr};
+
+/// Returns the width of the terminal in columns.
+///
+/// `COLUMNS` environment variable is used if it is set to a positive integer, so that users
+/// can override the width explicitly.
+/// Otherwise, the width of the terminal connected to the standard output is returned.
+/// Returns `None` if the standard output is not a terminal.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{terminal_width, TreeConfigBuilder};
+///
+/// let opts = TreeConfigBuilder::new().max_width(terminal_width()).build();
+/// ```
+pub fn terminal_width() -> Option<usize> {
+    width_from_env(env::var_os("COLUMNS").as_deref()).or_else(|| {
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| usize::from(width))
+    })
+}
+
+/// Parses the value of `COLUMNS` environment variable.
+fn width_from_env(columns: Option<&OsStr>) -> Option<usize> {
+    columns?
+        .to_str()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&width| width != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_env() {
+        let parse = |v: &'static str| width_from_env(Some(OsStr::new(v)));
+        assert_eq!(parse("80"), Some(80));
+        assert_eq!(parse(" 132\n"), Some(132));
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("-1"), None);
+        assert_eq!(parse("wide"), None);
+        assert_eq!(width_from_env(None), None);
+    }
+}
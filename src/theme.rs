@@ -0,0 +1,495 @@
+//! Named themes.
+
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::{error, fmt};
+
+#[cfg(feature = "ansi")]
+use crate::ansi::Color;
+use crate::config::{
+    CustomEdgeConfigBuilder, EdgeConfig, LabelOverflow, TreeConfig, TreeConfigBuilder,
+};
+
+/// Character set of edges of a theme.
+///
+/// This is serialized as a kebab-case string such as `"unicode"` and `"ascii-plus"`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum EdgeCharset {
+    /// `EdgeConfig::Ascii`.
+    #[default]
+    Ascii,
+    /// `EdgeConfig::UnicodeSingleWidth`.
+    Unicode,
+    /// `EdgeConfig::UnicodeDoubleWidth`.
+    UnicodeDouble,
+    /// `EdgeConfig::rounded()`.
+    Rounded,
+    /// `EdgeConfig::bold()`.
+    Bold,
+    /// `EdgeConfig::double()`.
+    Double,
+    /// `EdgeConfig::dotted()`.
+    Dotted,
+    /// `EdgeConfig::ascii_plus()`.
+    AsciiPlus,
+    /// `EdgeConfig::compact()`.
+    Compact,
+    /// `EdgeConfig::compact_ascii()`.
+    CompactAscii,
+    /// `EdgeConfig::bullet()`.
+    Bullet,
+    /// `EdgeConfig::from_environment()`.
+    Environment,
+    /// ASCII edges of the `tree /A` command of DOS and Windows.
+    ///
+    /// ```text
+    /// .
+    /// +---foo
+    /// |   \\---bar
+    /// \\---baz
+    /// ```
+    Dos,
+    /// Markdown list items marked with `-`.
+    ///
+    /// ```text
+    /// .
+    /// - foo
+    ///   - bar
+    /// - baz
+    /// ```
+    Markdown,
+}
+
+impl EdgeCharset {
+    /// Returns the characters for a tee, a corner, a horizontal line, and a vertical line.
+    ///
+    /// Returns `None` if the edges cannot be resized.
+    fn ruled_chars(self) -> Option<[char; 4]> {
+        match self {
+            Self::Ascii | Self::CompactAscii => Some(['|', '`', '-', '|']),
+            Self::Unicode | Self::Compact => Some(['\u{251C}', '\u{2514}', '\u{2500}', '\u{2502}']),
+            Self::Rounded => Some(['\u{251C}', '\u{2570}', '\u{2500}', '\u{2502}']),
+            Self::Bold => Some(['\u{2523}', '\u{2517}', '\u{2501}', '\u{2503}']),
+            Self::Double => Some(['\u{2560}', '\u{255A}', '\u{2550}', '\u{2551}']),
+            Self::Dotted => Some(['\u{251C}', '\u{2514}', '\u{2504}', '\u{2506}']),
+            Self::AsciiPlus => Some(['+', '+', '-', '|']),
+            Self::UnicodeDouble | Self::Bullet | Self::Environment | Self::Dos | Self::Markdown => {
+                None
+            }
+        }
+    }
+
+    /// Returns the edge config.
+    fn edge(self) -> EdgeConfig {
+        match self {
+            Self::Ascii => EdgeConfig::Ascii,
+            Self::Unicode => EdgeConfig::UnicodeSingleWidth,
+            Self::UnicodeDouble => EdgeConfig::UnicodeDoubleWidth,
+            Self::Rounded => EdgeConfig::rounded(),
+            Self::Bold => EdgeConfig::bold(),
+            Self::Double => EdgeConfig::double(),
+            Self::Dotted => EdgeConfig::dotted(),
+            Self::AsciiPlus => EdgeConfig::ascii_plus(),
+            Self::Compact => EdgeConfig::compact(),
+            Self::CompactAscii => EdgeConfig::compact_ascii(),
+            Self::Bullet => EdgeConfig::bullet(),
+            Self::Environment => EdgeConfig::from_environment(),
+            Self::Dos => EdgeConfig::Custom(
+                CustomEdgeConfigBuilder::new()
+                    .branch("+---")
+                    .last_branch("\\---")
+                    .branch_padding("")
+                    .continuation("|")
+                    .continuation_padding("   ")
+                    .last_continuation("")
+                    .last_continuation_padding("    ")
+                    .build()
+                    .expect("Should never fail: preset edges have consistent widths"),
+            ),
+            Self::Markdown => EdgeConfig::Bullet("- ".to_owned()),
+        }
+    }
+
+    /// Returns the edge config where each nest level has the given width.
+    fn edge_with_indent(self, indent: usize) -> Result<EdgeConfig, ThemeError> {
+        let [tee, corner, horizontal, vertical] = match self.ruled_chars() {
+            Some(chars) if indent >= 2 => chars,
+            _ => {
+                return Err(ThemeError::InvalidIndent {
+                    edges: self,
+                    indent,
+                })
+            }
+        };
+        let line = horizontal.to_string().repeat(indent - 2);
+        let edge = CustomEdgeConfigBuilder::new()
+            .branch(format!("{}{}", tee, line))
+            .last_branch(format!("{}{}", corner, line))
+            .branch_padding(" ")
+            .continuation(vertical)
+            .continuation_padding(" ".repeat(indent - 1))
+            .last_continuation("")
+            .last_continuation_padding(" ".repeat(indent))
+            .build()
+            .expect("Should never fail: ruled characters are single width");
+        Ok(EdgeConfig::Custom(edge))
+    }
+}
+
+/// Named set of styles for trees.
+///
+/// Themes are either [built-in][`Theme::builtin()`] or loaded from JSON and TOML documents
+/// (`serde` and `toml` features).
+/// A theme document is a table of themes keyed by their names, where each theme has the
+/// following optional fields:
+///
+/// * `edges`: Character set of edges. See [`EdgeCharset`] for the values.
+/// * `indent`: Width of each nest level, at least 2. Not available for `unicode-double`,
+///   `bullet`, `environment`, `dos`, and `markdown` edges.
+/// * `edge-color`: Color of edges, such as `"blue"` and `"bright-black"` (`ansi` feature).
+/// * `rainbow-edges`: Colors of edges cycled by depth (`ansi` feature).
+/// * `max-width`: Maximum display width of output lines.
+/// * `max-label-width`: Maximum display width of each line of labels.
+/// * `label-overflow`: `"truncate"`, `"word-wrap"`, or `"hard-wrap"`.
+///
+/// Unknown fields are rejected.
+///
+/// [`Theme::builtin()`]: #method.builtin
+/// [`EdgeCharset`]: enum.EdgeCharset.html
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields, rename_all = "kebab-case")
+)]
+pub struct Theme {
+    /// Character set of edges.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    edges: Option<EdgeCharset>,
+    /// Width of each nest level.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    indent: Option<usize>,
+    /// Color of edges.
+    #[cfg(feature = "ansi")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    edge_color: Option<Color>,
+    /// Colors of edges cycled by depth.
+    #[cfg(feature = "ansi")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    rainbow_edges: Vec<Color>,
+    /// Maximum display width of output lines.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    max_width: Option<usize>,
+    /// Maximum display width of each line of labels.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    max_label_width: Option<usize>,
+    /// How to handle lines of labels longer than the limits.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    label_overflow: Option<LabelOverflow>,
+}
+
+impl Theme {
+    /// Names of the built-in themes.
+    pub const BUILTIN_NAMES: &'static [&'static str] = &[
+        "ascii", "unicode", "rounded", "bold", "double", "dos", "markdown",
+    ];
+
+    /// Returns the built-in theme with the given name.
+    ///
+    /// See [`Theme::BUILTIN_NAMES`] for the available names.
+    /// Each built-in theme only sets the edges with the same name, or `EdgeCharset::Dos` and
+    /// `EdgeCharset::Markdown` for `"dos"` and `"markdown"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{ItemStyle, Theme, TreePrinter};
+    ///
+    /// let theme = Theme::builtin("dos").expect("built-in theme");
+    ///
+    /// let mut printer = TreePrinter::new(String::new(), theme.to_tree_config()?);
+    /// printer.open_node(ItemStyle::by_depth(false), "foo")?;
+    /// printer.open_node(ItemStyle::by_depth(true), "bar")?;
+    /// printer.close_node()?;
+    /// printer.close_node()?;
+    /// printer.open_node(ItemStyle::by_depth(true), "baz")?;
+    /// let got = printer.finalize()?;
+    ///
+    /// assert_eq!(got, "+---foo\n|   \\---bar\n\\---baz\n");
+    /// assert!(Theme::builtin("no-such-theme").is_none());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`Theme::BUILTIN_NAMES`]: #associatedconstant.BUILTIN_NAMES
+    pub fn builtin(name: &str) -> Option<Self> {
+        let edges = match name {
+            "ascii" => EdgeCharset::Ascii,
+            "unicode" => EdgeCharset::Unicode,
+            "rounded" => EdgeCharset::Rounded,
+            "bold" => EdgeCharset::Bold,
+            "double" => EdgeCharset::Double,
+            "dos" => EdgeCharset::Dos,
+            "markdown" => EdgeCharset::Markdown,
+            _ => return None,
+        };
+        Some(Self {
+            edges: Some(edges),
+            ..Self::default()
+        })
+    }
+
+    /// Loads the theme with the given name from the JSON theme document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{ItemStyle, Theme, TreePrinter};
+    ///
+    /// let doc = r#"{
+    ///     "plain": { "edges": "ascii" },
+    ///     "wide": { "edges": "rounded", "indent": 6, "max-width": 20 }
+    /// }"#;
+    /// let theme = Theme::from_json_named(doc, "wide")?;
+    ///
+    /// let mut printer = TreePrinter::new(String::new(), theme.to_tree_config()?);
+    /// printer.open_node(ItemStyle::by_depth(false), "foo")?;
+    /// printer.open_node(ItemStyle::by_depth(true), "a long label to be truncated")?;
+    /// printer.close_node()?;
+    /// printer.close_node()?;
+    /// printer.open_node(ItemStyle::by_depth(true), "bar")?;
+    /// let got = printer.finalize()?;
+    ///
+    /// let expected = "\u{251C}\u{2500}\u{2500}\u{2500}\u{2500} foo\n\
+    ///                 \u{2502}     \u{2570}\u{2500}\u{2500}\u{2500}\u{2500} a long \u{2026}\n\
+    ///                 \u{2570}\u{2500}\u{2500}\u{2500}\u{2500} bar\n";
+    /// assert_eq!(got, expected);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_json_named(doc: &str, name: &str) -> Result<Self, ThemeError> {
+        let mut themes: BTreeMap<String, Self> = serde_json::from_str(doc)?;
+        themes
+            .remove(name)
+            .ok_or_else(|| ThemeError::NotFound(name.to_owned()))
+    }
+
+    /// Loads the theme with the given name from the TOML theme document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{ItemStyle, Theme, TreePrinter};
+    ///
+    /// let doc = r#"
+    ///     [compact]
+    ///     edges = "compact-ascii"
+    ///     max-width = 8
+    ///     label-overflow = "word-wrap"
+    /// "#;
+    /// let theme = Theme::from_toml_named(doc, "compact")?;
+    ///
+    /// let mut printer = TreePrinter::new(String::new(), theme.to_tree_config()?);
+    /// printer.open_node(ItemStyle::by_depth(true), "foo bar baz")?;
+    /// let got = printer.finalize()?;
+    ///
+    /// assert_eq!(got, "` foo\n  bar\n  baz\n");
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml_named(doc: &str, name: &str) -> Result<Self, ThemeError> {
+        let mut themes: BTreeMap<String, Self> = toml::from_str(doc)?;
+        themes
+            .remove(name)
+            .ok_or_else(|| ThemeError::NotFound(name.to_owned()))
+    }
+
+    /// Returns a `TreeConfigBuilder` with the styles of the theme applied.
+    ///
+    /// Edges are set as the [depth edges][depth_edges] for all depths, so they are used by
+    /// [`ItemStyle::by_depth()`] and by retained trees without fixed edges.
+    ///
+    /// Returns an error if the indent width is not available for the edges.
+    ///
+    /// [depth_edges]: struct.TreeConfigBuilder.html#method.depth_edges
+    /// [`ItemStyle::by_depth()`]: struct.ItemStyle.html#method.by_depth
+    pub fn tree_config_builder(&self) -> Result<TreeConfigBuilder, ThemeError> {
+        let mut builder = TreeConfigBuilder::new();
+        let charset = self.edges.unwrap_or_default();
+        let edge = match self.indent {
+            Some(indent) => charset.edge_with_indent(indent)?,
+            None => charset.edge(),
+        };
+        builder
+            .depth_edges(vec![edge])
+            .max_width(self.max_width)
+            .max_label_width(self.max_label_width);
+        if let Some(overflow) = self.label_overflow {
+            builder.label_overflow(overflow);
+        }
+        #[cfg(feature = "ansi")]
+        builder
+            .edge_color(self.edge_color)
+            .rainbow_edges(self.rainbow_edges.clone());
+        Ok(builder)
+    }
+
+    /// Returns a `TreeConfig` with the styles of the theme.
+    ///
+    /// Returns an error if the indent width is not available for the edges.
+    pub fn to_tree_config(&self) -> Result<TreeConfig, ThemeError> {
+        Ok(self.tree_config_builder()?.build())
+    }
+}
+
+/// Error for loading themes.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ThemeError {
+    /// Invalid JSON document.
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    /// Invalid TOML document.
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    /// Theme with the name is not found.
+    NotFound(String),
+    /// Indent width is not available for the edges.
+    InvalidIndent {
+        /// Character set of edges.
+        edges: EdgeCharset,
+        /// Indent width.
+        indent: usize,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "serde")]
+            Self::Json(e) => write!(f, "Invalid JSON theme document: {}", e),
+            #[cfg(feature = "toml")]
+            Self::Toml(e) => write!(f, "Invalid TOML theme document: {}", e),
+            Self::NotFound(name) => write!(f, "Theme `{}` is not found", name),
+            Self::InvalidIndent { edges, indent } => write!(
+                f,
+                "Indent width {} is not available for edges {:?}",
+                indent, edges
+            ),
+        }
+    }
+}
+
+impl error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "serde")]
+            Self::Json(e) => Some(e),
+            #[cfg(feature = "toml")]
+            Self::Toml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ThemeError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for ThemeError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{config::ItemStyle, tree_printer::TreePrinter};
+
+    /// Prints a small tree with the theme.
+    fn print(theme: &Theme) -> String {
+        let mut printer = TreePrinter::new(String::new(), theme.to_tree_config().unwrap());
+        printer
+            .open_node(ItemStyle::by_depth(false), "foo")
+            .unwrap();
+        printer.open_node(ItemStyle::by_depth(true), "bar").unwrap();
+        printer.close_node().unwrap();
+        printer.close_node().unwrap();
+        printer.open_node(ItemStyle::by_depth(true), "baz").unwrap();
+        printer.finalize().unwrap()
+    }
+
+    #[test]
+    fn builtins() {
+        for name in Theme::BUILTIN_NAMES {
+            let theme = Theme::builtin(name).expect("built-in theme");
+            assert!(theme.to_tree_config().is_ok(), "{}", name);
+        }
+        let markdown = Theme::builtin("markdown").expect("built-in theme");
+        assert_eq!(print(&markdown), "- foo\n  - bar\n- baz\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn indent_widths() -> Result<(), ThemeError> {
+        let doc = r#"{
+            "narrow": { "indent": 2 },
+            "same": { "edges": "bold", "indent": 4 },
+            "invalid": { "edges": "bullet", "indent": 3 }
+        }"#;
+        let narrow = Theme::from_json_named(doc, "narrow")?;
+        assert_eq!(print(&narrow), "| foo\n| ` bar\n` baz\n");
+        let same = Theme::from_json_named(doc, "same")?;
+        assert_eq!(
+            print(&same),
+            print(&Theme {
+                edges: Some(EdgeCharset::Bold),
+                ..Theme::default()
+            })
+        );
+        let invalid = Theme::from_json_named(doc, "invalid")?;
+        assert!(matches!(
+            invalid.to_tree_config(),
+            Err(ThemeError::InvalidIndent { indent: 3, .. })
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_errors() {
+        let doc = r#"{ "plain": { "edges": "ascii", "edge-colour": "red" } }"#;
+        assert!(matches!(
+            Theme::from_json_named(doc, "plain"),
+            Err(ThemeError::Json(_))
+        ));
+        assert!(matches!(
+            Theme::from_json_named("{}", "plain"),
+            Err(ThemeError::NotFound(name)) if name == "plain"
+        ));
+    }
+
+    #[cfg(all(feature = "serde", feature = "ansi"))]
+    #[test]
+    fn colors() -> Result<(), ThemeError> {
+        let doc = r#"{ "blue": { "edge-color": "bright-blue" } }"#;
+        let theme = Theme::from_json_named(doc, "blue")?;
+        assert_eq!(theme.edge_color, Some(Color::BrightBlue));
+        assert_eq!(
+            print(&theme),
+            "\x1b[94m|--\x1b[39m foo\n\x1b[94m|\x1b[39m   \x1b[94m`--\x1b[39m bar\n\
+             \x1b[94m`--\x1b[39m baz\n"
+        );
+        Ok(())
+    }
+}
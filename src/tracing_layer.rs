@@ -0,0 +1,298 @@
+//! `tracing-subscriber` layer printing span trees.
+
+use std::{
+    fmt::{self, Write as _},
+    time::{Duration, Instant},
+};
+
+use tracing_core::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    registry::LookupSpan,
+};
+
+use crate::{config::RenderConfig, tree_node::TreeNode};
+
+/// Layer which renders the trees of spans when the root spans are closed.
+///
+/// Each span is labeled with its name and fields, such as `request{id=42 path="/"}`, and
+/// annotated with the duration from its creation to its closing.
+/// The durations of the root spans are shown after their labels.
+/// Children are sorted by their creation times.
+///
+/// The rendered trees are passed to the output function, and the spans are forgotten.
+///
+/// The layer requires the subscriber to implement `LookupSpan`, such as
+/// `tracing_subscriber::Registry`.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+///
+/// use plaintextree::SpanTreeLayer;
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let output = Arc::new(Mutex::new(String::new()));
+/// let sink = Arc::clone(&output);
+/// let layer = SpanTreeLayer::new(move |tree: &str| sink.lock().unwrap().push_str(tree))
+///     // Durations vary between runs.
+///     .with_durations(false);
+/// let subscriber = tracing_subscriber::registry().with(layer);
+///
+/// tracing::subscriber::with_default(subscriber, || {
+///     let _request = tracing::info_span!("request", id = 42).entered();
+///     for path in ["/index.html", "/style.css"] {
+///         let _fetch = tracing::info_span!("fetch", path).entered();
+///     }
+///     let _render = tracing::info_span!("render").entered();
+/// });
+///
+/// let expected = "\
+/// request{id=42}
+/// |-- fetch{path=\"/index.html\"}
+/// |-- fetch{path=\"/style.css\"}
+/// `-- render
+/// ";
+/// assert_eq!(*output.lock().unwrap(), expected);
+/// ```
+pub struct SpanTreeLayer<F> {
+    /// Output function.
+    output: F,
+    /// Render config.
+    config: RenderConfig,
+    /// Whether to annotate spans with their durations.
+    durations: bool,
+    /// Whether to show the fields of spans.
+    fields: bool,
+}
+
+impl SpanTreeLayer<fn(&str)> {
+    /// Creates a new `SpanTreeLayer` printing trees to the standard error.
+    pub fn stderr() -> Self {
+        Self::new(|tree| eprint!("{}", tree))
+    }
+}
+
+impl<F: Fn(&str) + Send + Sync + 'static> SpanTreeLayer<F> {
+    /// Creates a new `SpanTreeLayer` which passes the rendered trees to the given function.
+    pub fn new(output: F) -> Self {
+        Self {
+            output,
+            config: RenderConfig::new(),
+            durations: true,
+            fields: true,
+        }
+    }
+
+    /// Sets the render config.
+    ///
+    /// Default is `RenderConfig::new()`.
+    pub fn with_render_config(mut self, config: RenderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets whether to annotate spans with their durations.
+    ///
+    /// Default is `true`.
+    pub fn with_durations(mut self, v: bool) -> Self {
+        self.durations = v;
+        self
+    }
+
+    /// Sets whether to show the fields of spans.
+    ///
+    /// Default is `true`.
+    pub fn with_fields(mut self, v: bool) -> Self {
+        self.fields = v;
+        self
+    }
+}
+
+impl<F> fmt::Debug for SpanTreeLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanTreeLayer")
+            .field("config", &self.config)
+            .field("durations", &self.durations)
+            .field("fields", &self.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Span data stored in the span extensions.
+#[derive(Debug)]
+struct SpanData {
+    /// Creation time.
+    start: Instant,
+    /// Formatted fields, such as `id=42 path="/"`.
+    fields: String,
+    /// Closed children with their creation times.
+    children: Vec<(Instant, TreeNode)>,
+}
+
+impl Visit for SpanData {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &value)
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        write!(self.fields, "{}={:?}", field.name(), value)
+            .expect("Should never fail: writing to `String`");
+    }
+}
+
+impl<S, F> Layer<S> for SpanTreeLayer<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx
+            .span(id)
+            .expect("Should never fail: the span is just created");
+        let mut data = SpanData {
+            start: Instant::now(),
+            fields: String::new(),
+            children: Vec::new(),
+        };
+        if self.fields {
+            attrs.record(&mut data);
+        }
+        span.extensions_mut().insert(data);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if !self.fields {
+            return;
+        }
+        let span = ctx
+            .span(id)
+            .expect("Should never fail: the span is not closed");
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SpanData>() {
+            values.record(data);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx
+            .span(&id)
+            .expect("Should never fail: the span is being closed");
+        let data = match span.extensions_mut().remove::<SpanData>() {
+            Some(data) => data,
+            None => return,
+        };
+        let label = if data.fields.is_empty() {
+            span.name().to_owned()
+        } else {
+            format!("{}{{{}}}", span.name(), data.fields)
+        };
+        let mut node = TreeNode::new(label);
+        if self.durations {
+            node.set_annotation(Some(format_duration(data.start.elapsed())));
+        }
+        let mut children = data.children;
+        children.sort_by_key(|(start, _)| *start);
+        *node.children_mut() = children.into_iter().map(|(_, child)| child).collect();
+
+        match span.parent() {
+            Some(parent) => {
+                let mut extensions = parent.extensions_mut();
+                if let Some(parent_data) = extensions.get_mut::<SpanData>() {
+                    parent_data.children.push((data.start, node));
+                }
+            }
+            None => {
+                // Annotations of roots are not rendered.
+                if let Some(duration) = node.annotation().map(str::to_owned) {
+                    node.set_label(format!("{}  {}", node.label(), duration));
+                }
+                // Layers have nowhere to report errors, and rendering into `String` only fails
+                // when the render config is unsatisfiable.
+                if let Ok(tree) = node.render(String::new(), &self.config) {
+                    (self.output)(&tree);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the human-readable duration, rounded to three significant digits below 1000 s.
+fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    let (value, unit) = if nanos < 1_000 {
+        return format!("{}ns", nanos);
+    } else if nanos < 1_000_000 {
+        (nanos as f64 / 1e3, "\u{b5}s")
+    } else if nanos < 1_000_000_000 {
+        (nanos as f64 / 1e6, "ms")
+    } else {
+        (nanos as f64 / 1e9, "s")
+    };
+    let precision = if value < 10.0 {
+        2
+    } else if value < 100.0 {
+        1
+    } else {
+        0
+    };
+    format!("{:.*}{}", precision, value, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn durations() {
+        let cases = [
+            (Duration::from_nanos(999), "999ns"),
+            (Duration::from_nanos(1_500), "1.50\u{b5}s"),
+            (Duration::from_micros(12_345), "12.3ms"),
+            (Duration::from_millis(999_400), "999s"),
+            (Duration::from_secs(3600), "3600s"),
+        ];
+        for (duration, expected) in cases {
+            assert_eq!(format_duration(duration), expected);
+        }
+    }
+
+    #[test]
+    fn recorded_fields_and_roots() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&output);
+        let layer = SpanTreeLayer::new(move |tree: &str| {
+            sink.lock().expect("not poisoned").push(tree.to_owned())
+        });
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let job = tracing::info_span!("job", status = tracing::field::Empty);
+            job.in_scope(|| {
+                let _step = tracing::info_span!("step").entered();
+            });
+            job.record("status", "done");
+            drop(job);
+            let _other = tracing::info_span!("other").entered();
+        });
+
+        let output = output.lock().expect("not poisoned");
+        assert_eq!(output.len(), 2);
+        let (root, child) = output[0].split_once('\n').expect("has a child line");
+        assert!(root.starts_with("job{status=\"done\"}  "), "{:?}", root);
+        assert!(child.starts_with("`-- step  "), "{:?}", child);
+        assert!(output[1].starts_with("other  "), "{:?}", output[1]);
+    }
+}
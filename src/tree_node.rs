@@ -0,0 +1,2985 @@
+//! Retained tree.
+
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fmt::{self, Write},
+    iter::Peekable,
+    mem, vec,
+};
+
+use crate::{
+    canvas::{Canvas, VerticalAlign},
+    chart::Block,
+    config::{
+        BoxChars, ColumnAlign, ItemStyle, RenderConfig, TraversalOrder, TreeConfig, TreeLayout,
+    },
+    item_writer::{FIRST_STRONG_ISOLATE, POP_DIRECTIONAL_ISOLATE},
+    mirror,
+    quote::quote_label,
+    tree_printer::{split_lines, Error, Result, TreePrinter},
+};
+
+/// Conflict handling for [`TreeNode::merge()`].
+///
+/// [`TreeNode::merge()`]: struct.TreeNode.html#method.merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// Keeps the label of `self`.
+    KeepSelf,
+    /// Keeps the label of the other tree.
+    KeepOther,
+    /// Joins both labels with the given separator.
+    Join(String),
+}
+
+/// Highlight style for [`TreeNode::highlight()`].
+///
+/// [`TreeNode::highlight()`]: struct.TreeNode.html#method.highlight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Highlight {
+    /// Reverse video using ANSI escape sequences.
+    Ansi,
+    /// Wraps labels with the given markers, such as `>>> ` and ` <<<`.
+    Wrap {
+        /// Marker put before the label.
+        open: String,
+        /// Marker put after the label.
+        close: String,
+    },
+}
+
+impl Highlight {
+    /// Creates a new `Highlight::Wrap`.
+    pub fn wrap(open: impl Into<String>, close: impl Into<String>) -> Self {
+        Self::Wrap {
+            open: open.into(),
+            close: close.into(),
+        }
+    }
+
+    /// Returns the markers put before and after the label.
+    fn markers(&self) -> (&str, &str) {
+        match self {
+            Self::Ansi => ("\x1b[7m", "\x1b[27m"),
+            Self::Wrap { open, close } => (open, close),
+        }
+    }
+
+    /// Returns the highlighted label.
+    ///
+    /// Each non-empty line is highlighted separately, so that the markers never enclose tree
+    /// prefixes.
+    fn apply(&self, label: &str) -> String {
+        let (open, close) = self.markers();
+        label
+            .split('\n')
+            .map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}{}{}", open, line, close)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A node of a retained tree.
+///
+/// Unlike [`TreePrinter`], a retained tree knows the whole structure before rendering, so it
+/// can be rendered with options which require lookahead.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{RenderConfig, TreeNode};
+///
+/// let mut root = TreeNode::new(".");
+/// root.push_child(TreeNode::new("foo"));
+/// root.children_mut()[0].push_child(TreeNode::new("bar"));
+/// root.push_child(TreeNode::new("baz"));
+///
+/// let got = root.render(String::new(), &RenderConfig::new())?;
+///
+/// let expected = ".\n\
+///                 |-- foo\n\
+///                 |   `-- bar\n\
+///                 `-- baz\n";
+/// assert_eq!(got, expected);
+/// # plaintextree::Result::Ok(())
+/// ```
+///
+/// [`TreePrinter`]: struct.TreePrinter.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeNode {
+    /// Label.
+    label: String,
+    /// Annotation shown at the right of the label.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    annotation: Option<String>,
+    /// Metadata cells shown after the label and the annotation.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    metadata: Vec<String>,
+    /// Target of the alias, shown after the label with an arrow.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    alias_target: Option<String>,
+    /// Child nodes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    children: Vec<TreeNode>,
+    /// Whether to reverse the order of the children, overriding the render config.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reverse_children: Option<bool>,
+    /// Whether the node is collapsed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    collapsed: bool,
+    /// Whether the node is marked as the target of the highlighted path.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    marked: bool,
+}
+
+impl TreeNode {
+    /// Creates a new leaf node.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            annotation: None,
+            metadata: Vec::new(),
+            alias_target: None,
+            children: Vec::new(),
+            reverse_children: None,
+            collapsed: false,
+            marked: false,
+        }
+    }
+
+    /// Creates a new alias node, which is rendered as `name -> target`.
+    ///
+    /// This is same as creating a node by `TreeNode::new(name)` and setting the target by
+    /// [`set_alias_target()`][set_alias_target].
+    ///
+    /// [set_alias_target]: #method.set_alias_target
+    pub fn alias(name: impl Into<String>, target: impl Into<String>) -> Self {
+        let mut node = Self::new(name);
+        node.alias_target = Some(target.into());
+        node
+    }
+
+    /// Returns the label.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Sets the label.
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    /// Returns the child nodes.
+    pub fn children(&self) -> &[TreeNode] {
+        &self.children
+    }
+
+    /// Returns the mutable reference to the child nodes.
+    pub fn children_mut(&mut self) -> &mut Vec<TreeNode> {
+        &mut self.children
+    }
+
+    /// Adds a child node.
+    pub fn push_child(&mut self, child: TreeNode) -> &mut Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Sets whether to reverse the order of the children when rendered.
+    ///
+    /// `None` means following the [render config][`RenderConfigBuilder::reverse_children()`],
+    /// and this is the default.
+    /// Only the direct children of this node are affected.
+    ///
+    /// [`RenderConfigBuilder::reverse_children()`]:
+    /// struct.RenderConfigBuilder.html#method.reverse_children
+    pub fn set_reverse_children(&mut self, reverse: Option<bool>) {
+        self.reverse_children = reverse;
+    }
+
+    /// Returns whether the node is collapsed.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Sets whether the node is collapsed.
+    ///
+    /// A collapsed node is rendered as a single line with a suffix telling the number of hidden
+    /// descendants (see [`RenderConfigBuilder::collapsed_suffix()`]), and its descendants are not
+    /// rendered.
+    /// The collapsed state is ignored when [`RenderConfigBuilder::expand_all()`] is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut deps = TreeNode::new("node_modules");
+    /// deps.push_child(TreeNode::new("left-pad"))
+    ///     .push_child(TreeNode::new("is-odd"));
+    /// deps.set_collapsed(true);
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(deps).push_child(TreeNode::new("index.js"));
+    ///
+    /// assert_eq!(
+    ///     root.to_string(),
+    ///     ".\n|-- node_modules \u{2026} (2 hidden)\n`-- index.js\n"
+    /// );
+    ///
+    /// let config = RenderConfigBuilder::new().expand_all(true).build();
+    /// assert_eq!(
+    ///     root.render(String::new(), &config)?,
+    ///     ".\n|-- node_modules\n|   |-- left-pad\n|   `-- is-odd\n`-- index.js\n"
+    /// );
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`RenderConfigBuilder::collapsed_suffix()`]:
+    /// struct.RenderConfigBuilder.html#method.collapsed_suffix
+    /// [`RenderConfigBuilder::expand_all()`]: struct.RenderConfigBuilder.html#method.expand_all
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    /// Returns whether the node is marked.
+    pub fn is_marked(&self) -> bool {
+        self.marked
+    }
+
+    /// Marks the node as a target, so that the path from the root to the node is highlighted.
+    ///
+    /// The connectors of the node and its ancestors are [emphasized][emphasized], and colored
+    /// with [`RenderConfigBuilder::marked_path_color()`] if the `ansi` feature is enabled.
+    /// This is useful to show "you are here" in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.push_child(TreeNode::new("lib.rs"))
+    ///     .push_child(TreeNode::new("main.rs"));
+    /// src.children_mut()[1].set_marked(true);
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(TreeNode::new("Cargo.toml"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .edge(EdgeConfig::UnicodeSingleWidth)
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 \u{251D}\u{2501}\u{2501} src\n\
+    ///                 \u{2502}   \u{251C}\u{2500}\u{2500} lib.rs\n\
+    ///                 \u{2502}   \u{2515}\u{2501}\u{2501} main.rs\n\
+    ///                 \u{2514}\u{2500}\u{2500} Cargo.toml\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [emphasized]: struct.ItemStyle.html#method.emphasized
+    /// [`RenderConfigBuilder::marked_path_color()`]:
+    /// struct.RenderConfigBuilder.html#method.marked_path_color
+    pub fn set_marked(&mut self, marked: bool) {
+        self.marked = marked;
+    }
+
+    /// Returns whether the node or any of its descendants is marked.
+    fn is_on_marked_path(&self) -> bool {
+        self.marked || self.children.iter().any(TreeNode::is_on_marked_path)
+    }
+
+    /// Returns the annotation.
+    pub fn annotation(&self) -> Option<&str> {
+        self.annotation.as_deref()
+    }
+
+    /// Sets the annotation shown at the right of the first line of the label.
+    ///
+    /// Annotations in the tree are aligned to the same column, and the space before them is
+    /// filled with [`RenderConfigBuilder::annotation_leader()`] or
+    /// [`TreeConfigBuilder::annotation_leader()`].
+    /// In breadth-first order, annotations are put after two spaces without alignment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut src = TreeNode::new("src");
+    /// src.set_annotation(Some("12 KB"));
+    /// src.push_child(TreeNode::new("lib.rs"));
+    /// src.children_mut()[0].set_annotation(Some("1.2 MB"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(src).push_child(TreeNode::new("Cargo.toml"));
+    ///
+    /// let config = RenderConfigBuilder::new().annotation_leader('.').build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 |-- src ......... 12 KB\n\
+    ///                 |   `-- lib.rs .. 1.2 MB\n\
+    ///                 `-- Cargo.toml\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`RenderConfigBuilder::annotation_leader()`]:
+    /// struct.RenderConfigBuilder.html#method.annotation_leader
+    /// [`TreeConfigBuilder::annotation_leader()`]:
+    /// struct.TreeConfigBuilder.html#method.annotation_leader
+    pub fn set_annotation(&mut self, annotation: Option<impl Into<String>>) {
+        self.annotation = annotation.map(Into::into);
+    }
+
+    /// Returns the target of the alias.
+    pub fn alias_target(&self) -> Option<&str> {
+        self.alias_target.as_deref()
+    }
+
+    /// Sets the target of the alias, such as the destination of a symbolic link.
+    ///
+    /// The target is put after the first line of the label with an arrow (`name -> target`).
+    /// The label is still treated as the name of the node: kind suffixes are put before the
+    /// arrow, and label operations such as [`highlight_by()`] and [`merge()`] do not see the
+    /// target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{NodeKind, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut lib = TreeNode::new("lib");
+    /// lib.set_alias_target(Some("/usr/lib"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(lib)
+    ///     .push_child(TreeNode::alias("latest", "v1.2.0"));
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .classify(Some(|node| node.alias_target().map(|_| NodeKind::Symlink)))
+    ///     .build();
+    /// let got = root.render(String::new(), &config)?;
+    ///
+    /// let expected = ".\n\
+    ///                 |-- lib@ -> /usr/lib\n\
+    ///                 `-- latest@ -> v1.2.0\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [`highlight_by()`]: #method.highlight_by
+    /// [`merge()`]: #method.merge
+    pub fn set_alias_target(&mut self, target: Option<impl Into<String>>) {
+        self.alias_target = target.map(Into::into);
+    }
+
+    /// Returns the metadata cells.
+    pub fn metadata(&self) -> &[String] {
+        &self.metadata
+    }
+
+    /// Sets the metadata cells shown after the label and the annotation.
+    ///
+    /// Metadata of the nodes in the tree are laid out as a table, whose columns are
+    /// configured by [`RenderConfigBuilder::metadata_columns()`].
+    /// In breadth-first order, cells are put after two spaces without alignment.
+    ///
+    /// [`RenderConfigBuilder::metadata_columns()`]:
+    /// struct.RenderConfigBuilder.html#method.metadata_columns
+    pub fn set_metadata<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.metadata = cells.into_iter().map(Into::into).collect();
+    }
+
+    /// Returns whether the node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns the number of nodes in the tree, including the node itself.
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(TreeNode::node_count)
+            .sum::<usize>()
+    }
+
+    /// Replaces the descendant subtrees matching the predicate with placeholder nodes.
+    ///
+    /// Placeholders are labelled like `<elided: 42 nodes>`, where the number is the count of the
+    /// nodes in the removed subtree.
+    /// The node itself is never replaced, and descendants of a replaced subtree are not visited.
+    ///
+    /// Returns the total number of elided nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::TreeNode;
+    ///
+    /// let mut secrets = TreeNode::new("secrets");
+    /// secrets
+    ///     .push_child(TreeNode::new("api-key"))
+    ///     .push_child(TreeNode::new("password"));
+    /// let mut root = TreeNode::new("config");
+    /// root.push_child(TreeNode::new("name")).push_child(secrets);
+    ///
+    /// let elided = root.prune(|node| node.label() == "secrets");
+    ///
+    /// assert_eq!(elided, 3);
+    /// assert_eq!(root.to_string(), "config\n|-- name\n`-- <elided: 3 nodes>\n");
+    /// ```
+    pub fn prune(&mut self, mut predicate: impl FnMut(&TreeNode) -> bool) -> usize {
+        self.prune_with(&mut predicate, |count| match count {
+            1 => "<elided: 1 node>".to_owned(),
+            count => format!("<elided: {} nodes>", count),
+        })
+    }
+
+    /// Replaces the descendant subtrees matching the predicate with placeholder nodes, using the
+    /// given function to create the placeholder labels.
+    ///
+    /// The placeholder function receives the number of nodes in the removed subtree.
+    ///
+    /// Returns the total number of elided nodes.
+    ///
+    /// See [`prune()`] for details.
+    ///
+    /// [`prune()`]: #method.prune
+    pub fn prune_with(
+        &mut self,
+        mut predicate: impl FnMut(&TreeNode) -> bool,
+        mut placeholder: impl FnMut(usize) -> String,
+    ) -> usize {
+        self.prune_impl(&mut predicate, &mut placeholder)
+    }
+
+    /// Replaces the descendant subtrees matching the predicate with placeholder nodes.
+    fn prune_impl(
+        &mut self,
+        predicate: &mut dyn FnMut(&TreeNode) -> bool,
+        placeholder: &mut dyn FnMut(usize) -> String,
+    ) -> usize {
+        let mut elided = 0;
+        for child in &mut self.children {
+            if predicate(child) {
+                let count = child.node_count();
+                *child = TreeNode::new(placeholder(count));
+                elided += count;
+            } else {
+                elided += child.prune_impl(predicate, placeholder);
+            }
+        }
+        elided
+    }
+
+    /// Highlights the labels containing the given pattern.
+    ///
+    /// Non-matching nodes are kept as is, so the surrounding structure is still shown.
+    ///
+    /// Returns the number of highlighted nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{Highlight, TreeNode};
+    ///
+    /// let mut root = TreeNode::new("src");
+    /// root.push_child(TreeNode::new("main.rs"))
+    ///     .push_child(TreeNode::new("lib.rs"));
+    ///
+    /// let count = root.highlight("lib", &Highlight::wrap(">>> ", " <<<"));
+    ///
+    /// assert_eq!(count, 1);
+    /// assert_eq!(root.to_string(), "src\n|-- main.rs\n`-- >>> lib.rs <<<\n");
+    /// ```
+    pub fn highlight(&mut self, pattern: &str, style: &Highlight) -> usize {
+        self.highlight_by(|label| label.contains(pattern), style)
+    }
+
+    /// Highlights the labels for which the matcher returns `true`.
+    ///
+    /// Returns the number of highlighted nodes.
+    ///
+    /// See [`highlight()`] for details.
+    ///
+    /// [`highlight()`]: #method.highlight
+    pub fn highlight_by(
+        &mut self,
+        mut matcher: impl FnMut(&str) -> bool,
+        style: &Highlight,
+    ) -> usize {
+        self.highlight_impl(&mut matcher, style)
+    }
+
+    /// Highlights the labels for which the matcher returns `true`.
+    fn highlight_impl(
+        &mut self,
+        matcher: &mut dyn FnMut(&str) -> bool,
+        style: &Highlight,
+    ) -> usize {
+        let mut count = 0;
+        if matcher(&self.label) {
+            self.label = style.apply(&self.label);
+            count += 1;
+        }
+        for child in &mut self.children {
+            count += child.highlight_impl(matcher, style);
+        }
+        count
+    }
+
+    /// Merges two trees and returns the union of them.
+    ///
+    /// Children with the same label are merged recursively, and children only in `other` are
+    /// appended after the children of `self`.
+    /// As children are matched by their labels, only the roots can have different labels at
+    /// the same position; `conflict` decides the label of the merged root in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{MergeConflict, TreeNode};
+    ///
+    /// let mut shard1 = TreeNode::new("shard1");
+    /// shard1.push_child(TreeNode::new("users"));
+    /// shard1.children_mut()[0].push_child(TreeNode::new("alice"));
+    /// let mut shard2 = TreeNode::new("shard2");
+    /// shard2.push_child(TreeNode::new("users"));
+    /// shard2.children_mut()[0].push_child(TreeNode::new("bob"));
+    /// shard2.push_child(TreeNode::new("groups"));
+    ///
+    /// let merged = shard1.merge(&shard2, &MergeConflict::Join(" + ".to_owned()));
+    ///
+    /// let expected = "shard1 + shard2\n\
+    ///                 |-- users\n\
+    ///                 |   |-- alice\n\
+    ///                 |   `-- bob\n\
+    ///                 `-- groups\n";
+    /// assert_eq!(merged.to_string(), expected);
+    /// ```
+    pub fn merge(&self, other: &TreeNode, conflict: &MergeConflict) -> TreeNode {
+        let label = if self.label == other.label {
+            self.label.clone()
+        } else {
+            match conflict {
+                MergeConflict::KeepSelf => self.label.clone(),
+                MergeConflict::KeepOther => other.label.clone(),
+                MergeConflict::Join(sep) => format!("{}{}{}", self.label, sep, other.label),
+            }
+        };
+
+        let mut children = self.children.clone();
+        for other_child in &other.children {
+            match children
+                .iter_mut()
+                .find(|child| child.label == other_child.label)
+            {
+                Some(child) => *child = child.merge(other_child, conflict),
+                None => children.push(other_child.clone()),
+            }
+        }
+
+        Self {
+            label,
+            annotation: self.annotation.clone().or_else(|| other.annotation.clone()),
+            alias_target: self
+                .alias_target
+                .clone()
+                .or_else(|| other.alias_target.clone()),
+            metadata: if self.metadata.is_empty() {
+                other.metadata.clone()
+            } else {
+                self.metadata.clone()
+            },
+            children,
+            reverse_children: self.reverse_children.or(other.reverse_children),
+            collapsed: self.collapsed && other.collapsed,
+            marked: self.marked || other.marked,
+        }
+    }
+
+    /// Computes the structural diff from `self` to `new`, and returns it as a tree.
+    ///
+    /// Children are matched by their labels, preserving their order.
+    /// Removed nodes are placed before added nodes at the same position.
+    /// Each label in the result is prefixed by a marker:
+    ///
+    /// * `+ ` for nodes only in `new`,
+    /// * `- ` for nodes only in `self`,
+    /// * `~ ` for nodes in both trees with changed descendants (or a changed root label), and
+    /// * `  ` (two spaces) for unchanged nodes.
+    ///
+    /// Following lines of multiline labels are indented to align with the first line.
+    /// If the root labels differ, the label of `new` is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::TreeNode;
+    ///
+    /// let mut old = TreeNode::new(".");
+    /// old.push_child(TreeNode::new("src"));
+    /// old.children_mut()[0].push_child(TreeNode::new("main.rs"));
+    /// old.push_child(TreeNode::new("build.rs"));
+    /// let mut new = TreeNode::new(".");
+    /// new.push_child(TreeNode::new("src"));
+    /// new.children_mut()[0].push_child(TreeNode::new("main.rs"));
+    /// new.children_mut()[0].push_child(TreeNode::new("lib.rs"));
+    ///
+    /// let diff = old.diff(&new);
+    ///
+    /// let expected = "~ .\n\
+    ///                 |-- ~ src\n\
+    ///                 |   |--   main.rs\n\
+    ///                 |   `-- + lib.rs\n\
+    ///                 `-- - build.rs\n";
+    /// assert_eq!(diff.to_string(), expected);
+    /// ```
+    pub fn diff(&self, new: &TreeNode) -> TreeNode {
+        let (mut diff, changed) = diff_node(self, new);
+        if self.label != new.label && !changed {
+            diff.label = mark_label('~', &new.label);
+        }
+        diff
+    }
+
+    /// Renders the tree to the given writer, and returns the writer.
+    ///
+    /// The label of the node itself is emitted as the first line without prefixes, and the
+    /// descendants are emitted below it.
+    pub fn render<W: fmt::Write>(&self, mut writer: W, config: &RenderConfig) -> Result<W> {
+        let config = &*config.resolve_color_mode();
+        match config.layout() {
+            TreeLayout::Indented => self.render_indented(writer, config),
+            TreeLayout::Chart => {
+                let chart = chart_block(self, config, &config.chart_box_chars());
+                writer.write_str(&chart.into_string(config.tree_config()))?;
+                Ok(writer)
+            }
+            TreeLayout::Columns => self.render_columns(writer, config),
+        }
+    }
+
+    /// Renders the tree in the indented layout to the given writer, and returns the writer.
+    fn render_indented<W: fmt::Write>(&self, mut writer: W, config: &RenderConfig) -> Result<W> {
+        let tree_config = config.tree_config();
+        let (mut label, ends_with_newline) = self.root_label(config);
+        let body_config = match tree_config.max_lines() {
+            Some(max_lines) => {
+                // The root label is written out of the printer.
+                let root_lines = label
+                    .strip_suffix('\n')
+                    .unwrap_or(&label)
+                    .split('\n')
+                    .count();
+                let mut body_config = config.clone();
+                body_config
+                    .tree_config_mut()
+                    .set_max_lines(Some(max_lines.saturating_sub(root_lines)));
+                Cow::Owned(body_config)
+            }
+            None => Cow::Borrowed(config),
+        };
+        let bottom_up = tree_config.is_bottom_up();
+        let body = if tree_config.is_mirrored() || bottom_up {
+            let mut body = self.render_descendants(String::new(), &body_config)?;
+            if tree_config.is_mirrored() {
+                let (aligned, aligned_body) = mirror::align_root_label(&label, &body, tree_config);
+                label = Cow::Owned(aligned);
+                body = aligned_body;
+            }
+            Some(body)
+        } else {
+            None
+        };
+        let newline = tree_config.newline();
+        if let (true, Some(body)) = (bottom_up, &body) {
+            // Descendants come before the root in bottom-up trees.
+            writer.write_str(body)?;
+            if !body.is_empty() && !body.ends_with(newline) {
+                writer.write_str(newline)?;
+            }
+        }
+        if newline == "\n" {
+            writer.write_str(&label)?;
+        } else {
+            writer.write_str(&label.replace('\n', newline))?;
+        }
+        let has_visible_children = !self.hides_children(config) && !self.children.is_empty();
+        let need_newline =
+            (has_visible_children && !bottom_up) || tree_config.emit_trailing_newline();
+        if need_newline && !ends_with_newline {
+            writer.write_str(newline)?;
+        }
+
+        match body {
+            Some(body) if !bottom_up => {
+                writer.write_str(&body)?;
+                Ok(writer)
+            }
+            Some(_) => Ok(writer),
+            None => self.render_descendants(writer, &body_config),
+        }
+    }
+
+    /// Renders the tree in the multi-column layout to the given writer, and returns the writer.
+    fn render_columns<W: fmt::Write>(&self, mut writer: W, config: &RenderConfig) -> Result<W> {
+        let tree_config = config.tree_config();
+        let children = self.ordered_children(config);
+        if children.is_empty() {
+            return self.render_indented(writer, config);
+        }
+
+        let (label, ends_with_newline) = self.root_label(config);
+        let newline = tree_config.newline();
+        writer.write_str(&label.replace('\n', newline))?;
+        if !ends_with_newline {
+            writer.write_str(newline)?;
+        }
+
+        let cells = children
+            .into_iter()
+            .map(|child| child.render_indented(Canvas::new(tree_config), config))
+            .collect::<Result<Vec<_>>>()?;
+        let columns = flow_columns(
+            &cells,
+            tree_config.max_width().unwrap_or(DEFAULT_FLOW_WIDTH),
+            tree_config,
+        );
+        let canvas = Canvas::side_by_side(&columns, &" ".repeat(COLUMN_GAP), VerticalAlign::Top);
+        for (i, line) in canvas.to_lines().iter().enumerate() {
+            if i != 0 {
+                writer.write_str(newline)?;
+            }
+            writer.write_str(line)?;
+        }
+        if tree_config.emit_trailing_newline() {
+            writer.write_str(newline)?;
+        }
+        Ok(writer)
+    }
+
+    /// Returns the label of the node rendered as the root, and whether it ends with a newline.
+    fn root_label(&self, config: &RenderConfig) -> (Cow<'_, str>, bool) {
+        let (mut label, mut tail_len) = self.label_for_render(Cow::Borrowed(&self.label), config);
+        let tree_config = config.tree_config();
+        if tree_config.quote_labels() {
+            if let Cow::Owned(quoted) = quote_label(&label) {
+                label = Cow::Owned(quoted);
+                // The tail cannot be found in the quoted label.
+                tail_len = 0;
+            }
+        }
+        let max_width = match (tree_config.max_width(), tree_config.max_label_width()) {
+            (Some(max), Some(label_max)) => Some(max.min(label_max)),
+            (max, label_max) => max.or(label_max),
+        };
+        if let Some(max_width) = max_width {
+            label =
+                Cow::Owned(tree_config.fit_lines_with_tail(&label, tail_len, max_width, max_width));
+        }
+        let ends_with_newline = label.ends_with('\n');
+        if tree_config.isolates_bidi() {
+            label = Cow::Owned(isolate_lines(&label));
+        }
+        (label, ends_with_newline)
+    }
+
+    /// Returns an iterator lazily rendering the tree line by line.
+    ///
+    /// The lines are the same as [`render_lines()`][render_lines] returns, but each node is
+    /// rendered only when its lines are requested.
+    /// Mirrored and bottom-up trees, chart layouts, and breadth-first orders need the whole
+    /// output to lay out the lines, so they are rendered at once on the first request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfig, TreeNode};
+    ///
+    /// let mut root = TreeNode::new(".");
+    /// for i in 0..1000 {
+    ///     root.push_child(TreeNode::new(format!("item {}", i)));
+    /// }
+    ///
+    /// let config = RenderConfig::new();
+    /// let page = root.lines(&config).skip(1).take(2).collect::<Vec<_>>();
+    /// assert_eq!(page, ["|-- item 0", "|-- item 1"]);
+    /// ```
+    ///
+    /// [render_lines]: #method.render_lines
+    pub fn lines<'a>(&'a self, config: &'a RenderConfig) -> RenderedLines<'a> {
+        RenderedLines::new(self, config)
+    }
+
+    /// Returns an iterator lazily rendering the tree page by page.
+    ///
+    /// Each page has at most `page_height` lines without newlines.
+    /// The first lines of the ancestors of the first item in a page are repeated at the top of
+    /// the page, so that the page can be read without the previous pages.
+    /// If there is no room for all of them, nearer ancestors are repeated.
+    /// Ancestors are not repeated for the layouts which [`lines()`][lines] renders at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_height` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfig, TreeNode};
+    ///
+    /// let mut foo = TreeNode::new("foo");
+    /// foo.push_child(TreeNode::new("bar"));
+    /// foo.push_child(TreeNode::new("baz"));
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(foo);
+    /// root.push_child(TreeNode::new("qux"));
+    ///
+    /// let config = RenderConfig::new();
+    /// let pages = root.pages(&config, 3).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     pages,
+    ///     [
+    ///         vec![".", "|-- foo", "|   |-- bar"],
+    ///         vec![".", "|-- foo", "|   `-- baz"],
+    ///         vec![".", "`-- qux"],
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [lines]: #method.lines
+    pub fn pages<'a>(&'a self, config: &'a RenderConfig, page_height: usize) -> RenderedPages<'a> {
+        assert!(page_height != 0, "page height should not be zero");
+        RenderedPages {
+            lines: self.lines(config),
+            page_height,
+            open_items: Vec::new(),
+        }
+    }
+
+    /// Renders the tree and returns the lines of the output, without newlines.
+    ///
+    /// The output is split by the newline of the tree config, and the trailing newline does not
+    /// produce an empty line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{RenderConfig, TreeNode};
+    ///
+    /// let mut root = TreeNode::new(".");
+    /// root.push_child(TreeNode::new("foo\n\nfoo2"));
+    /// root.push_child(TreeNode::new("bar"));
+    ///
+    /// let lines = root.render_lines(&RenderConfig::new())?;
+    /// assert_eq!(lines, [".", "|-- foo", "|", "|   foo2", "`-- bar"]);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn render_lines(&self, config: &RenderConfig) -> Result<Vec<String>> {
+        let output = self.render(String::new(), config)?;
+        Ok(split_lines(&output, config.tree_config().newline()))
+    }
+
+    /// Renders the descendants to the given writer as top-level items, and returns the writer.
+    ///
+    /// The label of the node itself is not emitted.
+    pub(crate) fn render_descendants<W: fmt::Write>(
+        &self,
+        writer: W,
+        config: &RenderConfig,
+    ) -> Result<W> {
+        let config = &*config.resolve_color_mode();
+        let mut printer = TreePrinter::new(writer, config.tree_config().clone());
+        match config.traversal_order() {
+            TraversalOrder::DepthFirst => {
+                let layout = self.depth_first_layout(config, false);
+                render_children(&mut printer, self, config, 0, &layout)?
+            }
+            TraversalOrder::BreadthFirst => render_levels(&mut printer, self, config)?,
+        }
+
+        printer.finalize()
+    }
+
+    /// Renders the tree as the first column of a text table, and returns the writer.
+    ///
+    /// The metadata cells of the nodes (see [`set_metadata()`][set_metadata]) are aligned in
+    /// the following columns, with the layouts set by
+    /// [`RenderConfigBuilder::metadata_columns()`].
+    /// Unlike [`render()`][render], the metadata of the node itself are also shown.
+    ///
+    /// If headers are given, a header row and a rule are put above the tree.
+    /// The first header is for the tree column, and the rest are for the metadata columns.
+    /// Columns are widened to fit the headers.
+    ///
+    /// Nodes are always listed in the depth-first order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{ColumnAlign, MetadataColumn, RenderConfigBuilder, TreeNode};
+    ///
+    /// let mut root = TreeNode::new("app v0.1.0");
+    /// root.set_metadata(vec!["MIT", "12"]);
+    /// let mut serde = TreeNode::new("serde v1.0.0");
+    /// serde.set_metadata(vec!["MIT OR Apache-2.0", "120"]);
+    /// let mut derive = TreeNode::new("serde_derive v1.0.0");
+    /// derive.set_metadata(vec!["MIT OR Apache-2.0", "80"]);
+    /// serde.push_child(derive);
+    /// root.push_child(serde);
+    ///
+    /// let config = RenderConfigBuilder::new()
+    ///     .metadata_columns(vec![
+    ///         MetadataColumn::default(),
+    ///         MetadataColumn::new(ColumnAlign::Right),
+    ///     ])
+    ///     .build();
+    /// let got = root.render_table(String::new(), &config, &["crate", "license", "kLoC"])?;
+    ///
+    /// let expected = "crate                        license            kLoC\n\
+    ///                 ---------------------------  -----------------  ----\n\
+    ///                 app v0.1.0                   MIT                  12\n\
+    ///                 `-- serde v1.0.0             MIT OR Apache-2.0   120\n\
+    ///                 \x20   `-- serde_derive v1.0.0  MIT OR Apache-2.0    80\n";
+    /// assert_eq!(got, expected);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    ///
+    /// [set_metadata]: #method.set_metadata
+    /// [render]: #method.render
+    /// [`RenderConfigBuilder::metadata_columns()`]:
+    /// struct.RenderConfigBuilder.html#method.metadata_columns
+    pub fn render_table<W: fmt::Write>(
+        &self,
+        mut writer: W,
+        config: &RenderConfig,
+        headers: &[&str],
+    ) -> Result<W> {
+        let config = &*config.resolve_color_mode();
+        let (label, _) = self.label_for_render(Cow::Borrowed(&self.label), config);
+        let first_line = label.split('\n').next().unwrap_or("");
+        let mut layout = self.depth_first_layout(config, true);
+        let tree_width = layout
+            .metadata_column
+            .saturating_sub(2)
+            .max(config.str_width(first_line))
+            .max(headers.first().map_or(0, |header| config.str_width(header)));
+        layout.metadata_column = tree_width + 2;
+        let metadata_headers = headers.get(1..).unwrap_or_default();
+        // The root row and the header row are put in the same columns as the descendants.
+        let root_cells = self.metadata.iter().map(String::as_str).collect::<Vec<_>>();
+        for cells in [&root_cells[..], metadata_headers] {
+            if layout.metadata_widths.len() < cells.len() {
+                layout.metadata_widths.resize(cells.len(), 0);
+            }
+            for (width, cell) in layout.metadata_widths.iter_mut().zip(cells) {
+                *width = (*width).max(config.str_width(cell));
+            }
+        }
+
+        let newline = config.tree_config().newline();
+        if let Some((tree_header, metadata_headers)) = headers.split_first() {
+            let header_row = append_metadata(tree_header, metadata_headers, config, 0, &layout);
+            writer.write_str(header_row.trim_end())?;
+            writer.write_str(newline)?;
+            let mut rule = "-".repeat(tree_width);
+            for &width in &layout.metadata_widths {
+                rule.push_str("  ");
+                rule.extend(std::iter::repeat('-').take(width));
+            }
+            writer.write_str(&rule)?;
+            writer.write_str(newline)?;
+        }
+
+        let label = if self.metadata.is_empty() {
+            label
+        } else {
+            Cow::Owned(append_metadata(&label, &self.metadata, config, 0, &layout))
+        };
+        let ends_with_newline = label.ends_with('\n');
+        writer.write_str(&label.replace('\n', newline))?;
+        let has_visible_children = !self.hides_children(config) && !self.children.is_empty();
+        let need_newline = has_visible_children || config.tree_config().emit_trailing_newline();
+        if need_newline && !ends_with_newline {
+            writer.write_str(newline)?;
+        }
+
+        let mut printer = TreePrinter::new(writer, config.tree_config().clone());
+        render_children(&mut printer, self, config, 0, &layout)?;
+        printer.finalize()
+    }
+
+    /// Computes the columns for rendering the descendants in the depth-first order.
+    ///
+    /// If `table` is true, the metadata column is put after the labels of all descendants,
+    /// including ones without metadata.
+    fn depth_first_layout(&self, config: &RenderConfig, table: bool) -> Layout {
+        let label_column = if config.align_labels() && !config.tree_config().quote_labels() {
+            Some(config.gutter_width(self.visible_height(config)))
+        } else {
+            None
+        };
+        let mut layout = Layout {
+            label_column,
+            annotation_column: 0,
+            metadata_column: 0,
+            metadata_widths: Vec::new(),
+        };
+        if let Some(end) = max_annotated_label_end(self, config, 0, &layout) {
+            layout.annotation_column = end + config.tree_config().annotation_gap();
+        }
+        let mut metadata_end = None;
+        let mut widths = Vec::new();
+        measure_metadata(
+            self,
+            config,
+            0,
+            &layout,
+            table,
+            &mut metadata_end,
+            &mut widths,
+        );
+        if let Some(end) = metadata_end {
+            layout.metadata_column = end + 2;
+        }
+        layout.metadata_widths = widths
+            .into_iter()
+            .enumerate()
+            .map(|(i, width)| width.max(config.metadata_column(i).min_width()))
+            .collect();
+        layout
+    }
+
+    /// Returns the number of levels of the visible descendants.
+    fn visible_height(&self, config: &RenderConfig) -> usize {
+        self.ordered_children(config)
+            .into_iter()
+            .map(|child| child.compressed(config).2.visible_height(config) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns whether the children should be hidden in the rendering.
+    fn hides_children(&self, config: &RenderConfig) -> bool {
+        self.collapsed && !config.expand_all()
+    }
+
+    /// Returns the child nodes in the rendering order.
+    ///
+    /// Returns an empty vector if the node is collapsed.
+    fn ordered_children(&self, config: &RenderConfig) -> Vec<&TreeNode> {
+        if self.hides_children(config) {
+            return Vec::new();
+        }
+        let mut children = self.children.iter().collect::<Vec<_>>();
+        if self
+            .reverse_children
+            .unwrap_or_else(|| config.reverse_children())
+        {
+            children.reverse();
+        }
+        children
+    }
+
+    /// Returns the label to be rendered, considering the collapsed state and the fold markers.
+    ///
+    /// The second element is the length of the end of the first line which should not be
+    /// truncated or wrapped, that is, the alias target and the following collapsed suffix.
+    fn label_for_render<'a>(
+        &self,
+        label: Cow<'a, str>,
+        config: &RenderConfig,
+    ) -> (Cow<'a, str>, usize) {
+        let label = match config.tree_config().normalize_newlines(&label) {
+            Cow::Owned(normalized) => Cow::Owned(normalized),
+            Cow::Borrowed(_) => label,
+        };
+        // Expand tabs here, so that the widths of labels are measured correctly for alignment.
+        let label = match config.tree_config().expand_tabs(&label) {
+            Cow::Owned(expanded) => Cow::Owned(expanded),
+            Cow::Borrowed(_) => label,
+        };
+        let kind = config.classify(self);
+        let mut tail_len = 0;
+        let label = if kind.is_some() || self.alias_target.is_some() {
+            let first_line_end = label.find('\n').unwrap_or(label.len());
+            let mut first_line = label[..first_line_end].to_owned();
+            if let Some(kind) = &kind {
+                first_line.push_str(kind.suffix());
+            }
+            if let Some(target) = &self.alias_target {
+                let tree_config = config.tree_config();
+                let target = tree_config.normalize_newlines(target);
+                let target = tree_config.expand_tabs(&target);
+                let name_end = first_line.len();
+                first_line.push_str(" -> ");
+                config.write_alias_target(&mut first_line, &target);
+                tail_len = first_line_len(&first_line[name_end..]);
+            }
+            first_line.push_str(&label[first_line_end..]);
+            Cow::Owned(first_line)
+        } else {
+            label
+        };
+        let label = if self.hides_children(config) {
+            let first_line = label.lines().next().unwrap_or("");
+            match self.node_count() - 1 {
+                0 if first_line.len() == label.len() => label,
+                0 => Cow::Owned(first_line.to_owned()),
+                hidden => {
+                    let suffix = config.collapsed_suffix(hidden);
+                    if tail_len != 0 {
+                        tail_len += suffix.len();
+                    }
+                    Cow::Owned(format!("{}{}", first_line, suffix))
+                }
+            }
+        } else {
+            label
+        };
+        #[cfg(feature = "nerd-fonts")]
+        let label = match config.nerd_font_icon(self, kind.as_ref()) {
+            Some(icon) => Cow::Owned(config.tree_config().prefix_icon(&label, icon)),
+            None => label,
+        };
+
+        let markers = match config.fold_markers() {
+            Some(v) => v,
+            None => return (label, tail_len),
+        };
+        let marker = if self.children.is_empty() {
+            markers.leaf()
+        } else if self.hides_children(config) {
+            markers.collapsed()
+        } else {
+            markers.expanded()
+        };
+        if marker.is_empty() {
+            return (label, tail_len);
+        }
+        (Cow::Owned(format!("{} {}", marker, label)), tail_len)
+    }
+
+    /// Returns the label to be rendered, the length of its tail (see
+    /// [`label_for_render()`](#method.label_for_render)), and the node whose children are
+    /// rendered next, compressing single-child chains if requested.
+    fn compressed<'a>(&'a self, config: &RenderConfig) -> (Cow<'a, str>, usize, &'a TreeNode) {
+        let joiner = match config.chain_joiner() {
+            Some(v) => v,
+            None => {
+                let (label, tail_len) = self.label_for_render(Cow::Borrowed(&self.label), config);
+                return (label, tail_len, self);
+            }
+        };
+
+        let mut label = Cow::Borrowed(self.label.as_str());
+        let mut current = self;
+        while current.children.len() == 1 && !current.hides_children(config) {
+            let child = &current.children[0];
+            if current.label.contains('\n') || child.label.contains('\n') {
+                break;
+            }
+            let label = label.to_mut();
+            label.push_str(joiner);
+            label.push_str(&child.label);
+            current = child;
+        }
+
+        let (label, tail_len) = current.label_for_render(label, config);
+        (label, tail_len, current)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TreeNode {
+    /// Serializes the tree into JSON.
+    ///
+    /// Each node is serialized as `{ "label": ..., "children": [...] }`.
+    /// Render-time states such as the collapsed state are not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::TreeNode;
+    ///
+    /// let mut root = TreeNode::new("root");
+    /// root.push_child(TreeNode::new("leaf"));
+    ///
+    /// assert_eq!(
+    ///     root.to_json(),
+    ///     r#"{"label":"root","children":[{"label":"leaf","children":[]}]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Should never fail: `TreeNode` is always serializable")
+    }
+
+    /// Deserializes the tree from JSON.
+    ///
+    /// `children` can be omitted for leaf nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::TreeNode;
+    ///
+    /// let tree = TreeNode::from_json(r#"{"label":"root","children":[{"label":"leaf"}]}"#)?;
+    ///
+    /// assert_eq!(tree.label(), "root");
+    /// assert_eq!(tree.children()[0].label(), "leaf");
+    /// # Ok::<_, serde_json::Error>(())
+    /// ```
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+}
+
+impl fmt::Display for TreeNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, &RenderConfig::new())
+            .map(|_| ())
+            .map_err(|_| fmt::Error)
+    }
+}
+
+/// Retained tree builder.
+///
+/// This provides the same way to build a tree as [`TreePrinter`] does, but the nodes are kept
+/// as a [`TreeNode`] so that the tree can be rendered later in any form.
+/// As the builder knows the whole tree before rendering, styles are not required.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::TreeBuilder;
+///
+/// let mut builder = TreeBuilder::new(".");
+/// builder.open_node("foo")?;
+/// builder.open_node("bar")?;
+/// builder.close_node()?; // bar
+/// builder.close_node()?; // foo
+/// builder.open_node("baz")?;
+/// let tree = builder.finalize();
+///
+/// assert_eq!(tree.to_string(), ".\n|-- foo\n|   `-- bar\n`-- baz\n");
+/// # plaintextree::Result::Ok(())
+/// ```
+///
+/// [`TreeNode`]: struct.TreeNode.html
+/// [`TreePrinter`]: struct.TreePrinter.html
+#[derive(Debug, Clone)]
+pub struct TreeBuilder {
+    /// Open nodes, from the root to the innermost.
+    stack: Vec<TreeNode>,
+}
+
+impl TreeBuilder {
+    /// Creates a new `TreeBuilder` with the given root label.
+    pub fn new(root_label: impl Into<String>) -> Self {
+        Self {
+            stack: vec![TreeNode::new(root_label)],
+        }
+    }
+
+    /// Opens a new node with the given content.
+    pub fn open_node(&mut self, content: impl fmt::Display) -> Result<()> {
+        let mut label = String::new();
+        write!(label, "{}", content)?;
+        self.stack.push(TreeNode::new(label));
+
+        Ok(())
+    }
+
+    /// Closes a node.
+    pub fn close_node(&mut self) -> Result<()> {
+        if self.stack.len() <= 1 {
+            // Too much close!
+            return Err(Error::ExtraNodeClose);
+        }
+        let node = self
+            .stack
+            .pop()
+            .expect("Should never fail: `stack` is checked to have the open node");
+        self.stack
+            .last_mut()
+            .expect("Should never fail: the root is always in `stack`")
+            .push_child(node);
+
+        Ok(())
+    }
+
+    /// Closes all open nodes and returns the root node.
+    pub fn finalize(mut self) -> TreeNode {
+        while self.stack.len() > 1 {
+            self.close_node()
+                .expect("Should never fail: `stack` is checked to have the open node");
+        }
+        self.stack
+            .pop()
+            .expect("Should never fail: the root is always in `stack`")
+    }
+}
+
+/// Returns the label with each non-empty line wrapped in bidirectional isolates.
+fn isolate_lines(label: &str) -> String {
+    let mut isolated = String::with_capacity(label.len() + 8);
+    for (i, line) in label.split('\n').enumerate() {
+        if i != 0 {
+            isolated.push('\n');
+        }
+        if !line.is_empty() {
+            isolated.push(FIRST_STRONG_ISOLATE);
+            isolated.push_str(line);
+            isolated.push(POP_DIRECTIONAL_ISOLATE);
+        }
+    }
+    isolated
+}
+
+/// Returns the label prefixed by the diff marker.
+fn mark_label(marker: char, label: &str) -> String {
+    let mut marked = String::with_capacity(label.len() + 2);
+    for (i, line) in label.split('\n').enumerate() {
+        if i == 0 {
+            marked.push(marker);
+            marked.push(' ');
+        } else {
+            marked.push('\n');
+            if !line.is_empty() {
+                marked.push_str("  ");
+            }
+        }
+        marked.push_str(line);
+    }
+    marked
+}
+
+/// Returns a copy of the tree with all labels marked.
+fn mark_tree(marker: char, node: &TreeNode) -> TreeNode {
+    let mut marked = TreeNode::new(mark_label(marker, &node.label));
+    marked.children = node
+        .children
+        .iter()
+        .map(|child| mark_tree(marker, child))
+        .collect();
+    marked
+}
+
+/// Computes the diff of the matched nodes, and returns it with whether the descendants changed.
+fn diff_node(old: &TreeNode, new: &TreeNode) -> (TreeNode, bool) {
+    let (old_children, new_children) = (&old.children, &new.children);
+    let (n, m) = (old_children.len(), new_children.len());
+
+    // Lengths of the longest common subsequences of the suffixes.
+    let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_children[i].label == new_children[j].label {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut children = Vec::with_capacity(n.max(m));
+    let mut changed = false;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_children[i].label == new_children[j].label {
+            let (child, child_changed) = diff_node(&old_children[i], &new_children[j]);
+            children.push(child);
+            changed |= child_changed;
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            children.push(mark_tree('-', &old_children[i]));
+            changed = true;
+            i += 1;
+        } else {
+            children.push(mark_tree('+', &new_children[j]));
+            changed = true;
+            j += 1;
+        }
+    }
+
+    let mut diff = TreeNode::new(mark_label(if changed { '~' } else { ' ' }, &new.label));
+    diff.children = children;
+    (diff, changed)
+}
+
+/// Columns computed before rendering.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// Column where the first lines of labels start, if aligned.
+    label_column: Option<usize>,
+    /// Column where the annotations start.
+    annotation_column: usize,
+    /// Column where the metadata start.
+    metadata_column: usize,
+    /// Widths of the metadata columns.
+    metadata_widths: Vec<usize>,
+}
+
+/// Returns the label of the child node to be rendered, the length of its tail, and the last
+/// node of the chain.
+fn item_label<'a>(
+    child: &'a TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+) -> (Cow<'a, str>, usize, &'a TreeNode) {
+    let (mut label, tail_len, chain_end) = child.compressed(config);
+    if let Some(column) = layout.label_column {
+        let padding = column.saturating_sub(config.gutter_width(depth + 1));
+        label = Cow::Owned(indent_lines(&label, padding));
+    }
+    (label, tail_len, chain_end)
+}
+
+/// Returns the maximum column where the first lines of the annotated labels end.
+fn max_annotated_label_end(
+    parent: &TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+) -> Option<usize> {
+    parent
+        .ordered_children(config)
+        .into_iter()
+        .filter_map(|child| {
+            let (label, _, chain_end) = item_label(child, config, depth, layout);
+            let end = chain_end.annotation.as_ref().map(|_| {
+                let first_line = label.lines().next().unwrap_or("");
+                config.gutter_width(depth + 1) + config.str_width(first_line)
+            });
+            let descendants = max_annotated_label_end(chain_end, config, depth + 1, layout);
+            end.max(descendants)
+        })
+        .max()
+}
+
+/// Returns the label of the child node with the annotation, the length of its tail, and the
+/// last node of the chain.
+///
+/// The annotation after the tail is also a part of the tail.
+fn annotated_label<'a>(
+    child: &'a TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+) -> (Cow<'a, str>, usize, &'a TreeNode) {
+    let (mut label, mut tail_len, chain_end) = item_label(child, config, depth, layout);
+    if let Some(annotation) = &chain_end.annotation {
+        let first_line_end = label.find('\n').unwrap_or_else(|| label.len());
+        let column = config.gutter_width(depth + 1) + config.str_width(&label[..first_line_end]);
+        let width = layout.annotation_column.saturating_sub(column);
+        let annotated = config
+            .tree_config()
+            .annotate_first_line(&label, annotation, width);
+        if tail_len != 0 {
+            let inserted_end = first_line_end + annotated.len() - label.len();
+            tail_len += first_line_len(&annotated[first_line_end..inserted_end]);
+        }
+        label = Cow::Owned(annotated);
+    }
+    (label, tail_len, chain_end)
+}
+
+/// Updates the maximum end column of the annotated labels with metadata, and the maximum widths
+/// of the metadata columns.
+fn measure_metadata(
+    parent: &TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+    all_nodes: bool,
+    max_end: &mut Option<usize>,
+    widths: &mut Vec<usize>,
+) {
+    for child in parent.ordered_children(config) {
+        let (label, _, chain_end) = annotated_label(child, config, depth, layout);
+        if all_nodes || !chain_end.metadata.is_empty() {
+            let first_line = label.lines().next().unwrap_or("");
+            let end = config.gutter_width(depth + 1) + config.str_width(first_line);
+            *max_end = (*max_end).max(Some(end));
+            if widths.len() < chain_end.metadata.len() {
+                widths.resize(chain_end.metadata.len(), 0);
+            }
+            for (width, cell) in widths.iter_mut().zip(&chain_end.metadata) {
+                *width = (*width).max(config.str_width(cell));
+            }
+        }
+        measure_metadata(
+            chain_end,
+            config,
+            depth + 1,
+            layout,
+            all_nodes,
+            max_end,
+            widths,
+        );
+    }
+}
+
+/// Puts the metadata cells after the first line of the label.
+///
+/// `gutter` is the display width of the line prefix and padding before the label.
+fn append_metadata(
+    label: &str,
+    metadata: &[impl AsRef<str>],
+    config: &RenderConfig,
+    gutter: usize,
+    layout: &Layout,
+) -> String {
+    let first_line_end = label.find('\n').unwrap_or(label.len());
+    let column = gutter + config.str_width(&label[..first_line_end]);
+    let mut row = String::new();
+    for (i, &width) in layout.metadata_widths.iter().enumerate() {
+        if i != 0 {
+            row.push_str("  ");
+        }
+        let cell = metadata.get(i).map_or("", AsRef::as_ref);
+        let padding = std::iter::repeat(' ').take(width.saturating_sub(config.str_width(cell)));
+        match config.metadata_column(i).align() {
+            ColumnAlign::Left => {
+                row.push_str(cell);
+                row.extend(padding);
+            }
+            ColumnAlign::Right => {
+                row.extend(padding);
+                row.push_str(cell);
+            }
+        }
+    }
+
+    let row = if config.tree_config().emit_trailing_whitespace() {
+        &row
+    } else {
+        row.trim_end()
+    };
+    let mut appended = label[..first_line_end].to_owned();
+    if !row.is_empty() {
+        appended.extend(std::iter::repeat(' ').take(layout.metadata_column.saturating_sub(column)));
+        appended.push_str(row);
+    }
+    appended.push_str(&label[first_line_end..]);
+    appended
+}
+
+/// Renders the child nodes.
+fn render_children<W: fmt::Write>(
+    printer: &mut TreePrinter<W>,
+    parent: &TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+) -> Result<()> {
+    let children = parent.ordered_children(config);
+    let num_children = children.len();
+    for (i, child) in children.into_iter().enumerate() {
+        let (style, label, chain_end) =
+            child_item(child, config, depth, layout, i + 1 == num_children);
+        printer.open_node(style, label)?;
+        render_children(printer, chain_end, config, depth + 1, layout)?;
+        printer.close_node()?;
+    }
+
+    Ok(())
+}
+
+/// Number of spaces between columns of the multi-column layout.
+const COLUMN_GAP: usize = 2;
+
+/// Maximum width of the multi-column layout if no limit is set.
+const DEFAULT_FLOW_WIDTH: usize = 80;
+
+/// Stacks the cells into as few rows of cells as possible in the width, from top to bottom and
+/// then left to right.
+fn flow_columns(cells: &[Canvas], max_width: usize, tree_config: &TreeConfig) -> Vec<Canvas> {
+    let num_rows = (1..cells.len())
+        .find(|&num_rows| {
+            let widths = cells
+                .chunks(num_rows)
+                .map(|column| column.iter().map(Canvas::width).max().unwrap_or(0));
+            let num_columns = (cells.len() + num_rows - 1) / num_rows;
+            widths.sum::<usize>() + COLUMN_GAP * (num_columns - 1) <= max_width
+        })
+        .unwrap_or(cells.len());
+
+    cells
+        .chunks(num_rows)
+        .map(|column| {
+            let mut canvas = Canvas::new(tree_config);
+            let mut row = 0;
+            for cell in column {
+                canvas.put_canvas(0, row, cell);
+                row += cell.height();
+            }
+            canvas
+        })
+        .collect()
+}
+
+/// Returns the item style and the label of the child node, and the last node of the chain.
+fn child_item<'a>(
+    child: &'a TreeNode,
+    config: &RenderConfig,
+    depth: usize,
+    layout: &Layout,
+    is_last_child: bool,
+) -> (ItemStyle, Cow<'a, str>, &'a TreeNode) {
+    let (mut label, mut tail_len, chain_end) = annotated_label(child, config, depth, layout);
+    if !chain_end.metadata.is_empty() {
+        let with_metadata = append_metadata(
+            &label,
+            &chain_end.metadata,
+            config,
+            config.gutter_width(depth + 1),
+            layout,
+        );
+        if tail_len != 0 {
+            let first_line_end = label.find('\n').unwrap_or_else(|| label.len());
+            let inserted_end = first_line_end + with_metadata.len() - label.len();
+            tail_len += first_line_len(&with_metadata[first_line_end..inserted_end]);
+        }
+        label = Cow::Owned(with_metadata);
+    }
+    let style = config
+        .item_style(is_last_child, child.is_on_marked_path())
+        .with_label_tail_len(tail_len);
+    (style, label, chain_end)
+}
+
+/// Iterator lazily rendering a retained tree line by line.
+///
+/// This is created by [`TreeNode::lines()`].
+///
+/// [`TreeNode::lines()`]: struct.TreeNode.html#method.lines
+pub struct RenderedLines<'a> {
+    /// Render config.
+    config: Cow<'a, RenderConfig>,
+    /// Columns computed before rendering.
+    layout: Layout,
+    /// Tree printer writing to the pending output, or `None` if the rendering is finished.
+    printer: Option<TreePrinter<String>>,
+    /// Children to be rendered for each open node, and the children of the root at the bottom.
+    stack: Vec<Peekable<vec::IntoIter<&'a TreeNode>>>,
+    /// Byte offsets where the opened items start and their depths, for the items whose first
+    /// lines are not yet taken.
+    item_depths: VecDeque<(usize, usize)>,
+    /// Number of bytes taken from the pending output.
+    num_taken_bytes: usize,
+    /// Rendered lines not yet returned, with the depths of the items starting at the lines.
+    lines: VecDeque<(String, Option<usize>)>,
+}
+
+impl<'a> RenderedLines<'a> {
+    /// Creates a new `RenderedLines`.
+    fn new(root: &'a TreeNode, config: &'a RenderConfig) -> Self {
+        let config = config.resolve_color_mode();
+        let tree_config = config.tree_config();
+        let needs_whole_output = config.layout() != TreeLayout::Indented
+            || config.traversal_order() == TraversalOrder::BreadthFirst
+            || tree_config.is_mirrored()
+            || tree_config.is_bottom_up();
+        if needs_whole_output {
+            let lines = root
+                .render_lines(&config)
+                .expect("Should never fail: writing to `String`");
+            return Self {
+                layout: root.depth_first_layout(&config, false),
+                config,
+                printer: None,
+                stack: Vec::new(),
+                item_depths: VecDeque::new(),
+                num_taken_bytes: 0,
+                lines: lines.into_iter().map(|line| (line, None)).collect(),
+            };
+        }
+
+        let (label, ends_with_newline) = root.root_label(&config);
+        let children = root.ordered_children(&config);
+        let newline = tree_config.newline();
+        let mut head = label.replace('\n', newline);
+        if (!children.is_empty() || tree_config.emit_trailing_newline()) && !ends_with_newline {
+            head.push_str(newline);
+        }
+        let lines = split_lines(&head, newline)
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| (line, (i == 0).then_some(0)))
+            .collect::<VecDeque<_>>();
+        let mut tree_config = tree_config.clone();
+        tree_config.set_mark_items(true);
+        if let Some(max_lines) = tree_config.max_lines() {
+            tree_config.set_max_lines(Some(max_lines.saturating_sub(lines.len())));
+        }
+        Self {
+            layout: root.depth_first_layout(&config, false),
+            config,
+            printer: Some(TreePrinter::new(String::new(), tree_config)),
+            stack: vec![children.into_iter().peekable()],
+            item_depths: VecDeque::new(),
+            num_taken_bytes: 0,
+            lines,
+        }
+    }
+
+    /// Returns the next line, and the depth of the item if the item starts at the line.
+    ///
+    /// The depth of the root is 0.
+    /// Depths are not available for trees rendered at once.
+    fn next_line(&mut self) -> Option<(String, Option<usize>)> {
+        while self.lines.is_empty() {
+            let mut printer = self.printer.take()?;
+            self.step(&mut printer)
+                .expect("Should never fail: writing to `String`");
+            let finished = self.stack.is_empty();
+            let mut pending = mem::take(printer.inner_mut());
+            self.take_lines(&mut pending, finished);
+            *printer.inner_mut() = pending;
+            if !finished {
+                self.printer = Some(printer);
+            }
+        }
+        self.lines.pop_front()
+    }
+
+    /// Renders the next node, or closes the current node if all children are rendered.
+    fn step(&mut self, printer: &mut TreePrinter<String>) -> Result<()> {
+        let depth = self.stack.len() - 1;
+        let children = self
+            .stack
+            .last_mut()
+            .expect("Should never fail: called only while rendering");
+        match children.next() {
+            Some(child) => {
+                let is_last_child = children.peek().is_none();
+                let (style, label, chain_end) =
+                    child_item(child, &self.config, depth, &self.layout, is_last_child);
+                printer.open_node(style, label)?;
+                // Items after the line limit are not recorded.
+                for start in printer.take_item_starts() {
+                    self.item_depths.push_back((start, depth + 1));
+                }
+                self.stack.push(
+                    chain_end
+                        .ordered_children(&self.config)
+                        .into_iter()
+                        .peekable(),
+                );
+            }
+            None => {
+                self.stack.pop();
+                if self.stack.is_empty() {
+                    printer.finish()?;
+                } else {
+                    printer.close_node()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the complete lines of the pending output to the rendered lines.
+    ///
+    /// If `finished` is true, the incomplete last line is also moved.
+    fn take_lines(&mut self, pending: &mut String, finished: bool) {
+        let newline = self.config.tree_config().newline();
+        let rest = match pending.rfind(newline) {
+            _ if finished => String::new(),
+            Some(pos) => pending.split_off(pos + newline.len()),
+            None => return,
+        };
+        let mut line_start = self.num_taken_bytes;
+        self.num_taken_bytes += pending.len();
+        for line in split_lines(pending, newline) {
+            let next_line_start = line_start + line.len() + newline.len();
+            // Items with empty labels start at the same position as the next item.
+            let mut depth = None;
+            while let Some(&(_, item_depth)) = self
+                .item_depths
+                .front()
+                .filter(|&&(start, _)| start < next_line_start)
+            {
+                depth = Some(item_depth);
+                self.item_depths.pop_front();
+            }
+            self.lines.push_back((line, depth));
+            line_start = next_line_start;
+        }
+        *pending = rest;
+    }
+}
+
+impl Iterator for RenderedLines<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.next_line().map(|(line, _)| line)
+    }
+}
+
+/// Iterator lazily rendering a retained tree page by page.
+///
+/// This is created by [`TreeNode::pages()`].
+///
+/// [`TreeNode::pages()`]: struct.TreeNode.html#method.pages
+pub struct RenderedPages<'a> {
+    /// Rendered lines.
+    lines: RenderedLines<'a>,
+    /// Maximum number of lines in a page.
+    page_height: usize,
+    /// First lines of the items containing the last returned line, from the root.
+    open_items: Vec<String>,
+}
+
+impl<'a> RenderedPages<'a> {
+    /// Returns the next line and whether an item starts at the line, updating the open items.
+    fn next_line(&mut self) -> Option<(String, bool)> {
+        let (line, depth) = self.lines.next_line()?;
+        if let Some(depth) = depth {
+            self.open_items.truncate(depth);
+            self.open_items.push(line.clone());
+        }
+        Some((line, depth.is_some()))
+    }
+}
+
+impl Iterator for RenderedPages<'_> {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Vec<String>> {
+        let (first, starts_item) = self.next_line()?;
+        // Repeat the first lines of the items containing the first line, except the line
+        // itself. Nearer ancestors are kept if there is no room for all of them.
+        let num_context = self.open_items.len() - usize::from(starts_item);
+        let skip = num_context.saturating_sub(self.page_height - 1);
+        let mut page = self.open_items[skip..num_context].to_vec();
+        page.push(first);
+        while page.len() < self.page_height {
+            match self.next_line() {
+                Some((line, _)) => page.push(line),
+                None => break,
+            }
+        }
+        Some(page)
+    }
+}
+
+/// Puts the given number of spaces before each non-empty line.
+fn indent_lines(s: &str, width: usize) -> String {
+    let mut indented = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        if line != "\n" {
+            indented.extend(std::iter::repeat(' ').take(width));
+        }
+        indented.push_str(line);
+    }
+    indented
+}
+
+/// Renders the descendants level by level.
+fn render_levels<W: fmt::Write>(
+    printer: &mut TreePrinter<W>,
+    root: &TreeNode,
+    config: &RenderConfig,
+) -> Result<()> {
+    let mut level = root.ordered_children(config);
+    let mut depth = 1;
+    while !level.is_empty() {
+        let next_level = level
+            .iter()
+            .flat_map(|node| node.ordered_children(config))
+            .collect::<Vec<_>>();
+
+        let heading_style = config.item_style(next_level.is_empty(), false);
+        printer.open_node(heading_style, format_args!("depth {}", depth))?;
+        let num_nodes = level.len();
+        for (i, node) in level.into_iter().enumerate() {
+            let style = config.item_style(i + 1 == num_nodes, node.marked);
+            let (label, tail_len) = flat_label(node, config);
+            printer.open_node(style.with_label_tail_len(tail_len), label)?;
+            printer.close_node()?;
+        }
+        printer.close_node()?;
+
+        level = next_level;
+        depth += 1;
+    }
+
+    Ok(())
+}
+
+/// Returns the length of the first line of the text inserted at the end of the first line of a
+/// label, that is, the part of the text which extends the tail of the label.
+fn first_line_len(s: &str) -> usize {
+    s.find('\n').unwrap_or(s.len())
+}
+
+/// Returns the label with the annotation and the metadata put after the first line, and the
+/// length of the tail of the first line kept intact on fitting.
+///
+/// The annotation and the metadata after the tail are also a part of the tail.
+fn flat_label<'a>(node: &'a TreeNode, config: &RenderConfig) -> (Cow<'a, str>, usize) {
+    let (mut label, mut tail_len) = node.label_for_render(Cow::Borrowed(&node.label), config);
+    let mut insert_after_first_line = |label: &mut Cow<'_, str>, s: String| {
+        let first_line_end = label.find('\n').unwrap_or_else(|| label.len());
+        if tail_len != 0 {
+            tail_len += first_line_len(&s);
+        }
+        label.to_mut().insert_str(first_line_end, &s);
+    };
+    if let Some(annotation) = &node.annotation {
+        insert_after_first_line(&mut label, format!("  {}", annotation));
+    }
+    if !node.metadata.is_empty() {
+        insert_after_first_line(&mut label, format!("  {}", node.metadata.join("  ")));
+    }
+    (label, tail_len)
+}
+
+/// Returns the chart block of the node and its descendants.
+fn chart_block(node: &TreeNode, config: &RenderConfig, chars: &BoxChars) -> Block {
+    let tree_config = config.tree_config();
+    let (mut label, mut tail_len) = flat_label(node, config);
+    if tree_config.quote_labels() {
+        if let Cow::Owned(quoted) = quote_label(&label) {
+            label = Cow::Owned(quoted);
+            tail_len = 0;
+        }
+    }
+    if let Some(max_width) = tree_config.max_label_width() {
+        label = Cow::Owned(tree_config.fit_lines_with_tail(&label, tail_len, max_width, max_width));
+    }
+    let children = node
+        .ordered_children(config)
+        .into_iter()
+        .map(|child| chart_block(child, config, chars))
+        .collect();
+    Block::boxed(&label, chars, tree_config).with_children(children, chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{
+        EdgeConfig, FoldMarkers, LabelOverflow, MetadataColumn, NodeKind, RenderConfigBuilder,
+        TreeConfigBuilder,
+    };
+
+    /// Creates a tree with the given chain of labels.
+    fn chain(labels: &[&str]) -> TreeNode {
+        let mut iter = labels.iter().rev();
+        let mut node = TreeNode::new(*iter.next().expect("labels must not be empty"));
+        for label in iter {
+            let mut parent = TreeNode::new(*label);
+            parent.push_child(node);
+            node = parent;
+        }
+        node
+    }
+
+    #[test]
+    fn align_labels() -> Result<()> {
+        let mut root = chain(&[".", "foo", "bar\n\nbar2", "baz"]);
+        root.push_child(TreeNode::new("qux"));
+        let config = RenderConfigBuilder::new().align_labels(true).build();
+        let got = root.render(String::new(), &config)?;
+
+        let expected = ".\n\
+                        |--         foo\n\
+                        |   `--     bar\n\
+                        |\n\
+                        |           bar2\n\
+                        |       `-- baz\n\
+                        `--         qux\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn marked_path_color() -> Result<()> {
+        use crate::ansi::Color;
+
+        let mut root = chain(&[".", "foo", "bar"]);
+        root.push_child(TreeNode::new("baz"));
+        root.children_mut()[0].children_mut()[0].set_marked(true);
+        let config = RenderConfigBuilder::new()
+            .marked_path_color(Some(Color::Yellow))
+            .build();
+        let got = root.render(String::new(), &config)?;
+
+        let expected = ".\n\
+                        \x1b[33m|==\x1b[39m foo\n\
+                        \x1b[33m|\x1b[39m   \x1b[33m`==\x1b[39m bar\n\
+                        `-- baz\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn annotations() -> Result<()> {
+        let mut root = chain(&[".", "foo", "bar\nbar2"]);
+        root.push_child(TreeNode::new("baz"));
+        root.children_mut()[0].set_annotation(Some("1"));
+        root.children_mut()[0].children_mut()[0].set_annotation(Some("2"));
+        root.children_mut()[1].set_annotation(Some("3"));
+
+        let got = root.render(String::new(), &RenderConfig::new())?;
+        let expected = ".\n\
+                        |-- foo      1\n\
+                        |   `-- bar  2\n\
+                        |       bar2\n\
+                        `-- baz      3\n";
+        assert_eq!(got, expected);
+
+        let config = RenderConfigBuilder::new()
+            .annotation_leader('.')
+            .align_labels(true)
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |--     foo .. 1\n\
+                        |   `-- bar .. 2\n\
+                        |       bar2\n\
+                        `--     baz .. 3\n";
+        assert_eq!(got, expected);
+
+        // The leader of the render config overrides the one of the tree config.
+        let tree_config = TreeConfigBuilder::new().annotation_leader('_').build();
+        let configs = [
+            RenderConfigBuilder::new()
+                .tree_config(TreeConfigBuilder::new().annotation_leader('.').build())
+                .build(),
+            RenderConfigBuilder::new()
+                .annotation_leader('.')
+                .tree_config(tree_config)
+                .build(),
+        ];
+        let expected = ".\n\
+                        |-- foo ...... 1\n\
+                        |   `-- bar .. 2\n\
+                        |       bar2\n\
+                        `-- baz ...... 3\n";
+        for config in &configs {
+            assert_eq!(root.render(String::new(), config)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn metadata() -> Result<()> {
+        let mut root = chain(&[".", "foo", "bar\nbar2"]);
+        root.push_child(TreeNode::new("baz"));
+        root.children_mut()[0].set_annotation(Some("1"));
+        root.children_mut()[0].set_metadata(vec!["a", "10"]);
+        root.children_mut()[0].children_mut()[0].set_metadata(vec!["bb", "2"]);
+        root.children_mut()[1].set_metadata(vec!["c"]);
+
+        let config = RenderConfigBuilder::new()
+            .metadata_columns(vec![
+                MetadataColumn::default().with_min_width(3),
+                MetadataColumn::new(ColumnAlign::Right),
+            ])
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |-- foo  1   a    10\n\
+                        |   `-- bar  bb    2\n\
+                        |       bar2\n\
+                        `-- baz      c\n";
+        assert_eq!(got, expected);
+
+        let config = RenderConfigBuilder::new()
+            .traversal_order(TraversalOrder::BreadthFirst)
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |-- depth 1\n\
+                        |   |-- foo  1  a  10\n\
+                        |   `-- baz  c\n\
+                        `-- depth 2\n\
+                        \x20   `-- bar  bb  2\n\
+                        \x20       bar2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn classify() -> Result<()> {
+        let mut root = chain(&[".", "foo", "bar"]);
+        root.push_child(TreeNode::new("baz\nbaz2"));
+        root.children_mut()[0].set_collapsed(true);
+        root.children_mut()[0].set_annotation(Some("1"));
+
+        let config = RenderConfigBuilder::new()
+            .classify(Some(|node| {
+                if node.is_leaf() {
+                    Some(NodeKind::Custom("!".to_owned()))
+                } else {
+                    Some(NodeKind::Directory)
+                }
+            }))
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "./\n\
+                        |-- foo/ \u{2026} (1 hidden)  1\n\
+                        `-- baz!\n\
+                        \x20   baz2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn alias() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(TreeNode::alias("current", "current-v2"));
+        root.push_child(TreeNode::alias("old\nstale", "v1"));
+        root.children_mut()[1].set_annotation(Some("1"));
+
+        let count = root.highlight_by(
+            |label| label.contains("current"),
+            &Highlight::wrap("[", "]"),
+        );
+        assert_eq!(count, 1);
+        let got = root.render(String::new(), &RenderConfig::new())?;
+        let expected = ".\n\
+                        |-- [current] -> current-v2\n\
+                        `-- old -> v1  1\n\
+                        \x20   stale\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn alias_with_limited_widths() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(TreeNode::alias("long-name", "target"));
+        root.push_child(TreeNode::alias("long\nstale", "target"));
+        root.children_mut()[1].set_annotation(Some("1"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().max_width(Some(18)).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |-- lon\u{2026} -> target\n\
+                        `-- l\u{2026} -> target \u{2026}\n\
+                        \x20   stale\n";
+        assert_eq!(got, expected);
+
+        for max_width in 6..=20 {
+            let config = RenderConfigBuilder::new()
+                .tree_config(TreeConfigBuilder::new().max_width(Some(max_width)).build())
+                .build();
+            let got = root.render(String::new(), &config)?;
+            for line in got.lines() {
+                assert!(line.chars().count() <= max_width, "{:?}", got);
+            }
+            let lines = got.lines().collect::<Vec<_>>();
+            assert!(lines[1].starts_with("|-- l"), "{:?}", got);
+            assert!(lines[2].starts_with("`-- l"), "{:?}", got);
+        }
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .max_label_width(Some(14))
+                    .label_overflow(LabelOverflow::HardWrap)
+                    .build(),
+            )
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |-- long -> target\n\
+                        |   \u{21aa} -name\n\
+                        `-- l -> target  1\n\
+                        \x20   \u{21aa} ong\n\
+                        \x20   stale\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn alias_with_multiline_tail() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(TreeNode::alias("a", "b\nc"));
+        let mut annotated = TreeNode::alias("name", "target");
+        annotated.set_annotation(Some("x\ny"));
+        root.push_child(annotated);
+        let mut with_metadata = TreeNode::alias("name", "target");
+        with_metadata.set_metadata(vec!["m\nn"]);
+        root.push_child(with_metadata);
+        root.push_child(TreeNode::alias("crlf", "t\r\nu"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().max_width(Some(10)).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        |-- a -> b\n\
+                        |   c\n\
+                        |-- n\u{2026} ->\u{2026}\n\
+                        |   y\n\
+                        |-- n\u{2026} ->\u{2026}\n\
+                        |   n\n\
+                        `-- c\u{2026} ->\u{2026}\n\
+                        \x20   u\n";
+        assert_eq!(got, expected);
+
+        for max_width in 1..=20 {
+            let config = RenderConfigBuilder::new()
+                .tree_config(TreeConfigBuilder::new().max_width(Some(max_width)).build())
+                .build();
+            let got = root.render(String::new(), &config)?;
+            for line in got.lines().skip(1) {
+                assert!(line.chars().count() <= max_width.max(5), "{:?}", got);
+            }
+            let config = RenderConfigBuilder::new()
+                .tree_config(
+                    TreeConfigBuilder::new()
+                        .max_label_width(Some(max_width))
+                        .build(),
+                )
+                .build();
+            let got = root.render(String::new(), &config)?;
+            for line in got.lines().skip(1) {
+                assert!(line.chars().count() <= max_width + 4, "{:?}", got);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn alias_target_style_without_colors() -> Result<()> {
+        use crate::ansi::{Color, ColorMode, TextStyle};
+
+        let mut root = TreeNode::new(".");
+        root.push_child(TreeNode::alias("foo", "bar"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .color_mode(ColorMode::Never)
+                    .build(),
+            )
+            .alias_target_style(*TextStyle::new().bold(true).fg(Some(Color::Red)))
+            .build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, ".\n`-- foo -> bar\n");
+        Ok(())
+    }
+
+    #[test]
+    fn expand_tabs() -> Result<()> {
+        let mut root = TreeNode::new("a\tb");
+        root.push_child(TreeNode::new("c\td"));
+        root.push_child(TreeNode::new("e"));
+        root.children_mut()[0].set_annotation(Some("1"));
+        root.children_mut()[1].set_annotation(Some("2"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().tab_width(Some(4)).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "a   b\n\
+                        |-- c   d  1\n\
+                        `-- e      2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn crlf() -> Result<()> {
+        let mut root = TreeNode::new("root\r\n");
+        root.push_child(TreeNode::new("foo\r\nbar"));
+        root.push_child(TreeNode::new("a\rb"));
+        root.children_mut()[0].set_annotation(Some("1"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .strip_carriage_returns(true)
+                    .build(),
+            )
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "root\n\
+                        |-- foo  1\n\
+                        |   bar\n\
+                        `-- ab\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_newline() -> Result<()> {
+        let mut root = TreeNode::new("root\nlabel");
+        root.push_child(TreeNode::new("foo\nbar"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().newline("\r\n").build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "root\r\nlabel\r\n`-- foo\r\n    bar\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn isolate_bidi() -> Result<()> {
+        let mut root = TreeNode::new("\u{5E9}\u{5DC}\u{5D5}\u{5DD}");
+        root.push_child(TreeNode::new("foo\n\nbar"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().isolate_bidi(true).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\u{2068}\u{5E9}\u{5DC}\u{5D5}\u{5DD}\u{2069}\n\
+                        `-- \u{2068}foo\u{2069}\n\
+                        \n\
+                        \x20   \u{2068}bar\u{2069}\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn mirrored() -> Result<()> {
+        let mut child = TreeNode::new("foo\n\nfoo2");
+        child.push_child(TreeNode::new("bar"));
+        let mut root = TreeNode::new("root");
+        root.push_child(child);
+        root.push_child(TreeNode::new("baz"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().mirrored(true).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\x20      root\n\
+                        \x20   foo --|\n\
+                        \x20         |\n\
+                        \x20  foo2   |\n\
+                        bar --'   |\n\
+                        \x20   baz --'\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn table() -> Result<()> {
+        let mut long = TreeNode::new("a long label");
+        long.push_child(TreeNode::new("x"));
+        let mut short = TreeNode::new("b");
+        short.set_metadata(vec!["1"]);
+        let mut root = TreeNode::new("root");
+        root.push_child(long);
+        root.push_child(short);
+
+        let got = root.render_table(String::new(), &RenderConfig::new(), &[])?;
+        let expected = "root\n\
+                        |-- a long label\n\
+                        |   `-- x\n\
+                        `-- b             1\n";
+        assert_eq!(got, expected);
+
+        let got = root.render_table(String::new(), &RenderConfig::new(), &["", "", "size"])?;
+        let expected = "\x20                    size\n\
+                        ----------------  -  ----\n\
+                        root\n\
+                        |-- a long label\n\
+                        |   `-- x\n\
+                        `-- b             1\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn table_root_metadata() -> Result<()> {
+        let mut child = TreeNode::new("child");
+        child.set_metadata(vec!["MIT"]);
+        let mut root = TreeNode::new("root");
+        root.set_metadata(vec!["a-very-long-license", "9"]);
+        root.push_child(child);
+
+        let got = root.render_table(String::new(), &RenderConfig::new(), &[])?;
+        let expected = "root       a-very-long-license  9\n\
+                        `-- child  MIT\n";
+        assert_eq!(got, expected);
+
+        let mut root = TreeNode::new("root");
+        root.set_metadata(vec!["X"]);
+        root.push_child(TreeNode::new("child"));
+
+        let got = root.render_table(String::new(), &RenderConfig::new(), &[])?;
+        assert_eq!(got, "root       X\n`-- child\n");
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .emit_trailing_whitespace(true)
+                    .build(),
+            )
+            .build();
+        let mut root = TreeNode::new("root");
+        root.set_metadata(vec!["X", ""]);
+        root.push_child(TreeNode::new("child"));
+        let got = root.render_table(String::new(), &config, &[])?;
+        assert_eq!(got, "root       X  \n`-- child\n");
+        Ok(())
+    }
+
+    #[test]
+    fn chart_layout() -> Result<()> {
+        let mut lead = TreeNode::new("lead");
+        lead.push_child(TreeNode::new("a"));
+        lead.push_child(TreeNode::new("b"));
+        lead.push_child(TreeNode::new("c"));
+        let mut root = TreeNode::new("project\nmanager");
+        root.push_child(lead);
+        root.push_child(TreeNode::new("qa"));
+
+        let config = RenderConfigBuilder::new()
+            .edge(EdgeConfig::UnicodeSingleWidth)
+            .layout(TreeLayout::Chart)
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\x20          \u{250C}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2510}\n\
+                        \x20          \u{2502} project \u{2502}\n\
+                        \x20          \u{2502} manager \u{2502}\n\
+                        \x20          \u{2514}\u{2500}\u{2500}\u{2500}\u{2500}\u{252C}\u{2500}\u{2500}\u{2500}\u{2500}\u{2518}\n\
+                        \x20        \u{250C}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2534}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2510}\n\
+                        \x20    \u{250C}\u{2500}\u{2500}\u{2500}\u{2534}\u{2500}\u{2500}\u{2510}        \u{250C}\u{2500}\u{2500}\u{2534}\u{2500}\u{2510}\n\
+                        \x20    \u{2502} lead \u{2502}        \u{2502} qa \u{2502}\n\
+                        \x20    \u{2514}\u{2500}\u{2500}\u{2500}\u{252C}\u{2500}\u{2500}\u{2518}        \u{2514}\u{2500}\u{2500}\u{2500}\u{2500}\u{2518}\n\
+                        \x20 \u{250C}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{253C}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2510}\n\
+                        \u{250C}\u{2500}\u{2534}\u{2500}\u{2510}  \u{250C}\u{2500}\u{2534}\u{2500}\u{2510}  \u{250C}\u{2500}\u{2534}\u{2500}\u{2510}\n\
+                        \u{2502} a \u{2502}  \u{2502} b \u{2502}  \u{2502} c \u{2502}\n\
+                        \u{2514}\u{2500}\u{2500}\u{2500}\u{2518}  \u{2514}\u{2500}\u{2500}\u{2500}\u{2518}  \u{2514}\u{2500}\u{2500}\u{2500}\u{2518}\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn columns_layout() -> Result<()> {
+        let mut root = TreeNode::new("root");
+        for i in 0..10 {
+            root.push_child(TreeNode::new(format!("n{:02}", i)));
+        }
+        let config = RenderConfigBuilder::new()
+            .layout(TreeLayout::Columns)
+            .tree_config(TreeConfigBuilder::new().max_width(Some(20)).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "root\n\
+                        n00  n03  n06  n09\n\
+                        n01  n04  n07\n\
+                        n02  n05  n08\n";
+        assert_eq!(got, expected);
+
+        // Too wide cells are put in a single column.
+        let mut root = TreeNode::new("root");
+        root.push_child(TreeNode::new("a".repeat(12)));
+        root.push_child(TreeNode::new("b".repeat(12)));
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "root\naaaaaaaaaaaa\nbbbbbbbbbbbb\n");
+
+        let leaf = TreeNode::new("leaf");
+        assert_eq!(leaf.render(String::new(), &config)?, "leaf\n");
+
+        // Shorter columns are padded if trailing whitespace is requested.
+        let mut root = TreeNode::new("root");
+        for label in &["a", "bb", "c"] {
+            root.push_child(TreeNode::new(*label));
+        }
+        let config = RenderConfigBuilder::new()
+            .layout(TreeLayout::Columns)
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .max_width(Some(6))
+                    .emit_trailing_whitespace(true)
+                    .build(),
+            )
+            .build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "root\na   c\nbb  \n");
+        Ok(())
+    }
+
+    #[test]
+    fn bottom_up() -> Result<()> {
+        let mut child = TreeNode::new("foo\nfoo2");
+        child.push_child(TreeNode::new("bar"));
+        let mut root = TreeNode::new("root");
+        root.push_child(child);
+        root.push_child(TreeNode::new("baz"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().bottom_up(true).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ",-- baz\n\
+                        |   ,-- bar\n\
+                        |-- foo\n\
+                        |   foo2\n\
+                        root\n";
+        assert_eq!(got, expected);
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(
+                TreeConfigBuilder::new()
+                    .bottom_up(true)
+                    .mirrored(true)
+                    .emit_trailing_newline(false)
+                    .build(),
+            )
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\x20   baz --,\n\
+                        bar --,   |\n\
+                        \x20   foo --|\n\
+                        \x20  foo2   |\n\
+                        \x20      root";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn control_characters_in_labels() -> Result<()> {
+        let mut child = TreeNode::new("a\u{1}b");
+        child.push_child(TreeNode::new("c\0d"));
+        let mut root = TreeNode::new("root");
+        root.push_child(child);
+        root.push_child(TreeNode::new("\u{1}"));
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().bottom_up(true).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = ",-- \u{1}\n\
+                        |   ,-- c\0d\n\
+                        |-- a\u{1}b\n\
+                        root\n";
+        assert_eq!(got, expected);
+
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().mirrored(true).build())
+            .build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\x20      root\n\
+                        \x20   a\u{1}b --|\n\
+                        c\0d --'   |\n\
+                        \x20     \u{1} --'\n";
+        assert_eq!(got, expected);
+
+        let config = RenderConfig::new();
+        let got = root.lines(&config).collect::<Vec<_>>();
+        assert_eq!(got, ["root", "|-- a\u{1}b", "|   `-- c\0d", "`-- \u{1}"]);
+        assert_eq!(got, root.render_lines(&config)?);
+        let got = root.pages(&config, 3).collect::<Vec<_>>();
+        let expected = [
+            vec!["root", "|-- a\u{1}b", "|   `-- c\0d"],
+            vec!["root", "`-- \u{1}"],
+        ];
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "nerd-fonts")]
+    #[test]
+    fn nerd_font_icons() -> Result<()> {
+        let mut root = TreeNode::new("root");
+        root.push_child(TreeNode::new("README.MD\ndocument"));
+        root.push_child(TreeNode::new("unknown"));
+
+        let config = RenderConfigBuilder::new().nerd_font_icons(true).build();
+        let got = root.render(String::new(), &config)?;
+        let expected = "\u{F15B} root\n\
+                        |-- \u{E73E} README.MD\n\
+                        |     document\n\
+                        `-- \u{F15B} unknown\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_lines() -> Result<()> {
+        let mut root = TreeNode::new("root\nlabel");
+        let mut foo = TreeNode::new("foo\nfoo2");
+        foo.set_annotation(Some("note"));
+        foo.set_metadata(["1 KiB"]);
+        foo.push_child(TreeNode::new("baz"));
+        root.push_child(foo);
+        let mut bar = TreeNode::new("bar");
+        bar.set_metadata(["12 KiB"]);
+        root.push_child(bar);
+
+        let configs = [
+            RenderConfig::new(),
+            RenderConfigBuilder::new().reverse_children(true).build(),
+            RenderConfigBuilder::new()
+                .traversal_order(TraversalOrder::BreadthFirst)
+                .build(),
+        ];
+        for config in &configs {
+            let got = root.lines(config).collect::<Vec<_>>();
+            assert_eq!(got, root.render_lines(config)?);
+        }
+
+        let leaf = TreeNode::new("");
+        assert_eq!(
+            leaf.lines(&RenderConfig::new()).collect::<Vec<_>>(),
+            leaf.render_lines(&RenderConfig::new())?
+        );
+
+        let mut lines = root.lines(&configs[0]);
+        assert_eq!(lines.nth(2).as_deref(), Some("|-- foo  note  1 KiB"));
+        Ok(())
+    }
+
+    #[test]
+    fn pages() {
+        let mut bar = TreeNode::new("bar\nbar2\nbar3");
+        bar.push_child(TreeNode::new("baz"));
+        let mut foo = TreeNode::new("foo");
+        foo.push_child(bar);
+        let mut root = TreeNode::new(".");
+        root.push_child(foo);
+        root.push_child(TreeNode::new("qux"));
+
+        let config = RenderConfig::new();
+        let got = root.pages(&config, 4).collect::<Vec<_>>();
+        let expected = [
+            vec![".", "|-- foo", "|   `-- bar", "|       bar2"],
+            vec![".", "|-- foo", "|   `-- bar", "|       bar3"],
+            vec![".", "|-- foo", "|   `-- bar", "|       `-- baz"],
+            vec![".", "`-- qux"],
+        ];
+        assert_eq!(got, expected);
+
+        // Only the nearest ancestors are repeated in short pages.
+        let got = root.pages(&config, 2).nth(2);
+        assert_eq!(got, Some(vec!["|   `-- bar".into(), "|       bar2".into()]));
+
+        let config = RenderConfigBuilder::new()
+            .traversal_order(TraversalOrder::BreadthFirst)
+            .build();
+        let got = root.pages(&config, 4).flatten().collect::<Vec<_>>();
+        assert_eq!(got, root.lines(&config).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn max_lines() -> Result<()> {
+        let mut root = TreeNode::new("root\nlabel");
+        for name in ["foo", "bar", "baz"] {
+            root.push_child(TreeNode::new(name));
+        }
+        let config = RenderConfigBuilder::new()
+            .tree_config(TreeConfigBuilder::new().max_lines(Some(3)).build())
+            .build();
+        assert_eq!(
+            root.lines(&config).collect::<Vec<_>>(),
+            root.render_lines(&config)?
+        );
+        let got = root.render(String::new(), &config)?;
+        let expected = "root\n\
+                        label\n\
+                        |-- foo\n\
+                        \u{2026} output truncated, 2 nodes not shown\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn root_only() -> Result<()> {
+        let got = TreeNode::new("root").render(String::new(), &RenderConfig::new())?;
+        assert_eq!(got, "root\n");
+        Ok(())
+    }
+
+    #[test]
+    fn display() {
+        let mut root = TreeNode::new(".");
+        root.push_child(TreeNode::new("foo\nbar"));
+        assert_eq!(root.to_string(), ".\n`-- foo\n    bar\n");
+    }
+
+    #[test]
+    fn merge_same_root() {
+        let mut left = chain(&["r", "a", "b"]);
+        left.push_child(TreeNode::new("c"));
+        let right = chain(&["r", "a", "d"]);
+
+        let merged = left.merge(&right, &MergeConflict::KeepOther);
+        assert_eq!(
+            merged.to_string(),
+            "r\n|-- a\n|   |-- b\n|   `-- d\n`-- c\n"
+        );
+    }
+
+    #[test]
+    fn merge_conflicting_roots() {
+        let left = chain(&["left", "a"]);
+        let right = chain(&["right", "a"]);
+
+        assert_eq!(left.merge(&right, &MergeConflict::KeepSelf).label(), "left");
+        assert_eq!(
+            left.merge(&right, &MergeConflict::KeepOther).label(),
+            "right"
+        );
+        assert_eq!(
+            left.merge(&right, &MergeConflict::KeepSelf)
+                .children()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn prune_nested() {
+        let mut root = chain(&["r", "a", "b", "c"]);
+        root.push_child(TreeNode::new("b"));
+
+        let elided = root.prune_with(|node| node.label() == "b", |n| format!("({})", n));
+        assert_eq!(elided, 3);
+        assert_eq!(root.to_string(), "r\n|-- a\n|   `-- (2)\n`-- (1)\n");
+    }
+
+    #[test]
+    fn prune_never_replaces_root() {
+        let mut root = chain(&["r", "a"]);
+        assert_eq!(root.prune(|node| node.label() == "r"), 0);
+        assert_eq!(root.node_count(), 2);
+
+        assert_eq!(root.prune(|_| true), 1);
+        assert_eq!(root.to_string(), "r\n`-- <elided: 1 node>\n");
+    }
+
+    #[test]
+    fn highlight_multiline_and_root() {
+        let mut root = chain(&["match", "foo\n\nmatch"]);
+        root.push_child(TreeNode::new("bar"));
+
+        let count = root.highlight_by(|label| label.contains("match"), &Highlight::Ansi);
+        assert_eq!(count, 2);
+        let expected = "\x1b[7mmatch\x1b[27m\n\
+                        |-- \x1b[7mfoo\x1b[27m\n\
+                        |\n\
+                        |   \x1b[7mmatch\x1b[27m\n\
+                        `-- bar\n";
+        assert_eq!(root.to_string(), expected);
+    }
+
+    #[test]
+    fn breadth_first_with_multiline_labels() -> Result<()> {
+        let mut root = chain(&["r", "a\na2", "b"]);
+        root.push_child(chain(&["c", "d"]));
+        let config = RenderConfigBuilder::new()
+            .traversal_order(TraversalOrder::BreadthFirst)
+            .build();
+
+        let got = root.render(String::new(), &config)?;
+        let expected = "r\n\
+                        |-- depth 1\n\
+                        |   |-- a\n\
+                        |   |   a2\n\
+                        |   `-- c\n\
+                        `-- depth 2\n    \
+                            |-- b\n    \
+                            `-- d\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reverse_children() -> Result<()> {
+        let mut root = chain(&["r", "a", "a1"]);
+        root.children_mut()[0].push_child(TreeNode::new("a2"));
+        root.push_child(TreeNode::new("b"));
+
+        let config = RenderConfigBuilder::new().reverse_children(true).build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "r\n|-- b\n`-- a\n    |-- a2\n    `-- a1\n");
+
+        root.children_mut()[0].set_reverse_children(Some(false));
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "r\n|-- b\n`-- a\n    |-- a1\n    `-- a2\n");
+
+        root.set_reverse_children(Some(false));
+        root.children_mut()[0].set_reverse_children(Some(true));
+        let got = root.render(String::new(), &RenderConfig::new())?;
+        assert_eq!(got, "r\n|-- a\n|   |-- a2\n|   `-- a1\n`-- b\n");
+        Ok(())
+    }
+
+    #[test]
+    fn collapsed_node() -> Result<()> {
+        let mut root = chain(&["r", "a\na2", "b", "c"]);
+        root.children_mut()[0].set_collapsed(true);
+        root.push_child(TreeNode::new("d\nd2"));
+        root.children_mut()[1].set_collapsed(true);
+
+        let config = RenderConfigBuilder::new()
+            .collapsed_suffix(" [+{}]")
+            .build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "r\n|-- a [+2]\n`-- d\n");
+        Ok(())
+    }
+
+    #[test]
+    fn collapsed_node_stops_chain_compression() -> Result<()> {
+        let mut root = chain(&["r", "a", "b", "c", "d"]);
+        root.children_mut()[0].children_mut()[0].set_collapsed(true);
+
+        let config = RenderConfigBuilder::new()
+            .compress_chains(Some("/"))
+            .build();
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "r\n`-- a/b \u{2026} (2 hidden)\n");
+        Ok(())
+    }
+
+    #[test]
+    fn collapsed_root() -> Result<()> {
+        let mut root = chain(&["r", "a"]);
+        root.set_collapsed(true);
+        let config = RenderConfigBuilder::new()
+            .fold_markers(Some(FoldMarkers::default()))
+            .build();
+
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, "[+] r \u{2026} (1 hidden)\n");
+        Ok(())
+    }
+
+    #[test]
+    fn fold_markers() -> Result<()> {
+        let mut root = chain(&["r", "a", "b"]);
+        root.push_child(chain(&["c", "d"]));
+        root.children_mut()[1].set_collapsed(true);
+        root.push_child(TreeNode::new("e\ne2"));
+        let config = RenderConfigBuilder::new()
+            .fold_markers(Some(FoldMarkers::new("+", "-", "*")))
+            .collapsed_suffix("")
+            .build();
+
+        let got = root.render(String::new(), &config)?;
+        let expected = "- r\n\
+                        |-- - a\n\
+                        |   `-- * b\n\
+                        |-- + c\n\
+                        `-- * e\n    \
+                            e2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn builder_extra_close() -> Result<()> {
+        let mut builder = TreeBuilder::new("r");
+        builder.open_node(42)?;
+        builder.close_node()?;
+        assert!(matches!(builder.close_node(), Err(Error::ExtraNodeClose)));
+        assert_eq!(builder.finalize(), chain(&["r", "42"]));
+        Ok(())
+    }
+
+    #[test]
+    fn chain_compression_to_leaf() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(chain(&["a", "b", "c"]))
+            .push_child(TreeNode::new("d"));
+        let config = RenderConfigBuilder::new()
+            .compress_chains(Some("."))
+            .build();
+
+        let got = root.render(String::new(), &config)?;
+        assert_eq!(got, ".\n|-- a.b.c\n`-- d\n");
+        Ok(())
+    }
+
+    #[test]
+    fn chain_compression_stops_at_multiline_label() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(chain(&["a", "b", "c\nc2", "d"]));
+        let config = RenderConfigBuilder::new()
+            .compress_chains(Some("/"))
+            .build();
+
+        let got = root.render(String::new(), &config)?;
+        let expected = ".\n\
+                        `-- a/b\n    \
+                            `-- c\n        \
+                                c2\n        \
+                                `-- d\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn no_chain_compression_by_default() -> Result<()> {
+        let mut root = TreeNode::new(".");
+        root.push_child(chain(&["a", "b"]));
+
+        let got = root.render(String::new(), &RenderConfig::new())?;
+        assert_eq!(got, ".\n`-- a\n    `-- b\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_roundtrip() {
+        let mut root = chain(&["a", "b\n\"quoted\""]);
+        root.push_child(TreeNode::new("c"));
+        root.set_collapsed(true);
+
+        let restored = TreeNode::from_json(&root.to_json()).expect("should be valid JSON");
+        root.set_collapsed(false);
+        assert_eq!(restored, root);
+    }
+
+    #[test]
+    fn diff_multiline_and_root_label() {
+        let old = chain(&["r", "a\n\nb"]);
+        let new = chain(&["s", "a\n\nb"]);
+        let diff = old.diff(&new);
+        assert_eq!(diff.to_string(), "~ s\n`--   a\n\n      b\n");
+
+        let mut new = chain(&["r", "a\n\nb"]);
+        new.push_child(TreeNode::new("x"));
+        new.children_mut()[1].push_child(TreeNode::new("y"));
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.to_string(),
+            "~ r\n|--   a\n|\n|     b\n`-- + x\n    `-- + y\n"
+        );
+    }
+}
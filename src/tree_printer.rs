@@ -1,13 +1,19 @@
 //! Tree printer.
 
 use std::{
+    borrow::Cow,
     error,
     fmt::{self, Write},
+    io::{self, IsTerminal},
+    mem,
+    sync::Arc,
 };
 
 use crate::{
-    config::{ItemStyle, TreeConfig},
-    item_writer::ItemState,
+    config::{ItemStyle, NodeKind, TreeConfig},
+    item_writer::{ItemState, LineWrite},
+    mirror,
+    quote::quote_label,
 };
 
 /// Tree print result.
@@ -20,6 +26,10 @@ pub enum Error {
     ExtraNodeClose,
     /// Backend formatter error.
     Format(fmt::Error),
+    /// Backend I/O error.
+    ///
+    /// The error is shared so that `Error` can be cloned.
+    Io(Arc<io::Error>),
 }
 
 impl fmt::Display for Error {
@@ -29,6 +39,7 @@ impl fmt::Display for Error {
                 f.write_str("Attempt to close a node but there are no open nodes")
             }
             Self::Format(e) => write!(f, "Backend formatter error: {}", e),
+            Self::Io(e) => write!(f, "Backend I/O error: {}", e),
         }
     }
 }
@@ -37,6 +48,7 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::Format(e) => Some(e),
+            Self::Io(e) => Some(&**e),
             _ => None,
         }
     }
@@ -48,43 +60,307 @@ impl From<fmt::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(Arc::new(e))
+    }
+}
+
+/// Output of the tree printer.
+struct Output<W> {
+    /// Inner writer.
+    writer: W,
+    /// Buffer for the tree to be reordered or mirrored on finalization.
+    buffer: Option<String>,
+    /// Byte offsets in the buffer where the contents of lines start, for mirrored trees.
+    content_starts: Vec<usize>,
+    /// Limit of the number of lines written to the inner writer.
+    budget: Option<LineBudget>,
+    /// Number of bytes written to the buffer or the inner writer.
+    len: usize,
+}
+
+impl<W: fmt::Write> fmt::Write for Output<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = match (&self.buffer, &mut self.budget) {
+            (None, Some(budget)) => budget.take(s),
+            _ => s,
+        };
+        match &mut self.buffer {
+            Some(buf) => buf.write_str(s)?,
+            None => self.writer.write_str(s)?,
+        }
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> LineWrite for Output<W> {
+    fn mark_content_start(&mut self) {
+        if self.buffer.is_some() {
+            self.content_starts.push(self.len);
+        }
+    }
+}
+
+/// Limit of the number of output lines.
+struct LineBudget {
+    /// Maximum number of lines.
+    max_lines: usize,
+    /// Newline sequence.
+    newline: String,
+    /// Number of newlines written.
+    lines: usize,
+    /// Whether any text is discarded.
+    truncated: bool,
+}
+
+impl LineBudget {
+    /// Returns the part of the text within the limit.
+    fn take<'a>(&mut self, s: &'a str) -> &'a str {
+        let mut end = 0;
+        while self.lines < self.max_lines {
+            match s[end..].find(&self.newline) {
+                Some(pos) => {
+                    end += pos + self.newline.len();
+                    self.lines += 1;
+                }
+                None => return s,
+            }
+        }
+        if end < s.len() {
+            self.truncated = true;
+        }
+        &s[..end]
+    }
+}
+
 /// Tree printer.
 pub struct TreePrinter<W> {
     /// Writer.
-    writer: W,
+    writer: Output<W>,
     /// Options.
     opts: TreeConfig,
     /// Item writer states for each nest level.
     states: Vec<ItemState>,
+    /// Whether the cursor was at the beginning of a line when the last node is closed.
+    at_line_head: bool,
+    /// Index of the current output line.
+    line_index: usize,
+    /// Number of closed nodes with children.
+    num_branches: usize,
+    /// Number of closed nodes without children.
+    num_leaves: usize,
+    /// Number of nodes starting after the line limit.
+    num_hidden: usize,
+    /// Byte offsets in the output where items start, if recorded.
+    item_starts: Vec<usize>,
 }
 
 impl<W: fmt::Write> TreePrinter<W> {
     /// Creates a new `TreePrinter`.
+    ///
+    /// `ColorMode::Auto` treats the writer as not a terminal.
     pub fn new(writer: W, opts: TreeConfig) -> Self {
+        Self::with_terminal(writer, opts, false)
+    }
+
+    /// Creates a new `TreePrinter`, resolving `ColorMode::Auto` with whether the destination is
+    /// a terminal.
+    pub(crate) fn with_terminal(writer: W, opts: TreeConfig, is_terminal: bool) -> Self {
+        #[cfg(feature = "ansi")]
+        let opts = {
+            let mut opts = opts;
+            opts.resolve_color_mode(is_terminal);
+            opts
+        };
+        #[cfg(not(feature = "ansi"))]
+        let _ = is_terminal;
+        let buffer = (opts.is_mirrored() || opts.is_bottom_up()).then(String::new);
+        let budget = opts.max_lines().map(|max_lines| LineBudget {
+            max_lines,
+            newline: opts.newline().to_owned(),
+            lines: 0,
+            truncated: false,
+        });
         Self {
-            writer,
+            writer: Output {
+                writer,
+                buffer,
+                content_starts: Vec::new(),
+                budget,
+                len: 0,
+            },
             opts,
             states: Vec::new(),
+            at_line_head: true,
+            line_index: 0,
+            num_branches: 0,
+            num_leaves: 0,
+            num_hidden: 0,
+            item_starts: Vec::new(),
         }
     }
 
     /// Opens a new node with the given content.
     pub fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
         // Go to newline before emitting new node.
-        if !self.states.is_empty() {
+        if let Some(parent) = self.states.last_mut() {
+            parent.set_has_children();
             self.opts
-                .writer(&mut self.writer, &mut self.states)
+                .writer(&mut self.writer, &mut self.states, &mut self.line_index)
                 .go_to_next_line()?;
         }
+        // Items of bottom-up trees are reordered on finalization, and hidden later.
+        let hidden = !self.opts.is_bottom_up()
+            && self
+                .opts
+                .max_lines()
+                .is_some_and(|max_lines| self.line_index >= max_lines);
+        if hidden {
+            self.num_hidden += 1;
+        } else if self.opts.marks_items() {
+            self.item_starts.push(self.writer.len);
+        }
 
+        let annotation = style.annotation().map(ToOwned::to_owned);
+        let kind = style.kind().cloned();
+        let icon = style.icon().map(ToOwned::to_owned);
+        let mut label_tail_len = style.label_tail_len();
+        let parent_edge = self.states.last().map(ItemState::edge);
+        let style = style.resolve(&self.opts, self.states.len(), parent_edge);
+        let box_chars = if style.is_boxed() {
+            Some(style.edge().box_chars())
+        } else {
+            None
+        };
         self.states.push(style.into());
-        self.opts
-            .writer(&mut self.writer, &mut self.states)
-            .write_fmt(format_args!("{}", content))?;
+        let (last, ancestors) = self
+            .states
+            .split_last()
+            .expect("Should never fail: a state is pushed just now");
+        let guide_width = ancestors
+            .iter()
+            .map(|state| state.guide_width(&self.opts))
+            .sum::<usize>();
+        let prefix_width = guide_width + last.prefix_width(&self.opts);
+        let continuation_width = guide_width + last.continuation_width(&self.opts);
+        let label_widths = self.label_widths(
+            prefix_width,
+            continuation_width,
+            annotation.as_deref(),
+            kind.as_ref(),
+            icon.as_deref(),
+            box_chars.is_some(),
+        );
+        let mut writer = self
+            .opts
+            .writer(&mut self.writer, &mut self.states, &mut self.line_index);
+        let needs_buffer = self.opts.quote_labels()
+            || self.opts.expands_tabs()
+            || label_widths.is_some()
+            || annotation.is_some()
+            || kind.is_some()
+            || icon.is_some()
+            || box_chars.is_some();
+        if needs_buffer {
+            let mut label = content.to_string();
+            // The tail cannot be found once the label is rewritten.
+            if let Cow::Owned(normalized) = self.opts.normalize_newlines(&label) {
+                label = normalized;
+                label_tail_len = 0;
+            }
+            if self.opts.quote_labels() {
+                if let Cow::Owned(quoted) = quote_label(&label) {
+                    label = quoted;
+                    label_tail_len = 0;
+                }
+            }
+            if let Cow::Owned(expanded) = self.opts.expand_tabs(&label) {
+                label = expanded;
+                label_tail_len = 0;
+            }
+            if let Some((first_max, rest_max)) = label_widths {
+                label = self
+                    .opts
+                    .fit_lines_with_tail(&label, label_tail_len, first_max, rest_max);
+            }
+            if let Some(kind) = kind {
+                let first_line_end = label.find('\n').unwrap_or(label.len());
+                label.insert_str(first_line_end, kind.suffix());
+            }
+            if let Some(icon) = icon {
+                label = self.opts.prefix_icon(&label, &icon);
+            }
+            if let Some(box_chars) = box_chars {
+                label = box_chars.wrap(&label, &self.opts);
+            }
+            if let Some(annotation) = annotation {
+                let column = match self.opts.max_width() {
+                    Some(max_width) => self.opts.annotation_column().min(max_width),
+                    None => self.opts.annotation_column(),
+                };
+                let first_line = label.split('\n').next().unwrap_or_default();
+                let width = column
+                    .saturating_sub(
+                        prefix_width
+                            + self.opts.str_width(first_line)
+                            + self.opts.str_width(&annotation),
+                    )
+                    .max(self.opts.annotation_gap());
+                label = self.opts.annotate_first_line(&label, &annotation, width);
+            }
+            writer.write_str(&label)?;
+        } else {
+            writer.write_fmt(format_args!("{}", content))?;
+        }
 
         Ok(())
     }
 
+    /// Returns the maximum display widths of the first line and the following lines of the
+    /// label, if limited.
+    fn label_widths(
+        &self,
+        prefix_width: usize,
+        continuation_width: usize,
+        annotation: Option<&str>,
+        kind: Option<&NodeKind>,
+        icon: Option<&str>,
+        boxed: bool,
+    ) -> Option<(usize, usize)> {
+        let max_label_width = self.opts.max_label_width();
+        let max_width = match self.opts.max_width() {
+            Some(v) => v,
+            None => return max_label_width.map(|max| (max, max)),
+        };
+
+        let annotation_width = annotation.map_or(0, |annotation| {
+            self.opts.str_width(annotation) + self.opts.annotation_gap()
+        });
+        let kind_width = kind.map_or(0, |kind| self.opts.str_width(kind.suffix()));
+        let icon_width = icon.map_or(0, |icon| self.opts.str_width(icon) + 1);
+        let (first, rest) = if boxed {
+            // The box is as wide as the content, and its top border is put on the first line
+            // with the annotation.
+            let content = max_width
+                .saturating_sub(prefix_width.max(continuation_width) + annotation_width + 4);
+            (content, content)
+        } else {
+            (
+                max_width.saturating_sub(prefix_width + annotation_width),
+                max_width.saturating_sub(continuation_width),
+            )
+        };
+        let first = first.saturating_sub(kind_width + icon_width);
+        let rest = rest.saturating_sub(icon_width);
+
+        match max_label_width {
+            Some(max) => Some((first.min(max), rest.min(max))),
+            None => Some((first, rest)),
+        }
+    }
+
     /// Closes a node.
     pub fn close_node(&mut self) -> Result<()> {
         if self.states.is_empty() {
@@ -92,26 +368,355 @@ impl<W: fmt::Write> TreePrinter<W> {
             return Err(Error::ExtraNodeClose);
         }
 
-        if self.opts.emit_trailing_newline() {
+        self.opts
+            .writer(&mut self.writer, &mut self.states, &mut self.line_index)
+            .flush_carriage_return()?;
+        let last = self
+            .states
+            .last()
+            .expect("Should never fail: `states` is checked to be non-empty");
+        let emit_spacer =
+            self.opts.emit_spacer_rows() && last.has_children() && !last.is_last_child();
+        if self.opts.emit_trailing_newline() || emit_spacer {
             // Go to newline automatically at the end of a node.
             self.opts
-                .writer(&mut self.writer, &mut self.states)
+                .writer(&mut self.writer, &mut self.states, &mut self.line_index)
                 .go_to_next_line()?;
         }
+        if emit_spacer {
+            // Emit guides only, to separate the subtree from the next sibling.
+            self.opts
+                .writer(&mut self.writer, &mut self.states, &mut self.line_index)
+                .write_str("\n")?;
+        }
 
-        self.states.pop();
+        let state = self
+            .states
+            .pop()
+            .expect("Should never fail: `states` is checked to be non-empty");
+        self.at_line_head = state.is_at_line_head();
+        if state.has_children() {
+            self.num_branches += 1;
+        } else {
+            self.num_leaves += 1;
+        }
 
         Ok(())
     }
 
+    /// Writes the summary line.
+    fn write_summary_footer(&mut self) -> fmt::Result {
+        let newline = self.opts.newline();
+        if !self.at_line_head {
+            self.writer.write_str(newline)?;
+        }
+        let branches = if self.num_branches == 1 {
+            "branch"
+        } else {
+            "branches"
+        };
+        let leaves = if self.num_leaves == 1 {
+            "leaf"
+        } else {
+            "leaves"
+        };
+        write!(
+            self.writer,
+            "{}{} {}, {} {}",
+            newline, self.num_branches, branches, self.num_leaves, leaves
+        )?;
+        if self.opts.emit_trailing_newline() {
+            self.writer.write_str(newline)?;
+        }
+        Ok(())
+    }
+
     /// Finishes writing the tree and returns the inner writer.
     pub fn finalize(mut self) -> Result<W> {
+        self.finish()?;
+        Ok(self.writer.writer)
+    }
+
+    /// Returns the inner writer.
+    ///
+    /// Note that the tree may be buffered until finished.
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        &mut self.writer.writer
+    }
+
+    /// Returns the byte offsets where the items opened since the last call start.
+    ///
+    /// Bytes are counted from the creation of the printer, and hidden items are not included.
+    /// Items are recorded only if the tree config asks to.
+    pub(crate) fn take_item_starts(&mut self) -> Vec<usize> {
+        mem::take(&mut self.item_starts)
+    }
+
+    /// Closes the open nodes and writes the buffered tree and the summary footer.
+    pub(crate) fn finish(&mut self) -> Result<()> {
         for _ in 0..self.states.len() {
             self.close_node()?;
         }
         assert!(self.states.is_empty());
 
-        Ok(self.writer)
+        if let Some(mut buf) = self.writer.buffer.take() {
+            let mut content_starts = mem::take(&mut self.writer.content_starts);
+            if self.opts.is_bottom_up() {
+                if let Some(max_lines) = self.opts.max_lines() {
+                    self.num_hidden = self.count_hidden_bottom_up(&buf, max_lines);
+                }
+                buf =
+                    mirror::reverse_items(&buf, &self.item_starts, &mut content_starts, &self.opts);
+            }
+            if self.opts.is_mirrored() {
+                buf = mirror::mirror_lines(&buf, &content_starts, &self.opts);
+            }
+            self.writer.write_str(&buf)?;
+        }
+        if self.opts.emit_summary_footer() {
+            self.write_summary_footer()?;
+        }
+        if self
+            .writer
+            .budget
+            .as_ref()
+            .is_some_and(|budget| budget.truncated)
+        {
+            self.write_truncation_marker()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of items of the buffered bottom-up tree which start after the line
+    /// limit once reordered.
+    fn count_hidden_bottom_up(&self, buf: &str, max_lines: usize) -> usize {
+        let newline = self.opts.newline();
+        let mut num_preceding_lines = 0;
+        let mut num_hidden = 0;
+        // The item ending last comes first.
+        for range in mirror::item_ranges(&self.item_starts, buf.len()).rev() {
+            if num_preceding_lines >= max_lines {
+                num_hidden += 1;
+            }
+            num_preceding_lines += split_lines(&buf[range], newline).len();
+        }
+        num_hidden
+    }
+
+    /// Writes the marker line telling that the output is truncated, ignoring the line limit.
+    fn write_truncation_marker(&mut self) -> fmt::Result {
+        let nodes = if self.num_hidden == 1 {
+            "node"
+        } else {
+            "nodes"
+        };
+        write!(
+            self.writer.writer,
+            "\u{2026} output truncated, {} {} not shown",
+            self.num_hidden, nodes
+        )?;
+        if self.opts.emit_trailing_newline() {
+            self.writer.writer.write_str(self.opts.newline())?;
+        }
+        Ok(())
+    }
+}
+
+impl TreePrinter<String> {
+    /// Finishes writing the tree and returns the lines of the output, without newlines.
+    ///
+    /// The output is split by the newline of the tree config, and the trailing newline does not
+    /// produce an empty line.
+    /// Text written to the string before the printer is created is also included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{EdgeConfig, ItemStyle, TreeConfig, TreePrinter};
+    ///
+    /// let mut printer = TreePrinter::new(".\n".to_owned(), TreeConfig::new());
+    /// printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\nfoo2")?;
+    /// printer.close_node()?;
+    /// printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+    /// let lines = printer.finalize_lines()?;
+    ///
+    /// assert_eq!(lines, [".", "|-- foo", "|   foo2", "`-- bar"]);
+    /// # plaintextree::Result::Ok(())
+    /// ```
+    pub fn finalize_lines(self) -> Result<Vec<String>> {
+        let newline = self.opts.newline().to_owned();
+        let output = self.finalize()?;
+        Ok(split_lines(&output, &newline))
+    }
+}
+
+/// Splits the output into lines without newlines.
+///
+/// The trailing newline does not produce an empty line.
+pub(crate) fn split_lines(output: &str, newline: &str) -> Vec<String> {
+    if output.is_empty() {
+        return Vec::new();
+    }
+    let body = output.strip_suffix(newline).unwrap_or(output);
+    body.split(newline).map(ToOwned::to_owned).collect()
+}
+
+/// Destination of the output of a [`BackendPrinter`].
+pub(crate) trait Backend {
+    /// Writes the text.
+    fn write(&mut self, s: &str) -> Result<()>;
+
+    /// Flushes the destination after the whole tree is written.
+    fn flush(&mut self) -> Result<()>;
+}
+
+/// Tree printer writing the output of each method to the backend before the method returns.
+///
+/// Mirrored and bottom-up trees are buffered until finalized.
+pub(crate) struct BackendPrinter<B> {
+    /// Tree printer writing to the pending output.
+    printer: TreePrinter<String>,
+    /// Backend.
+    backend: B,
+}
+
+impl<B: Backend> BackendPrinter<B> {
+    /// Creates a new `BackendPrinter`, resolving `ColorMode::Auto` with whether the destination
+    /// is a terminal.
+    pub(crate) fn new(backend: B, opts: TreeConfig, is_terminal: bool) -> Self {
+        Self {
+            printer: TreePrinter::with_terminal(String::new(), opts, is_terminal),
+            backend,
+        }
+    }
+
+    /// Opens a new node with the given content.
+    pub(crate) fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content)?;
+        self.write_pending()
+    }
+
+    /// Closes a node.
+    pub(crate) fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node()?;
+        self.write_pending()
+    }
+
+    /// Finishes writing the tree, flushes the backend, and returns it.
+    pub(crate) fn finalize(mut self) -> Result<B> {
+        self.printer.finish()?;
+        self.write_pending()?;
+        self.backend.flush()?;
+        Ok(self.backend)
+    }
+
+    /// Writes the pending output to the backend.
+    fn write_pending(&mut self) -> Result<()> {
+        let pending = mem::take(self.printer.inner_mut());
+        if !pending.is_empty() {
+            self.backend.write(&pending)?;
+        }
+        Ok(())
+    }
+}
+
+/// Backend writing UTF-8 text to an `io::Write`.
+struct IoBackend<W>(W);
+
+impl<W: io::Write> Backend for IoBackend<W> {
+    fn write(&mut self, s: &str) -> Result<()> {
+        self.0.write_all(s.as_bytes())?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// Tree printer writing to an `io::Write`.
+///
+/// This is the same as [`TreePrinter`] except that the tree is written to `std::io::Write`
+/// as UTF-8 text, so that the output need not be buffered into a `String` first.
+/// The output of each method is written before the method returns, except for mirrored and
+/// bottom-up trees, which are buffered until finalized.
+/// I/O errors are returned as [`Error::Io`].
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{EdgeConfig, IoTreePrinter, ItemStyle, TreeConfig};
+///
+/// let mut printer = IoTreePrinter::new(Vec::new(), TreeConfig::new());
+/// printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")?;
+/// printer.close_node()?;
+/// printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+/// let buf = printer.finalize()?;
+///
+/// assert_eq!(buf, b"|-- foo\n`-- bar\n");
+/// # plaintextree::Result::Ok(())
+/// ```
+///
+/// [`TreePrinter`]: struct.TreePrinter.html
+/// [`Error::Io`]: enum.Error.html#variant.Io
+pub struct IoTreePrinter<W> {
+    /// Tree printer.
+    printer: BackendPrinter<IoBackend<W>>,
+}
+
+impl<W: io::Write + IsTerminal> IoTreePrinter<W> {
+    /// Creates a new `IoTreePrinter`, resolving `ColorMode::Auto` with whether the writer is a
+    /// terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use plaintextree::{IoTreePrinter, TreeConfig};
+    ///
+    /// let printer = IoTreePrinter::with_terminal_detection(std::io::stdout(), TreeConfig::new());
+    /// ```
+    pub fn with_terminal_detection(writer: W, opts: TreeConfig) -> Self {
+        let is_terminal = writer.is_terminal();
+        Self {
+            printer: BackendPrinter::new(IoBackend(writer), opts, is_terminal),
+        }
+    }
+}
+
+impl<W: io::Write> IoTreePrinter<W> {
+    /// Creates a new `IoTreePrinter`.
+    ///
+    /// `ColorMode::Auto` treats the writer as not a terminal.
+    /// Use [`with_terminal_detection()`](#method.with_terminal_detection) for terminals.
+    pub fn new(writer: W, opts: TreeConfig) -> Self {
+        Self {
+            printer: BackendPrinter::new(IoBackend(writer), opts, false),
+        }
+    }
+
+    /// Opens a new node with the given content.
+    ///
+    /// See [`TreePrinter::open_node()`].
+    ///
+    /// [`TreePrinter::open_node()`]: struct.TreePrinter.html#method.open_node
+    pub fn open_node(&mut self, style: ItemStyle, content: impl fmt::Display) -> Result<()> {
+        self.printer.open_node(style, content)
+    }
+
+    /// Closes a node.
+    ///
+    /// See [`TreePrinter::close_node()`].
+    ///
+    /// [`TreePrinter::close_node()`]: struct.TreePrinter.html#method.close_node
+    pub fn close_node(&mut self) -> Result<()> {
+        self.printer.close_node()
+    }
+
+    /// Finishes writing the tree, flushes the inner writer, and returns it.
+    pub fn finalize(self) -> Result<W> {
+        Ok(self.printer.finalize()?.0)
     }
 }
 
@@ -119,7 +724,83 @@ impl<W: fmt::Write> TreePrinter<W> {
 mod tests {
     use super::*;
 
-    use crate::config::EdgeConfig;
+    use crate::{
+        config::{EdgeConfig, LabelOverflow, TreeConfigBuilder},
+        width::AmbiWidth,
+    };
+
+    /// Writer which fails after the given number of bytes.
+    struct FailingWriter {
+        /// Number of bytes to accept.
+        remaining: usize,
+    }
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+            }
+            let len = buf.len().min(self.remaining);
+            self.remaining -= len;
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lines() -> Result<()> {
+        assert_eq!(split_lines("", "\n"), Vec::<String>::new());
+        assert_eq!(split_lines("\n", "\n"), [""]);
+        assert_eq!(split_lines("a\r\n\r\nb", "\r\n"), ["a", "", "b"]);
+
+        let opts = TreeConfigBuilder::new()
+            .newline("\r\n")
+            .emit_trailing_newline(false)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\nfoo2")?;
+        assert_eq!(printer.finalize_lines()?, ["`-- foo", "    foo2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_split_across_chunks() -> Result<()> {
+        /// Label written in the given chunks.
+        struct Chunks(&'static [&'static str]);
+
+        impl fmt::Display for Chunks {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.iter().try_for_each(|chunk| f.write_str(chunk))
+            }
+        }
+
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        printer.open_node(
+            ItemStyle::non_last(EdgeConfig::Ascii),
+            Chunks(&["a\r", "\nb"]),
+        )?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), Chunks(&["c\r"]))?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), Chunks(&["d\r"]))?;
+        assert_eq!(printer.finalize()?, "|-- a\n|   b\n|   `-- c\r\n`-- d\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn io_errors() {
+        let mut printer = IoTreePrinter::new(FailingWriter { remaining: 8 }, TreeConfig::new());
+        printer
+            .open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo")
+            .expect("fits in the writer");
+        let got = printer
+            .open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")
+            .expect_err("overflows the writer");
+        assert!(matches!(got.clone(), Error::Io(e) if e.kind() == io::ErrorKind::BrokenPipe));
+    }
 
     fn emit_test_tree(edge: EdgeConfig) -> Result<String> {
         let mut buf = String::new();
@@ -163,6 +844,559 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inherited_edges() -> Result<()> {
+        let emit = |inherit: bool| -> Result<String> {
+            let style = |is_last_child: bool, edge: EdgeConfig| {
+                if inherit {
+                    ItemStyle::inherit(is_last_child)
+                } else {
+                    ItemStyle::new(is_last_child, edge)
+                }
+            };
+            let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+            printer.open_node(ItemStyle::non_last(EdgeConfig::double()), "foo")?;
+            printer.open_node(style(false, EdgeConfig::double()), "bar")?;
+            printer.open_node(ItemStyle::last(EdgeConfig::bullet()), "baz")?;
+            printer.open_node(style(true, EdgeConfig::bullet()), "qux")?;
+            printer.close_node()?;
+            printer.close_node()?;
+            printer.close_node()?;
+            printer.open_node(style(true, EdgeConfig::double()), "quux")?;
+            printer.close_node()?;
+            printer.close_node()?;
+            printer.open_node(style(true, EdgeConfig::Ascii), "corge")?;
+            printer.finalize()
+        };
+
+        assert_eq!(emit(true)?, emit(false)?);
+        Ok(())
+    }
+
+    #[test]
+    fn indent() -> Result<()> {
+        let got = emit_test_tree(EdgeConfig::Indent("  ".to_owned()))?;
+
+        let expected = ".\n  \
+                        foo\n    \
+                        bar\n      \
+                        baz\n\
+                        \n      \
+                        baz2\n    \
+                        qux\n      \
+                        quux\n  \
+                        corge\n  \
+                        grault\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn bullet() -> Result<()> {
+        let got = emit_test_tree(EdgeConfig::bullet())?;
+
+        let expected = ".\n\
+                        \u{2022} foo\n  \
+                        \u{2022} bar\n    \
+                        \u{2022} baz\n\
+                        \n      \
+                        baz2\n  \
+                        \u{2022} qux\n    \
+                        \u{2022} quux\n\
+                        \u{2022} corge\n\
+                        \u{2022} grault\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn presets() -> Result<()> {
+        let expected = ".\n\
+                        \u{2523}\u{2501}\u{2501} foo\n\
+                        \u{2503}   \u{2523}\u{2501}\u{2501} bar\n\
+                        \u{2503}   \u{2503}   \u{2517}\u{2501}\u{2501} baz\n\
+                        \u{2503}   \u{2503}\n\
+                        \u{2503}   \u{2503}       baz2\n\
+                        \u{2503}   \u{2517}\u{2501}\u{2501} qux\n\
+                        \u{2503}       \u{2517}\u{2501}\u{2501} quux\n\
+                        \u{2523}\u{2501}\u{2501} corge\n\
+                        \u{2517}\u{2501}\u{2501} grault\n";
+        assert_eq!(emit_test_tree(EdgeConfig::bold())?, expected);
+
+        let plus = emit_test_tree(EdgeConfig::ascii_plus())?;
+        let ascii = emit_test_tree(EdgeConfig::Ascii)?;
+        assert_eq!(plus, ascii.replace('`', "+").replace("|--", "+--"));
+
+        let expected = ".\n\
+                        | foo\n\
+                        | | bar\n\
+                        | | ` baz\n\
+                        | |\n\
+                        | |   baz2\n\
+                        | ` qux\n\
+                        |   ` quux\n\
+                        | corge\n\
+                        ` grault\n";
+        assert_eq!(emit_test_tree(EdgeConfig::compact_ascii())?, expected);
+
+        let rounded = emit_test_tree(EdgeConfig::rounded())?;
+        let single = emit_test_tree(EdgeConfig::UnicodeSingleWidth)?;
+        assert_eq!(rounded, single.replace('\u{2514}', "\u{2570}"));
+        Ok(())
+    }
+
+    #[test]
+    fn blank_lines_keep_ancestor_guides() -> Result<()> {
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        let edge = EdgeConfig::UnicodeSingleWidth;
+        printer.open_node(ItemStyle::non_last(edge.clone()), "foo")?;
+        printer.open_node(ItemStyle::last(edge.clone()), "bar\n\nbar2")?;
+        printer.open_node(ItemStyle::last(edge.clone()), "baz\n\nbaz2")?;
+        let got = printer.finalize()?;
+
+        let expected = "\u{251C}\u{2500}\u{2500} foo\n\
+                        \u{2502}   \u{2514}\u{2500}\u{2500} bar\n\
+                        \u{2502}\n\
+                        \u{2502}       bar2\n\
+                        \u{2502}       \u{2514}\u{2500}\u{2500} baz\n\
+                        \u{2502}\n\
+                        \u{2502}           baz2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn max_lines() -> Result<()> {
+        /// Prints the test tree with the given line limit.
+        fn print(max_lines: usize, builder: &mut TreeConfigBuilder) -> Result<String> {
+            let mut printer =
+                TreePrinter::new(String::new(), builder.max_lines(Some(max_lines)).build());
+            let edge = EdgeConfig::Ascii;
+            printer.open_node(ItemStyle::non_last(edge.clone()), "foo\nfoo2")?;
+            printer.open_node(ItemStyle::last(edge.clone()), "bar")?;
+            printer.close_node()?;
+            printer.close_node()?;
+            printer.open_node(ItemStyle::last(edge), "baz")?;
+            printer.finalize()
+        }
+
+        assert_eq!(
+            print(0, &mut TreeConfigBuilder::new())?,
+            "\u{2026} output truncated, 3 nodes not shown\n"
+        );
+        // A partially shown node is not counted.
+        assert_eq!(
+            print(1, TreeConfigBuilder::new().emit_trailing_newline(false))?,
+            "|-- foo\n\u{2026} output truncated, 2 nodes not shown"
+        );
+        assert_eq!(
+            print(3, &mut TreeConfigBuilder::new())?,
+            "|-- foo\n|   foo2\n|   `-- bar\n\u{2026} output truncated, 1 node not shown\n"
+        );
+        assert_eq!(
+            print(4, &mut TreeConfigBuilder::new())?,
+            "|-- foo\n|   foo2\n|   `-- bar\n`-- baz\n"
+        );
+        assert_eq!(
+            print(2, TreeConfigBuilder::new().bottom_up(true))?,
+            ",-- baz\n|   ,-- bar\n\u{2026} output truncated, 1 node not shown\n"
+        );
+        assert_eq!(
+            print(2, TreeConfigBuilder::new().mirrored(true))?,
+            "    foo --|\n   foo2   |\n\u{2026} output truncated, 2 nodes not shown\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn spacer_rows() -> Result<()> {
+        let opts = TreeConfigBuilder::new().emit_spacer_rows(true).build();
+        let mut buf = String::new();
+        let mut printer = TreePrinter::new(&mut buf, opts);
+        let edge = EdgeConfig::Ascii;
+        printer.open_node(ItemStyle::non_last(edge.clone()), "foo")?;
+        printer.open_node(ItemStyle::non_last(edge.clone()), "bar")?;
+        printer.open_node(ItemStyle::last(edge.clone()), "baz")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::non_last(edge.clone()), "qux")?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(edge.clone()), "quux")?;
+        printer.open_node(ItemStyle::last(edge.clone()), "corge")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(edge.clone()), "grault")?;
+        printer.finalize()?;
+
+        let expected = "|-- foo\n\
+                        |   |-- bar\n\
+                        |   |   `-- baz\n\
+                        |   |\n\
+                        |   |-- qux\n\
+                        |   `-- quux\n\
+                        |       `-- corge\n\
+                        |\n\
+                        `-- grault\n";
+        assert_eq!(buf, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn hanging_indent() -> Result<()> {
+        let opts = TreeConfigBuilder::new().hanging_indent(true).build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\nfoo2")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::UnicodeDoubleWidth), "bar\nbar2")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::bullet()), "baz\nbaz2")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- foo\n\
+                        |   foo2\n\
+                        |   \u{2514}\u{2500} bar\n\
+                        |      bar2\n\
+                        |      \u{2022} baz\n\
+                        |        baz2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn ambiguous_width() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .ambiguous_width(AmbiWidth::Wide)
+            .annotation_column(20)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        let edge = EdgeConfig::UnicodeSingleWidth;
+        printer.open_node(
+            ItemStyle::non_last(edge.clone()).with_annotation("1"),
+            "foo",
+        )?;
+        printer.open_node(ItemStyle::last(edge).with_annotation("2"), "bar\nbar2")?;
+        let got = printer.finalize()?;
+
+        let expected = "\u{251C}\u{2500}\u{2500} foo         1\n\
+                        \u{2502}     \u{2514}\u{2500}\u{2500} bar  2\n\
+                        \u{2502}            bar2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn flush_left() -> Result<()> {
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        let style = ItemStyle::non_last(EdgeConfig::Ascii).with_flush_left(true);
+        printer.open_node(style, "foo\n\n  foo2")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar\nbar2")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- foo\n\
+                        \n  \
+                        foo2\n\
+                        |   `-- bar\n\
+                        |       bar2\n\
+                        `-- baz\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn emphasized() -> Result<()> {
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        printer.open_node(
+            ItemStyle::non_last(EdgeConfig::Ascii).emphasized(),
+            "foo\nfoo2",
+        )?;
+        printer.open_node(ItemStyle::last(EdgeConfig::rounded()).emphasized(), "bar")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(EdgeConfig::bold()).emphasized(), "baz")?;
+        let got = printer.finalize()?;
+
+        let expected = "|== foo\n\
+                        |   foo2\n\
+                        |   \u{2515}\u{2501}\u{2501} bar\n\
+                        \u{2517}\u{2501}\u{2501} baz\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn line_gutter() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .line_gutter(Some(|line, _| format!("{}>", line)))
+            .emit_summary_footer(true)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n\nbar")?;
+        let got = printer.finalize()?;
+
+        assert_eq!(got, "0>`-- foo\n1>\n2>    bar\n\n0 branches, 1 leaf\n");
+        Ok(())
+    }
+
+    #[test]
+    fn right_aligned_annotations() -> Result<()> {
+        let opts = TreeConfigBuilder::new().annotation_column(20).build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        let style = ItemStyle::non_last(EdgeConfig::Ascii).with_annotation("12 KB");
+        printer.open_node(style, "foo\nfoo2")?;
+        let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("1.2 MB");
+        printer.open_node(style, "bar")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("too long");
+        printer.open_node(style, "a-very-long-label")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- foo        12 KB\n\
+                        |   foo2\n\
+                        |   `-- bar   1.2 MB\n\
+                        `-- a-very-long-label  too long\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn boxed() -> Result<()> {
+        let mut printer = TreePrinter::new(String::new(), TreeConfig::new());
+        let style = ItemStyle::non_last(EdgeConfig::Ascii).boxed();
+        printer.open_node(style, "foo\n\nab\n")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        let style = ItemStyle::last(EdgeConfig::bold()).boxed();
+        printer.open_node(style, "baz")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- +-----+\n\
+                        |   | foo |\n\
+                        |   |     |\n\
+                        |   | ab  |\n\
+                        |   +-----+\n\
+                        |   `-- bar\n\
+                        \u{2517}\u{2501}\u{2501} \u{250F}\u{2501}\u{2501}\u{2501}\u{2501}\u{2501}\u{2513}\n\
+                        \x20   \u{2503} baz \u{2503}\n\
+                        \x20   \u{2517}\u{2501}\u{2501}\u{2501}\u{2501}\u{2501}\u{251B}\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn box_chars_follow_edges() {
+        let corner = |edge: EdgeConfig| {
+            edge.box_chars()
+                .wrap("x", &TreeConfig::new())
+                .chars()
+                .next()
+        };
+        assert_eq!(corner(EdgeConfig::Ascii), Some('+'));
+        assert_eq!(corner(EdgeConfig::UnicodeSingleWidth), Some('\u{250C}'));
+        assert_eq!(corner(EdgeConfig::rounded()), Some('\u{256D}'));
+        assert_eq!(corner(EdgeConfig::bold()), Some('\u{250F}'));
+        assert_eq!(corner(EdgeConfig::double()), Some('\u{2554}'));
+        assert_eq!(corner(EdgeConfig::dotted()), Some('\u{250C}'));
+        assert_eq!(corner(EdgeConfig::ascii_plus()), Some('+'));
+    }
+
+    #[test]
+    fn max_width() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .max_width(Some(16))
+            .annotation_column(40)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        let style = ItemStyle::non_last(EdgeConfig::Ascii).with_annotation("1 KB");
+        printer.open_node(style, "annotated label\nsecond line of it")?;
+        let style = ItemStyle::last(EdgeConfig::Ascii).boxed();
+        printer.open_node(style, "boxed content")?;
+        printer.close_node()?;
+        printer.close_node()?;
+        let style = ItemStyle::last(EdgeConfig::Ascii).with_flush_left(true);
+        printer.open_node(style, "flush-left item\nflush-left continuation")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- annot\u{2026}  1 KB\n\
+                        |   second line\u{2026}\n\
+                        |   `-- +------+\n\
+                        |       | box\u{2026} |\n\
+                        |       +------+\n\
+                        `-- flush-left \u{2026}\n\
+                        flush-left cont\u{2026}\n";
+        assert_eq!(got, expected);
+        assert!(got.lines().all(|line| line.chars().count() <= 16));
+        Ok(())
+    }
+
+    #[test]
+    fn icon() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .max_width(Some(12))
+            .label_overflow(LabelOverflow::WordWrap)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        let style = ItemStyle::last(EdgeConfig::Ascii).with_icon("*");
+        printer.open_node(style, "aaa bbb ccc\n\nddd")?;
+        let got = printer.finalize()?;
+
+        let expected = "`-- * aaa\n\
+                        \x20     bbb\n\
+                        \x20     ccc\n\
+                        \n\
+                        \x20     ddd\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn summary_footer() -> Result<()> {
+        let mut opts = TreeConfigBuilder::new();
+        opts.emit_summary_footer(true);
+        let mut printer = TreePrinter::new(String::new(), opts.build());
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar")?;
+        let got = printer.finalize()?;
+
+        assert_eq!(got, "`-- foo\n    `-- bar\n\n1 branch, 1 leaf\n");
+        Ok(())
+    }
+
+    #[test]
+    fn summary_footer_without_trailing_newline() -> Result<()> {
+        let mut opts = TreeConfigBuilder::new();
+        opts.emit_summary_footer(true).emit_trailing_newline(false);
+        let mut printer = TreePrinter::new(String::new(), opts.build());
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "bar")?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz")?;
+        let got = printer.finalize()?;
+
+        assert_eq!(
+            got,
+            "`-- foo\n    |-- bar\n    `-- baz\n\n1 branch, 2 leaves"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_newline() -> Result<()> {
+        let mut opts = TreeConfigBuilder::new();
+        opts.emit_summary_footer(true).newline("\r\n");
+        let mut printer = TreePrinter::new(String::new(), opts.build());
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo\n\nbar")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "baz\r\n")?;
+        let got = printer.finalize()?;
+
+        let expected = "`-- foo\r\n\
+                        \r\n\
+                        \x20   bar\r\n\
+                        \x20   `-- baz\r\n\
+                        \r\n\
+                        1 branch, 1 leaf\r\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn cyclic_depth_edges() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .depth_edges(vec![EdgeConfig::Ascii, EdgeConfig::Indent("  ".to_owned())])
+            .cycle_depth_edges(true)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::by_depth(true), "foo")?;
+        printer.open_node(ItemStyle::by_depth(true), "bar")?;
+        printer.open_node(ItemStyle::by_depth(false), "baz")?;
+        printer.close_node()?;
+        // Explicit edge configs take precedence.
+        printer.open_node(ItemStyle::last(EdgeConfig::bullet()), "qux")?;
+        printer.close_node()?;
+        printer.open_node(ItemStyle::by_depth(true), "quux")?;
+        let got = printer.finalize()?;
+
+        let expected = "`-- foo\n      \
+                        bar\n      \
+                        |-- baz\n      \
+                        \u{2022} qux\n      \
+                        `-- quux\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn rainbow_edges() -> Result<()> {
+        use crate::ansi::Color;
+
+        let opts = TreeConfigBuilder::new()
+            .edge_color(Some(Color::Blue))
+            .rainbow_edges(vec![Color::Red, Color::Green, Color::Yellow])
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        printer.open_node(
+            ItemStyle::last(EdgeConfig::Ascii).with_edge_color(Color::Cyan),
+            "bar",
+        )?;
+        printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "baz\nbaz2")?;
+        let got = printer.finalize()?;
+
+        let expected = "\x1b[31m`--\x1b[39m foo\n    \
+                        \x1b[36m`--\x1b[39m bar\n        \
+                        \x1b[33m|--\x1b[39m baz\n        \
+                        \x1b[33m|\x1b[39m   baz2\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn auto_color_mode_without_terminal() -> Result<()> {
+        use crate::ansi::{Color, ColorMode};
+
+        if std::env::var_os("CLICOLOR_FORCE").is_some() {
+            // Colors are forced by the environment.
+            return Ok(());
+        }
+        let opts = TreeConfigBuilder::new()
+            .edge_color(Some(Color::Blue))
+            .color_mode(ColorMode::Auto)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts.clone());
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        assert_eq!(printer.finalize()?, "`-- foo\n");
+
+        let mut printer = IoTreePrinter::new(Vec::new(), opts);
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "foo")?;
+        assert_eq!(printer.finalize()?, b"`-- foo\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn dim_continuation_lines() -> Result<()> {
+        let opts = TreeConfigBuilder::new()
+            .dim_continuation_lines(true)
+            .build();
+        let mut printer = TreePrinter::new(String::new(), opts);
+        printer.open_node(ItemStyle::non_last(EdgeConfig::Ascii), "foo\n\nfoo2")?;
+        printer.open_node(ItemStyle::last(EdgeConfig::Ascii), "bar\nbar2")?;
+        let got = printer.finalize()?;
+
+        let expected = "|-- foo\n\
+                        |\n\
+                        |   \x1b[2mfoo2\x1b[22m\n\
+                        |   `-- bar\n\
+                        |       \x1b[2mbar2\x1b[22m\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+
     #[test]
     fn unicode_single_width() -> Result<()> {
         let got = emit_test_tree(EdgeConfig::UnicodeSingleWidth)?;
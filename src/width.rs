@@ -0,0 +1,540 @@
+//! Display width computation.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+#[cfg(feature = "unicode-width")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Display width oracle.
+///
+/// The tree printer uses this to measure the text of labels and edges, for example to align
+/// annotations and metadata columns.
+/// Implement this trait to follow the width rules of a specific terminal.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{EdgeConfig, ItemStyle, TreeConfigBuilder, TreePrinter, Width};
+///
+/// /// Treats every character as double width.
+/// #[derive(Debug)]
+/// struct Wide;
+///
+/// impl Width for Wide {
+///     fn str_width(&self, s: &str) -> usize {
+///         s.chars().count() * 2
+///     }
+/// }
+///
+/// let opts = TreeConfigBuilder::new()
+///     .width(Wide)
+///     .annotation_column(20)
+///     .build();
+/// let mut writer = TreePrinter::new(String::new(), opts);
+/// let style = ItemStyle::last(EdgeConfig::Ascii).with_annotation("1");
+/// writer.open_node(style, "foo")?;
+/// let buf = writer.finalize()?;
+///
+/// assert_eq!(buf, "`-- foo    1\n");
+/// # plaintextree::Result::Ok(())
+/// ```
+pub trait Width: fmt::Debug + Send + Sync {
+    /// Returns the display width of the given string.
+    fn str_width(&self, s: &str) -> usize;
+
+    /// Returns the display width of the given string, treating East Asian ambiguous width
+    /// characters as wide.
+    ///
+    /// This is used when [`AmbiWidth::Wide`] is set to the config.
+    /// The default implementation counts box drawing characters as double width, assuming that
+    /// `str_width()` counts them as single width.
+    ///
+    /// [`AmbiWidth::Wide`]: enum.AmbiWidth.html#variant.Wide
+    fn str_width_cjk(&self, s: &str) -> usize {
+        self.str_width(s) + s.chars().filter(|&c| is_box_drawing(c)).count()
+    }
+}
+
+/// Display width of East Asian ambiguous width characters.
+///
+/// Ambiguous width characters such as box drawing characters, Greek and Cyrillic letters, and
+/// bullets (U+2022) are rendered as double width in East Asian environments.
+/// See [UAX #11: East Asian Width][UAX-11] for detail.
+///
+/// [UAX-11]: https://unicode.org/reports/tr11/
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AmbiWidth {
+    /// Ambiguous width characters are single width.
+    #[default]
+    Narrow,
+    /// Ambiguous width characters are double width.
+    Wide,
+}
+
+/// Width oracle which counts `char`s.
+///
+/// This is correct for most of non-East-Asian text.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharCountWidth;
+
+impl Width for CharCountWidth {
+    fn str_width(&self, s: &str) -> usize {
+        s.chars().count()
+    }
+}
+
+/// Width oracle based on the [`unicode-width`] crate.
+///
+/// This treats East Asian wide characters and emoji as double width.
+///
+/// [`unicode-width`]: https://crates.io/crates/unicode-width
+#[cfg(feature = "unicode-width")]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnicodeWidth;
+
+#[cfg(feature = "unicode-width")]
+impl Width for UnicodeWidth {
+    fn str_width(&self, s: &str) -> usize {
+        unicode_width::UnicodeWidthStr::width(s)
+    }
+
+    fn str_width_cjk(&self, s: &str) -> usize {
+        unicode_width::UnicodeWidthStr::width_cjk(s)
+    }
+}
+
+/// Width oracle with the ambiguous width setting applied.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AmbiAwareWidth<'a> {
+    /// Underlying oracle.
+    width: &'a dyn Width,
+    /// Display width of ambiguous width characters.
+    ambiguous: AmbiWidth,
+}
+
+impl<'a> AmbiAwareWidth<'a> {
+    /// Creates a new `AmbiAwareWidth`.
+    pub(crate) fn new(width: &'a dyn Width, ambiguous: AmbiWidth) -> Self {
+        Self { width, ambiguous }
+    }
+}
+
+impl Width for AmbiAwareWidth<'_> {
+    fn str_width(&self, s: &str) -> usize {
+        match self.ambiguous {
+            AmbiWidth::Narrow => self.width.str_width(s),
+            AmbiWidth::Wide => self.width.str_width_cjk(s),
+        }
+    }
+
+    fn str_width_cjk(&self, s: &str) -> usize {
+        self.width.str_width_cjk(s)
+    }
+}
+
+/// Returns whether the character is a box drawing character.
+pub(crate) fn is_box_drawing(c: char) -> bool {
+    ('\u{2500}'..='\u{257F}').contains(&c)
+}
+
+/// Returns the default width oracle.
+///
+/// This is `UnicodeWidth` if `unicode-width` feature is enabled, and `CharCountWidth` otherwise.
+pub(crate) fn default_width() -> Arc<dyn Width> {
+    #[cfg(feature = "unicode-width")]
+    {
+        Arc::new(UnicodeWidth)
+    }
+    #[cfg(not(feature = "unicode-width"))]
+    {
+        Arc::new(CharCountWidth)
+    }
+}
+
+/// Returns the display width of the given string with the default width oracle.
+///
+/// Escape sequences are not counted.
+pub(crate) fn str_width(s: &str) -> usize {
+    str_width_ambi(s, AmbiWidth::Narrow)
+}
+
+/// Returns the display width of the given string with the default width oracle and the given
+/// width of ambiguous width characters.
+///
+/// Escape sequences are not counted.
+pub(crate) fn str_width_ambi(s: &str, ambiguous: AmbiWidth) -> usize {
+    #[cfg(feature = "unicode-width")]
+    let width = &UnicodeWidth;
+    #[cfg(not(feature = "unicode-width"))]
+    let width = &CharCountWidth;
+    visible_width(s, &AmbiAwareWidth::new(width, ambiguous))
+}
+
+/// Returns the length of the escape sequence at the start of the string, if any.
+///
+/// CSI sequences (such as SGR sequences `\x1b[...m`), OSC sequences terminated by BEL or ST, and
+/// other two-character escape sequences are recognized.
+fn escape_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\x1b')?;
+    let mut chars = rest.char_indices();
+    match chars.next() {
+        None => Some(1),
+        Some((_, '[')) => {
+            for (i, c) in chars {
+                if ('\x40'..='\x7e').contains(&c) {
+                    return Some(1 + i + 1);
+                }
+            }
+            Some(s.len())
+        }
+        Some((_, ']')) => {
+            let mut after_escape = false;
+            for (i, c) in chars {
+                if c == '\x07' || (after_escape && c == '\\') {
+                    return Some(1 + i + 1);
+                }
+                after_escape = c == '\x1b';
+            }
+            Some(s.len())
+        }
+        Some((_, c)) => Some(1 + c.len_utf8()),
+    }
+}
+
+/// Splits the string into escape sequences and grapheme clusters.
+///
+/// Without `unicode-width` feature, characters are used instead of grapheme clusters.
+/// The second element of each item is whether the unit is an escape sequence.
+pub(crate) fn units(s: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        if let Some(len) = escape_len(rest) {
+            let (unit, next) = rest.split_at(len);
+            rest = next;
+            return Some((unit, true));
+        }
+        #[cfg(feature = "unicode-width")]
+        let grapheme = rest.graphemes(true).next()?;
+        #[cfg(not(feature = "unicode-width"))]
+        let grapheme = {
+            let c = rest.chars().next()?;
+            &rest[..c.len_utf8()]
+        };
+        rest = &rest[grapheme.len()..];
+        Some((grapheme, false))
+    })
+}
+
+/// Returns the display width of the string, ignoring escape sequences.
+///
+/// Escape sequences such as SGR sequences in pre-styled labels have no width.
+pub(crate) fn visible_width(s: &str, width: &dyn Width) -> usize {
+    if !s.contains('\x1b') {
+        return width.str_width(s);
+    }
+    let visible = units(s)
+        .filter(|&(_, is_escape)| !is_escape)
+        .map(|(unit, _)| unit)
+        .collect::<String>();
+    width.str_width(&visible)
+}
+
+/// Expands tabs into spaces, with tab stops at every `tab_width` columns from the start of each
+/// line.
+///
+/// Tabs are kept as is if `tab_width` is zero.
+pub(crate) fn expand_tabs<'a>(s: &'a str, tab_width: usize, width: &dyn Width) -> Cow<'a, str> {
+    if tab_width == 0 || !s.contains('\t') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut expanded = String::with_capacity(s.len());
+    for (i, line) in s.split('\n').enumerate() {
+        if i != 0 {
+            expanded.push('\n');
+        }
+        let mut column = 0;
+        let mut pieces = line.split('\t').peekable();
+        while let Some(piece) = pieces.next() {
+            expanded.push_str(piece);
+            column += visible_width(piece, width);
+            if pieces.peek().is_some() {
+                let spaces = tab_width - column % tab_width;
+                expanded.extend(std::iter::repeat(' ').take(spaces));
+                column += spaces;
+            }
+        }
+    }
+    Cow::Owned(expanded)
+}
+
+/// Truncates the string to fit the given width, appending the ellipsis if truncated.
+///
+/// The string is cut at grapheme cluster boundaries, so combining sequences and emoji ZWJ
+/// sequences are never split.
+/// Escape sequences after the cut are kept, so that styles are reset properly.
+/// If the ellipsis itself is wider than the given width, the ellipsis is truncated without
+/// ellipsis.
+pub(crate) fn truncate<'a>(
+    s: &'a str,
+    max_width: usize,
+    ellipsis: &str,
+    width: &dyn Width,
+) -> Cow<'a, str> {
+    if visible_width(s, width) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let ellipsis_width = visible_width(ellipsis, width);
+    if ellipsis_width > max_width {
+        return Cow::Owned(truncate(ellipsis, max_width, "", width).into_owned());
+    }
+
+    let budget = max_width - ellipsis_width;
+    let mut used = 0;
+    let mut truncated = String::with_capacity(s.len() + ellipsis.len());
+    let mut cut = false;
+    for (unit, is_escape) in units(s) {
+        if is_escape {
+            truncated.push_str(unit);
+            continue;
+        }
+        if cut {
+            continue;
+        }
+        used += width.str_width(unit);
+        if used > budget {
+            truncated.push_str(ellipsis);
+            cut = true;
+            continue;
+        }
+        truncated.push_str(unit);
+    }
+    Cow::Owned(truncated)
+}
+
+/// Splits the line at the width limits, and returns the split lines.
+///
+/// The first split line fits `first_max`, and the others fit `rest_max` including the marker
+/// put at the start.
+/// Grapheme clusters are never split, and each line has at least one grapheme cluster.
+pub(crate) fn hard_wrap(
+    line: &str,
+    first_max: usize,
+    rest_max: usize,
+    marker: &str,
+    width: &dyn Width,
+) -> Vec<String> {
+    let rest_max = rest_max.saturating_sub(visible_width(marker, width));
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut has_grapheme = false;
+    for (grapheme, is_escape) in units(line) {
+        if is_escape {
+            current.push_str(grapheme);
+            continue;
+        }
+        let max_width = if lines.is_empty() {
+            first_max
+        } else {
+            rest_max
+        };
+        let grapheme_width = width.str_width(grapheme);
+        if has_grapheme && current_width + grapheme_width > max_width {
+            lines.push(std::mem::replace(&mut current, marker.to_owned()));
+            current_width = 0;
+        }
+        current.push_str(grapheme);
+        current_width += grapheme_width;
+        has_grapheme = true;
+    }
+    lines.push(current);
+    lines
+}
+
+/// Wraps the line at spaces to fit the given widths, and returns the wrapped lines.
+///
+/// The first wrapped line fits `first_max`, and the others fit `rest_max`.
+/// Words wider than the line are broken at grapheme cluster boundaries.
+pub(crate) fn wrap(
+    line: &str,
+    first_max: usize,
+    rest_max: usize,
+    width: &dyn Width,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut current_started = false;
+    for word in line.split(' ') {
+        let mut word = word;
+        loop {
+            let max_width = if lines.is_empty() {
+                first_max
+            } else {
+                rest_max
+            };
+            let word_width = visible_width(word, width);
+            let separator = if current_started { 1 } else { 0 };
+            if current_width + separator + word_width <= max_width {
+                if current_started {
+                    current.push(' ');
+                }
+                current.push_str(word);
+                current_width += separator + word_width;
+                current_started = true;
+                break;
+            }
+            if current_started {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+                current_started = false;
+                continue;
+            }
+
+            // The word is too wide even for an empty line.
+            // Take at least one grapheme to make progress.
+            let mut used = 0;
+            let mut end = 0;
+            let mut has_grapheme = false;
+            for (grapheme, is_escape) in units(word) {
+                if !is_escape {
+                    used += width.str_width(grapheme);
+                    if used > max_width && has_grapheme {
+                        break;
+                    }
+                    has_grapheme = true;
+                }
+                end += grapheme.len();
+            }
+            lines.push(word[..end].to_owned());
+            word = &word[end..];
+            if word.is_empty() {
+                break;
+            }
+        }
+    }
+    if current_started || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_count() {
+        assert_eq!(CharCountWidth.str_width("foo"), 3);
+        assert_eq!(CharCountWidth.str_width("\u{3042}\u{3044}"), 2);
+    }
+
+    #[test]
+    fn expand_tabs_per_line() {
+        let width = CharCountWidth;
+        assert_eq!(expand_tabs("foo", 4, &width), "foo");
+        assert_eq!(expand_tabs("\tfoo", 4, &width), "    foo");
+        assert_eq!(expand_tabs("ab\tc\td", 4, &width), "ab  c   d");
+        assert_eq!(expand_tabs("abcd\te\n\tf", 4, &width), "abcd    e\n    f");
+        assert_eq!(expand_tabs("a\tb", 0, &width), "a\tb");
+    }
+
+    #[test]
+    fn truncate_graphemes() {
+        let width = CharCountWidth;
+        assert_eq!(truncate("foobar", 6, "\u{2026}", &width), "foobar");
+        assert_eq!(truncate("foobar", 4, "\u{2026}", &width), "foo\u{2026}");
+        assert_eq!(truncate("foobar", 4, "...", &width), "f...");
+        assert_eq!(truncate("foobar", 2, "...", &width), "..");
+        #[cfg(feature = "unicode-width")]
+        {
+            // `e` with a combining acute accent is never split.
+            assert_eq!(truncate("cafe\u{301}s", 5, "~", &width), "caf~");
+            // Emoji ZWJ sequence (family) is never split.
+            let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+            assert_eq!(truncate(&format!("a{}b", family), 5, "", &width), "a");
+        }
+        #[cfg(not(feature = "unicode-width"))]
+        assert_eq!(truncate("cafe\u{301}s", 5, "~", &width), "cafe~");
+    }
+
+    #[test]
+    fn wrap_words() {
+        let width = CharCountWidth;
+        assert_eq!(wrap("", 5, 5, &width), vec![""]);
+        assert_eq!(wrap("foo bar", 7, 7, &width), vec!["foo bar"]);
+        assert_eq!(wrap("foo bar baz", 8, 4, &width), vec!["foo bar", "baz"]);
+        assert_eq!(wrap("a bb ccc", 1, 4, &width), vec!["a", "bb", "ccc"]);
+        assert_eq!(
+            wrap("abcdefg hi", 3, 3, &width),
+            vec!["abc", "def", "g", "hi"]
+        );
+        assert_eq!(wrap("x  y", 10, 10, &width), vec!["x  y"]);
+        // Grapheme clusters are kept even if the width is zero.
+        #[cfg(feature = "unicode-width")]
+        assert_eq!(
+            wrap("e\u{301}e\u{301}", 0, 0, &width),
+            vec!["e\u{301}", "e\u{301}"]
+        );
+    }
+
+    #[test]
+    fn hard_wrap_graphemes() {
+        let width = CharCountWidth;
+        assert_eq!(hard_wrap("", 3, 3, ">", &width), vec![""]);
+        assert_eq!(hard_wrap("abc", 3, 3, ">", &width), vec!["abc"]);
+        assert_eq!(
+            hard_wrap("abcdefgh", 3, 4, ">", &width),
+            vec!["abc", ">def", ">gh"]
+        );
+        #[cfg(feature = "unicode-width")]
+        assert_eq!(
+            hard_wrap("ae\u{301}b", 1, 1, ">", &width),
+            vec!["a", ">e\u{301}", ">b"]
+        );
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let width = CharCountWidth;
+        assert_eq!(visible_width("\x1b[1;31mfoo\x1b[0m", &width), 3);
+        assert_eq!(
+            visible_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\", &width),
+            4
+        );
+        assert_eq!(
+            truncate("\x1b[31mfoobar\x1b[0m", 4, "\u{2026}", &width),
+            "\x1b[31mfoo\u{2026}\x1b[0m"
+        );
+        assert_eq!(
+            wrap("\x1b[1mfoo\x1b[0m bar", 3, 3, &width),
+            vec!["\x1b[1mfoo\x1b[0m", "bar"]
+        );
+        assert_eq!(
+            hard_wrap("\x1b[31mabcd\x1b[0m", 2, 3, ">", &width),
+            vec!["\x1b[31mab", ">cd\x1b[0m"]
+        );
+        assert_eq!(
+            expand_tabs("\x1b[1ma\x1b[0m\tb", 4, &width),
+            "\x1b[1ma\x1b[0m   b"
+        );
+    }
+
+    #[test]
+    fn ambiguous_width() {
+        let narrow = AmbiAwareWidth::new(&CharCountWidth, AmbiWidth::Narrow);
+        let wide = AmbiAwareWidth::new(&CharCountWidth, AmbiWidth::Wide);
+        assert_eq!(narrow.str_width("\u{251C}\u{2500} foo"), 6);
+        assert_eq!(wide.str_width("\u{251C}\u{2500} foo"), 8);
+        assert_eq!(visible_width("\x1b[1m\u{2502}\x1b[0m", &wide), 2);
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn unicode_width() {
+        assert_eq!(UnicodeWidth.str_width("foo"), 3);
+        assert_eq!(UnicodeWidth.str_width("\u{3042}\u{3044}"), 4);
+        assert_eq!(UnicodeWidth.str_width("e\u{301}"), 1);
+    }
+}
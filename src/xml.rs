@@ -0,0 +1,193 @@
+//! Trees of XML documents.
+
+use roxmltree::{Document, Node};
+
+use crate::tree_node::TreeNode;
+
+/// Options for rendering XML documents as trees.
+#[derive(Debug, Clone)]
+pub struct XmlTreeOptions {
+    /// Maximum number of characters of texts and attribute values to show.
+    max_text_chars: Option<usize>,
+    /// Whether to skip text nodes consisting only of whitespaces.
+    skip_whitespace: bool,
+}
+
+impl Default for XmlTreeOptions {
+    fn default() -> Self {
+        Self {
+            max_text_chars: None,
+            skip_whitespace: true,
+        }
+    }
+}
+
+impl XmlTreeOptions {
+    /// Creates a new default `XmlTreeOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of characters of texts and attribute values to show.
+    ///
+    /// Longer texts are elided with `…` (U+2026 HORIZONTAL ELLIPSIS) at the end.
+    ///
+    /// Default is `None`, that is, texts are shown entirely.
+    pub fn max_text_chars(&mut self, max: Option<usize>) -> &mut Self {
+        self.max_text_chars = max;
+        self
+    }
+
+    /// Sets whether to skip text nodes consisting only of whitespaces.
+    ///
+    /// Such nodes are usually indentation between elements.
+    ///
+    /// Default is `true`.
+    pub fn skip_whitespace(&mut self, v: bool) -> &mut Self {
+        self.skip_whitespace = v;
+        self
+    }
+
+    /// Returns the quoted text, elided if it is too long.
+    fn quote(&self, text: &str) -> String {
+        match self.max_text_chars {
+            Some(max) if text.chars().nth(max).is_some() => {
+                let elided = text.chars().take(max).collect::<String>();
+                let mut quoted = format!("{:?}", elided);
+                quoted.insert(quoted.len() - 1, '\u{2026}');
+                quoted
+            }
+            _ => format!("{:?}", text),
+        }
+    }
+
+    /// Returns the tree node for the element.
+    fn element(&self, element: Node<'_, '_>) -> TreeNode {
+        let mut node = TreeNode::new(qualified_name(element));
+        for attr in element.attributes() {
+            let name = match attr.namespace().and_then(|ns| element.lookup_prefix(ns)) {
+                Some(prefix) => format!("@{}:{}", prefix, attr.name()),
+                None => format!("@{}", attr.name()),
+            };
+            let mut leaf = TreeNode::new(name);
+            leaf.set_annotation(Some(self.quote(attr.value())));
+            node.push_child(leaf);
+        }
+        for child in element.children() {
+            if child.is_element() {
+                node.push_child(self.element(child));
+            } else if let Some(text) = child.text().filter(|_| child.is_text()) {
+                if self.skip_whitespace && text.trim().is_empty() {
+                    continue;
+                }
+                node.push_child(TreeNode::new(self.quote(text)));
+            }
+        }
+        node
+    }
+}
+
+/// Returns the name of the element with the namespace prefix.
+fn qualified_name(element: Node<'_, '_>) -> String {
+    let tag = element.tag_name();
+    match tag.namespace().and_then(|ns| element.lookup_prefix(ns)) {
+        Some(prefix) if !prefix.is_empty() => format!("{}:{}", prefix, tag.name()),
+        _ => tag.name().to_owned(),
+    }
+}
+
+/// Creates a tree of the XML element.
+///
+/// Child elements are branches labeled with their names.
+/// Attributes are leaves labeled with `@` and their names, and annotated with their quoted
+/// values.
+/// Text nodes are leaves labeled with their quoted texts.
+/// Comments and processing instructions are not shown.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{xml_element_tree, RenderConfig, XmlTreeOptions};
+///
+/// let doc = roxmltree::Document::parse(r#"<a><b id="x">text</b></a>"#)?;
+/// let tree = xml_element_tree(doc.root_element(), &XmlTreeOptions::new());
+///
+/// let expected = "\
+/// a
+/// `-- b
+///     |-- @id  \"x\"
+///     `-- \"text\"
+/// ";
+/// assert_eq!(tree.render(String::new(), &RenderConfig::new())?, expected);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn xml_element_tree(element: Node<'_, '_>, opts: &XmlTreeOptions) -> TreeNode {
+    opts.element(element)
+}
+
+/// Parses the XML document and creates a tree of the root element.
+///
+/// See [`xml_element_tree()`] for the tree.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{xml_document_tree, RenderConfig, XmlTreeOptions};
+///
+/// let doc = r##"<?xml version="1.0"?>
+/// <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+///   <!-- icons -->
+///   <title>Icons for the navigation bar</title>
+///   <use xlink:href="#home" x="0"/>
+/// </svg>
+/// "##;
+/// let tree = xml_document_tree(doc, XmlTreeOptions::new().max_text_chars(Some(9)))?;
+///
+/// let expected = "\
+/// svg
+/// |-- title
+/// |   `-- \"Icons for\u{2026}\"
+/// `-- use
+///     |-- @xlink:href  \"#home\"
+///     `-- @x           \"0\"
+/// ";
+/// assert_eq!(tree.render(String::new(), &RenderConfig::new())?, expected);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`xml_element_tree()`]: fn.xml_element_tree.html
+pub fn xml_document_tree(src: &str, opts: &XmlTreeOptions) -> Result<TreeNode, roxmltree::Error> {
+    let doc = Document::parse(src)?;
+    Ok(opts.element(doc.root_element()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_texts() -> Result<(), roxmltree::Error> {
+        let src = "<p>\n  <b>bold</b> and\n  <i> </i>\n</p>";
+        let got = xml_document_tree(src, &XmlTreeOptions::new())?.to_string();
+        let expected = "p\n\
+                        |-- b\n\
+                        |   `-- \"bold\"\n\
+                        |-- \" and\\n  \"\n\
+                        `-- i\n";
+        assert_eq!(got, expected);
+
+        let mut opts = XmlTreeOptions::new();
+        opts.skip_whitespace(false);
+        let got = xml_document_tree(src, &opts)?.to_string();
+        let expected = "p\n\
+                        |-- \"\\n  \"\n\
+                        |-- b\n\
+                        |   `-- \"bold\"\n\
+                        |-- \" and\\n  \"\n\
+                        |-- i\n\
+                        |   `-- \" \"\n\
+                        `-- \"\\n\"\n";
+        assert_eq!(got, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! Trees of YAML values.
+//!
+//! Anchors and aliases are not shown as reference markers.
+//! They are resolved by `serde_yaml` before the values reach here, and showing them requires
+//! the event stream of the parser, which `serde_yaml` does not expose.
+
+use serde_yaml::Value;
+
+use crate::tree_node::TreeNode;
+
+/// Options for rendering YAML values as trees.
+#[derive(Debug, Clone)]
+pub struct YamlTreeOptions {
+    /// Label of the root node.
+    root_label: String,
+}
+
+impl Default for YamlTreeOptions {
+    fn default() -> Self {
+        Self {
+            root_label: ".".to_owned(),
+        }
+    }
+}
+
+impl YamlTreeOptions {
+    /// Creates a new default `YamlTreeOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the label of the root node.
+    ///
+    /// Default is `"."`.
+    pub fn root_label(&mut self, label: impl Into<String>) -> &mut Self {
+        self.root_label = label.into();
+        self
+    }
+}
+
+/// Returns whether the value has children in the tree.
+fn is_branch(value: &Value) -> bool {
+    match untagged(value) {
+        Value::Sequence(items) => !items.is_empty(),
+        Value::Mapping(entries) => !entries.is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the single-line YAML representation of the scalar value.
+fn scalar(value: &Value) -> String {
+    match serde_yaml::to_string(value) {
+        Ok(s) if !s.trim_end().contains('\n') => s.trim_end().to_owned(),
+        // Multiline strings are emitted as block scalars.
+        _ => match value {
+            Value::String(s) => format!("{:?}", s),
+            Value::Tagged(tagged) => format!("{} {}", tagged.tag, scalar(&tagged.value)),
+            _ => format!("{:?}", value),
+        },
+    }
+}
+
+/// Returns the tree node for the value with the given name.
+fn node(name: String, value: &Value) -> TreeNode {
+    if !is_branch(value) {
+        return TreeNode::new(format!("{}: {}", name, scalar(value)));
+    }
+    let mut label = name;
+    let mut value = value;
+    while let Value::Tagged(tagged) = value {
+        label.push_str(&format!(" {}", tagged.tag));
+        value = &tagged.value;
+    }
+    let mut node = TreeNode::new(label);
+    match value {
+        Value::Sequence(items) => {
+            for (i, item) in items.iter().enumerate() {
+                node.push_child(self::node(format!("[{}]", i), item));
+            }
+        }
+        Value::Mapping(entries) => {
+            for (key, item) in entries {
+                node.push_child(self::node(scalar(key), item));
+            }
+        }
+        _ => unreachable!("Should never happen: branches are sequences or mappings"),
+    }
+    node
+}
+
+/// Returns the value without tags.
+fn untagged(mut value: &Value) -> &Value {
+    while let Value::Tagged(tagged) = value {
+        value = &tagged.value;
+    }
+    value
+}
+
+/// Creates a tree of the YAML value.
+///
+/// Mappings and sequences are branches, and scalars are leaves.
+/// Each node is labeled with the key or the `[index]` of the value, and leaves also have the
+/// YAML representations of the values after `: `.
+/// Tags are shown after the keys of branches, and before the values of leaves.
+/// Empty mappings and sequences are leaves with `{}` and `[]`.
+///
+/// Anchors and aliases are not supported yet.
+/// `serde_yaml` resolves aliases (`*name`) into copies of the anchored values (`&name`) when
+/// parsing, and does not expose the anchor names.
+/// Aliased values are shown as copies, and neither `&name` nor `*name` markers are shown.
+///
+/// # Examples
+///
+/// ```
+/// use plaintextree::{yaml_value_tree, YamlTreeOptions};
+///
+/// let doc = "\
+/// name: web
+/// ports: [80, 443]
+/// env: !Secret
+///   token: '0123'
+/// volumes: []
+/// ";
+/// let value: serde_yaml::Value = serde_yaml::from_str(doc)?;
+/// let tree = yaml_value_tree(&value, YamlTreeOptions::new().root_label("compose.yaml"));
+///
+/// let expected = "\
+/// compose.yaml
+/// |-- name: web
+/// |-- ports
+/// |   |-- [0]: 80
+/// |   `-- [1]: 443
+/// |-- env !Secret
+/// |   `-- token: '0123'
+/// `-- volumes: []
+/// ";
+/// assert_eq!(tree.to_string(), expected);
+/// # Ok::<_, serde_yaml::Error>(())
+/// ```
+pub fn yaml_value_tree(value: &Value, opts: &YamlTreeOptions) -> TreeNode {
+    node(opts.root_label.clone(), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        let value: Value =
+            serde_yaml::from_str("[~, true, 1.5, '12', \"a\\nb\", !Id 42]").expect("valid YAML");
+        let got = yaml_value_tree(&value, &YamlTreeOptions::new()).to_string();
+        let expected = ".\n\
+                        |-- [0]: null\n\
+                        |-- [1]: true\n\
+                        |-- [2]: 1.5\n\
+                        |-- [3]: '12'\n\
+                        |-- [4]: \"a\\nb\"\n\
+                        `-- [5]: !Id 42\n";
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn aliases_as_copies() {
+        let doc = "a: &a {b: 1}\nc: *a\n";
+        let value: Value = serde_yaml::from_str(doc).expect("valid YAML");
+        let got = yaml_value_tree(&value, &YamlTreeOptions::new()).to_string();
+        let expected = ".\n\
+                        |-- a\n\
+                        |   `-- b: 1\n\
+                        `-- c\n    \
+                        `-- b: 1\n";
+        assert_eq!(got, expected);
+    }
+}